@@ -1,5 +1,6 @@
 use cortex_m::{delay::Delay as CortexDelay, peripheral::SYST};
 use embedded_hal::blocking::delay::*;
+use embedded_hal_1::delay::DelayNs;
 
 static mut DELAY: Option<CortexDelay> = None;
 
@@ -29,3 +30,12 @@ impl DelayUs<u32> for Delay {
         unsafe { &mut DELAY }.as_mut().unwrap().delay_us(us)
     }
 }
+
+impl DelayNs for Delay {
+    fn delay_ns(&mut self, ns: u32) {
+        unsafe { &mut DELAY }
+            .as_mut()
+            .unwrap()
+            .delay_us(ns.div_ceil(1000))
+    }
+}
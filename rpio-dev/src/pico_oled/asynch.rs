@@ -0,0 +1,58 @@
+use super::cmd::{self, Cmd};
+use rpio_spi::{AsyncSpiDevice, OutputPin};
+
+/// The async counterpart of [`PicoOled`](super::PicoOled), sending the same
+/// commands.
+pub struct AsyncPicoOled<SPI: AsyncSpiDevice, DCMD: OutputPin> {
+    buf: [u128; 64],
+    spi: SPI,
+    dcmd: DCMD,
+}
+
+impl<SPI: AsyncSpiDevice, DCMD: OutputPin> AsyncPicoOled<SPI, DCMD> {
+    pub fn new(spi: SPI, dcmd: DCMD) -> Self {
+        Self {
+            buf: [0; 64],
+            spi,
+            dcmd,
+        }
+    }
+
+    /// Send the bytes of an encoded command.
+    pub async fn send(&mut self, mut cmd: Cmd) {
+        self.dcmd.set_low().ok();
+        self.spi.transfer(cmd.bytes_mut()).await.ok();
+    }
+
+    pub async fn init(&mut self) {
+        for cmd in cmd::init() {
+            self.send(cmd).await;
+        }
+
+        self.clear().await;
+    }
+
+    pub async fn clear(&mut self) {
+        for y in 0u8..64 {
+            self.draw_scan(y, 0).await;
+        }
+    }
+
+    /// Draw the scans of the frame buffer which have changed since the last
+    /// update.
+    pub async fn update(&mut self, fb: &[u128]) {
+        for (offset, &scan) in fb.iter().enumerate() {
+            let y = 63 - (offset % 64);
+            if self.buf[y] != scan {
+                self.buf[y] = scan;
+                self.draw_scan(y as u8, scan).await;
+            }
+        }
+    }
+
+    async fn draw_scan(&mut self, y: u8, data: u128) {
+        self.send(Cmd::col(y)).await;
+        self.dcmd.set_high().ok();
+        self.spi.transfer(&mut cmd::scan(data)).await.ok();
+    }
+}
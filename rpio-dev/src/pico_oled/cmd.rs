@@ -0,0 +1,187 @@
+#[repr(u8)]
+#[derive(Debug, Copy, Clone)]
+pub enum Mode {
+    Page = 0x20,
+    Vertical = 0x21,
+}
+
+impl Mode {
+    fn to_cmd(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Command bytes for the display controller, sent with the data/command pin
+/// low.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cmd {
+    bytes: [u8; 2],
+    len: usize,
+}
+
+impl Cmd {
+    const fn one(cmd: u8) -> Self {
+        Self {
+            bytes: [cmd, 0],
+            len: 1,
+        }
+    }
+
+    const fn two(cmd: u8, arg: u8) -> Self {
+        Self {
+            bytes: [cmd, arg],
+            len: 2,
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    pub fn bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes[..self.len]
+    }
+
+    /// The name of the command, for tracing.
+    pub fn name(&self) -> &'static str {
+        match self.bytes[0] {
+            0x00..=0x0F => "LowerColAddr",
+            0x10..=0x17 => "HigherColAddr",
+            0x20 | 0x21 => "AddressingMode",
+            0x81 => "Contrast",
+            0xA0 | 0xA1 => "Remap",
+            0xA4 | 0xA5 => "ForceDisplayOn",
+            0xA6 | 0xA7 => "Reversed",
+            0xA8 => "MultiplexRatio",
+            0xAD => "DcConverter",
+            0xAE => "DisplayOff",
+            0xAF => "DisplayOn",
+            0xB0..=0xBF => "PageAddr",
+            0xC0 | 0xC8 => "ReversedScan",
+            0xD3 => "DisplayOffset",
+            0xD5 => "DclkOscFreq",
+            0xD9 => "PreChargePeriod",
+            0xDB => "VcomDeselectLevel",
+            0xDC => "DisplayStart",
+            _ => "Unknown",
+        }
+    }
+
+    pub fn display_start(start: u8) -> Self {
+        Self::two(0xDC, start)
+    }
+
+    pub fn addressing_mode(mode: Mode) -> Self {
+        Self::one(mode.to_cmd())
+    }
+
+    pub fn contrast(contrast: u8) -> Self {
+        Self::two(0x81, contrast)
+    }
+
+    pub fn remap(remap: bool) -> Self {
+        Self::one(0xA0 | remap as u8)
+    }
+
+    pub fn multiplex_ratio(ratio: u8) -> Self {
+        Self::two(0xA8, ratio)
+    }
+
+    pub fn display_on() -> Self {
+        Self::one(0xAF)
+    }
+
+    pub fn display_off() -> Self {
+        Self::one(0xAE)
+    }
+
+    pub fn force_display_on(force: bool) -> Self {
+        Self::one(0xA4 | force as u8)
+    }
+
+    /// Reversed colours
+    pub fn reversed(reversed: bool) -> Self {
+        Self::one(0xA6 + reversed as u8)
+    }
+
+    pub fn display_offset(offset: u8) -> Self {
+        Self::two(0xD3, offset)
+    }
+
+    pub fn reversed_scan(reversed: bool) -> Self {
+        Self::one(0xC0 & ((reversed as u8) << 3))
+    }
+
+    pub fn dclk_osc_freq(setting: u8) -> Self {
+        Self::two(0xD5, setting)
+    }
+
+    pub fn pre_charge_period(setting: u8) -> Self {
+        Self::two(0xD9, setting)
+    }
+
+    pub fn vcom_deselect_level(level: u8) -> Self {
+        Self::two(0xDB, level)
+    }
+
+    pub fn dc_converter(setting: u8) -> Self {
+        Self::two(0xAD, 0x80 | setting & 0x0F)
+    }
+
+    pub fn page_addr(page: u8) -> Self {
+        Self::one(0xB0 | page & 0x0F)
+    }
+
+    pub fn lower_col_addr(col: u8) -> Self {
+        Self::one(col & 0x0F)
+    }
+
+    pub fn higher_col_addr(col: u8) -> Self {
+        Self::one(0x10 | col & 0x7)
+    }
+
+    pub fn col(col: u8) -> Self {
+        Self::two(col & 0x0F, 0x10 | (col >> 4) & 0x7)
+    }
+}
+
+/// The commands sent by `init`, before the display is cleared.
+pub fn init() -> [Cmd; 13] {
+    // Based off the 'example code' for the device.
+    // Need to figure out what all of this does.
+    // self.cmd(0xAE); //  #turn off OLED display
+    // self.cmd(0x00); //    #set lower column address
+    // self.cmd(0x10); //    #set higher column address
+    // self.cmd(0xB0); //    #set page address
+
+    // I do not understand these, but it doesn't seem to matter
+    // self.cmd(0xad); //     #set charge pump enable
+    // self.cmd(0x8a); //     #Set DC-DC enable (a=0:disable; a=1:enable)
+
+    [
+        Cmd::display_start(0),
+        Cmd::contrast(0x80),
+        Cmd::addressing_mode(Mode::Vertical),
+        Cmd::remap(false),
+        Cmd::multiplex_ratio(0x3f),
+        Cmd::reversed(false),
+        Cmd::reversed_scan(true),
+        Cmd::dclk_osc_freq(0x41),
+        Cmd::pre_charge_period(0x22),
+        Cmd::vcom_deselect_level(0x35),
+        Cmd::display_on(),
+        Cmd::display_offset(0x60),
+        Cmd::force_display_on(false),
+    ]
+}
+
+/// Encode a scan as display data.
+pub fn scan(data: u128) -> [u8; 16] {
+    let mut bytes = data.to_be_bytes();
+
+    for byte in bytes.iter_mut() {
+        *byte = byte.reverse_bits();
+    }
+
+    bytes
+}
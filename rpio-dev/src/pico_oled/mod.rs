@@ -1,6 +1,7 @@
 mod ascii;
 mod bfb;
 mod boled;
+mod cmd;
 mod fb;
 mod oled;
 mod print;
@@ -8,8 +9,15 @@ mod scaled;
 pub mod screen;
 mod types;
 
+#[cfg(feature = "async")]
+mod asynch;
+
+#[cfg(feature = "async")]
+pub use asynch::AsyncPicoOled;
+
+pub use cmd::{Cmd, Mode};
 pub use fb::FrameBuf;
-pub use oled::PicoOled;
+pub use oled::{PicoOled, FRAME_LEN};
 pub use print::*;
 pub use scaled::ScaledBuf;
 pub use screen::Screen;
@@ -1,5 +1,11 @@
+use super::cmd::{self, Cmd, Mode};
 use super::types::Display;
-use rpio_spi::{OutputPin, SpiDevice};
+use embedded_hal_1::spi::ErrorKind as HalKind;
+use rpio_spi::{BackgroundTransfer, Error, ErrorKind, OutputPin, Result, SpiDevice};
+
+/// The size in bytes of a full frame, as sent by
+/// [`start_frame`](PicoOled::start_frame).
+pub const FRAME_LEN: usize = 1024;
 
 pub struct PicoOled<SPI: SpiDevice, DCMD: OutputPin> {
     buf: [u128; 64],
@@ -7,25 +13,6 @@ pub struct PicoOled<SPI: SpiDevice, DCMD: OutputPin> {
     dcmd: DCMD,
 }
 
-#[repr(u8)]
-#[derive(Debug, Copy, Clone)]
-pub enum Mode {
-    Page = 0x20,
-    Vertical = 0x21,
-}
-
-impl Mode {
-    fn to_cmd(self) -> u8 {
-        self as u8
-    }
-}
-
-#[repr(u8)]
-#[derive(Debug, Copy, Clone)]
-enum Cmd {
-    SetDisplayStart,
-}
-
 impl<SPI: SpiDevice, DCMD: OutputPin> PicoOled<SPI, DCMD> {
     pub fn new(spi: SPI, dcmd: DCMD) -> Self {
         Self {
@@ -47,113 +34,100 @@ impl<SPI: SpiDevice, DCMD: OutputPin> PicoOled<SPI, DCMD> {
         self
     }
 
+    /// Send the bytes of an encoded command.
+    pub fn send(&mut self, cmd: Cmd) -> &mut Self {
+        self.spi.annotate(cmd.name());
+
+        for &byte in cmd.bytes() {
+            self.cmd(byte);
+        }
+
+        self
+    }
+
     pub fn set_display_start(&mut self, start: u8) -> &mut Self {
-        self.cmd(0xDC).cmd(start)
+        self.send(Cmd::display_start(start))
     }
 
     pub fn set_addressing_mode(&mut self, mode: Mode) -> &mut Self {
-        self.cmd(mode.to_cmd())
+        self.send(Cmd::addressing_mode(mode))
     }
 
     pub fn set_contrast(&mut self, contrast: u8) -> &mut Self {
-        self.cmd(0x81).cmd(contrast)
+        self.send(Cmd::contrast(contrast))
     }
 
     pub fn set_remap(&mut self, remap: bool) -> &mut Self {
-        self.cmd(0xA0 | remap as u8)
+        self.send(Cmd::remap(remap))
     }
 
     pub fn set_multiplex_ratio(&mut self, ratio: u8) -> &mut Self {
-        self.cmd(0xA8).cmd(ratio)
+        self.send(Cmd::multiplex_ratio(ratio))
     }
 
     pub fn display_on(&mut self) -> &mut Self {
-        self.cmd(0xAF)
+        self.send(Cmd::display_on())
     }
 
     pub fn display_off(&mut self) -> &mut Self {
-        self.cmd(0xAE)
+        self.send(Cmd::display_off())
     }
 
     pub fn force_display_on(&mut self, force: bool) -> &mut Self {
-        self.cmd(0xA4 | force as u8)
+        self.send(Cmd::force_display_on(force))
     }
 
     /// Set reversed colours
     pub fn set_reversed(&mut self, reversed: bool) -> &mut Self {
-        self.cmd(0xA6 + reversed as u8)
+        self.send(Cmd::reversed(reversed))
     }
 
     pub fn set_display_offset(&mut self, offset: u8) -> &mut Self {
-        self.cmd(0xD3).cmd(offset)
+        self.send(Cmd::display_offset(offset))
     }
 
     pub fn set_reversed_scan(&mut self, reversed: bool) -> &mut Self {
-        self.cmd(0xC0 & ((reversed as u8) << 3))
+        self.send(Cmd::reversed_scan(reversed))
     }
 
     pub fn set_dclk_osc_freq(&mut self, setting: u8) -> &mut Self {
-        self.cmd(0xD5).cmd(setting)
+        self.send(Cmd::dclk_osc_freq(setting))
     }
 
     pub fn set_pre_charge_period(&mut self, setting: u8) -> &mut Self {
-        self.cmd(0xD9).cmd(setting)
+        self.send(Cmd::pre_charge_period(setting))
     }
 
     pub fn set_vcom_deselect_level(&mut self, level: u8) -> &mut Self {
-        self.cmd(0xDB).cmd(level)
+        self.send(Cmd::vcom_deselect_level(level))
     }
 
     pub fn set_dc_converter(&mut self, setting: u8) -> &mut Self {
-        self.cmd(0xAD).cmd(0x80 | setting & 0x0F)
+        self.send(Cmd::dc_converter(setting))
     }
 
     pub fn init(&mut self) {
-        // Based off the 'example code' for the device.
-        // Need to figure out what all of this does.
-        // self.cmd(0xAE); //  #turn off OLED display
-        // self.cmd(0x00); //    #set lower column address
-        // self.cmd(0x10); //    #set higher column address
-        // self.cmd(0xB0); //    #set page address
-
-        self.set_display_start(0);
-        self.set_contrast(0x80);
-
-        self.set_addressing_mode(Mode::Vertical);
-        self.set_remap(false);
-        self.set_multiplex_ratio(0x3f);
-
-        self.set_reversed(false);
-        self.set_reversed_scan(true);
-        self.set_dclk_osc_freq(0x41);
-
-        self.set_pre_charge_period(0x22);
-        self.set_vcom_deselect_level(0x35);
-
-        // I do not understand these, but it doesn't seem to matter
-        // self.cmd(0xad); //     #set charge pump enable
-        // self.cmd(0x8a); //     #Set DC-DC enable (a=0:disable; a=1:enable)
-
-        self.display_on();
-        self.set_display_offset(0x60);
-        self.force_display_on(false);
+        for cmd in cmd::init() {
+            self.send(cmd);
+        }
+
         self.clear();
     }
 
     pub fn set_page_addr(&mut self, page: u8) -> &mut Self {
-        self.cmd(0xB0 | page & 0x0F)
+        self.send(Cmd::page_addr(page))
     }
 
     pub fn set_lower_col_addr(&mut self, col: u8) -> &mut Self {
-        self.cmd(col & 0x0F)
+        self.send(Cmd::lower_col_addr(col))
     }
 
     pub fn set_higher_col_addr(&mut self, col: u8) -> &mut Self {
-        self.cmd(0x10 | col & 0x7)
+        self.send(Cmd::higher_col_addr(col))
     }
 
     pub fn set_col(&mut self, col: u8) -> &mut Self {
-        self.set_lower_col_addr(col).set_higher_col_addr(col >> 4)
+        self.send(Cmd::col(col))
     }
 
     pub fn clear(&mut self) {
@@ -169,12 +143,53 @@ impl<SPI: SpiDevice, DCMD: OutputPin> PicoOled<SPI, DCMD> {
         // self.cmd(0x00 + (y & 0x0f));
         // self.cmd(0x10 + (y >> 4));
 
-        for byte in data.to_be_bytes() {
-            self.data(byte.reverse_bits());
+        for byte in cmd::scan(data) {
+            self.data(byte);
         }
     }
 }
 
+impl<SPI: BackgroundTransfer, DCMD: OutputPin> PicoOled<SPI, DCMD> {
+    /// Encode the whole frame buffer into `frame` and start sending it in the
+    /// background, relying on the vertical addressing mode set by
+    /// [`init`](Self::init).
+    ///
+    /// The next frame can be encoded into another buffer in the meantime. The
+    /// previous frame must be finished before starting a new one, or this
+    /// fails with [`Error::Busy`] and leaves the display untouched.
+    pub fn start_frame(&mut self, fb: &[u128], frame: &'static mut [u8; FRAME_LEN]) -> Result {
+        if !self.spi.is_done() {
+            return Err(Error::Busy);
+        }
+
+        for (offset, &scan) in fb.iter().enumerate() {
+            self.buf[63 - (offset % 64)] = scan;
+        }
+
+        for (bytes, &scan) in frame.chunks_exact_mut(16).zip(self.buf.iter()) {
+            bytes.copy_from_slice(&cmd::scan(scan));
+        }
+
+        self.set_col(0);
+        self.dcmd.set_high().ok();
+        self.spi.start_transfer(frame)
+    }
+
+    /// Whether the frame in progress has been sent.
+    pub fn is_frame_done(&self) -> bool {
+        self.spi.is_done()
+    }
+
+    /// Wait for the frame in progress to be sent and return its buffer.
+    pub fn finish_frame(&mut self) -> Result<&'static mut [u8; FRAME_LEN]> {
+        self.spi.finish().and_then(|frame| {
+            frame
+                .try_into()
+                .map_err(|_| Error::with_hal(ErrorKind::Transfer, HalKind::Other))
+        })
+    }
+}
+
 impl<SPI: SpiDevice, DCMD: OutputPin> Display for PicoOled<SPI, DCMD> {
     fn update(&mut self, fb: &[u128]) {
         for (offset, &scan) in fb.iter().enumerate() {
@@ -186,3 +201,56 @@ impl<SPI: SpiDevice, DCMD: OutputPin> Display for PicoOled<SPI, DCMD> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{PicoOled, FRAME_LEN};
+    use rpio_spi::{Error, MockSpi, OutputPin, Transaction};
+    use std::boxed::Box;
+
+    /// Counts the changes of level.
+    #[derive(Default)]
+    struct MockPin {
+        changes: usize,
+    }
+
+    impl OutputPin for MockPin {
+        type Error = ();
+
+        fn set_low(&mut self) -> core::result::Result<(), ()> {
+            self.changes += 1;
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> core::result::Result<(), ()> {
+            self.changes += 1;
+            Ok(())
+        }
+    }
+
+    fn frame() -> &'static mut [u8; FRAME_LEN] {
+        Box::leak(Box::new([0xAA; FRAME_LEN]))
+    }
+
+    #[test]
+    fn start_frame_busy() {
+        let mock = MockSpi::new();
+        let mut oled = PicoOled::new(mock.clone(), MockPin::default());
+        let fb = [1; 64];
+
+        oled.start_frame(&fb, frame()).unwrap();
+        assert!(!oled.is_frame_done());
+
+        let (log, changes) = (mock.log(), oled.dcmd.changes);
+        assert_eq!(oled.start_frame(&[2; 64], frame()), Err(Error::Busy));
+        assert_eq!(oled.buf, [1; 64]);
+        assert_eq!((mock.log(), oled.dcmd.changes), (log, changes));
+
+        let frame = oled.finish_frame().unwrap();
+        assert!(frame.iter().any(|&byte| byte != 0xAA));
+        assert!(oled.is_frame_done());
+        assert_eq!(mock.log().last(), Some(&Transaction::Deselect));
+    }
+}
@@ -111,7 +111,7 @@ fn main() -> ! {
     };
 
     let flash = {
-        use rpio::flash::{Buffer, Device, FlashBuffer, Size};
+        use rpio::flash::{AnyFamily, Buffer, Device, FlashBuffer, Size};
 
         pinout!(
             spi {
@@ -132,7 +132,12 @@ fn main() -> ! {
         .init();
 
         let buf = Buffer::<5001>::new();
-        Device::new(spi, Size::from_mb(1).unwrap(), buf)
+        let mut flash =
+            Device::new(spi, Size::from_mb(1).unwrap(), buf).with_family(AnyFamily::default());
+
+        // Configure for the chip fitted, if it is recognised.
+        flash.identify().ok();
+        flash
     };
 
     pinout!(
@@ -168,9 +173,10 @@ fn main() -> ! {
         Program::Pio,
         Program::ReadIrq,
         Program::Draw,
+        Program::Diagnostics,
     ];
 
-    for (i, program) in programs.iter().enumerate().take(8) {
+    for (i, program) in programs.iter().enumerate().take(9) {
         screen
             .write_fmt(format_args!("{} {}\n", i + 1, program.name()))
             .ok()
@@ -181,7 +187,7 @@ fn main() -> ! {
 
     let selection = loop {
         match keypad.read_keyup() {
-            Some(key @ 1..=8) => match programs.get(key as usize - 1) {
+            Some(key @ 1..=9) => match programs.get(key as usize - 1) {
                 Some(program) => {
                     break *program;
                 }
@@ -209,6 +215,7 @@ fn main() -> ! {
         Program::KeySeq => programs::keyseq(io),
         Program::ReadIrq => programs::read_irq(io, btnb, btna),
         Program::Draw => programs::draw(io),
+        Program::Diagnostics => programs::diagnostics(io, flash),
         Program::Pio => {
             let (mut pio, sm0, _, _, _) = pac.PIO0.split(&mut pac.RESETS);
             programs::pio(io, btnb.into_pull_down_disabled(), pio, sm0);
@@ -0,0 +1,52 @@
+use crate::io::*;
+use rpio::spi::diagnostics::{ClockSweep, SPEEDS};
+
+pub fn diagnostics<S, D, K, FlashSpi, FlashBuf>(
+    io: Io<S, D, K>,
+    mut flash: Device<FlashSpi, FlashBuf, AnyFamily>,
+) -> !
+where
+    S: SpiDevice,
+    D: OutputPin,
+    K: Keypad,
+    FlashSpi: SpiDevice,
+    FlashBuf: FlashBuffer,
+{
+    setup!(io => delay, screen, keypad);
+    offset!(4, 4);
+
+    loop {
+        print!("CHECKING");
+        let check = flash.check(&SPEEDS);
+
+        clear!();
+
+        match check.id {
+            Ok(IdCheck::Found(id)) => {
+                draw!(fmtln "ID {:02x}{:02x}{:02x}", id.manufacturer, id.memory_type, id.capacity)
+            }
+            Ok(IdCheck::StuckLow) => draw!(fmtln "MISO LOW"),
+            Ok(IdCheck::StuckHigh) => draw!(fmtln "MISO HIGH"),
+            Ok(IdCheck::Unstable(..)) => draw!(fmtln "ID UNSTABLE"),
+            Err(err) => draw!(fmtln "ERR {:?}", err.kind()),
+        }
+
+        match check.clock {
+            Some(Ok(ClockSweep {
+                highest: Some(speed),
+                ..
+            })) => draw!(fmtln "MAX {} KHZ", speed / 1000),
+            Some(Ok(_)) => draw!(fmtln "NO CLOCK OK"),
+            Some(Err(err)) => draw!(fmtln "ERR {:?}", err.kind()),
+            None => (),
+        }
+
+        draw!(fmtln "{}", if check.is_passed() { "PASS" } else { "FAIL" });
+        update!();
+
+        // Run again on any key.
+        while keypad.read_keyup().is_none() {
+            delay.delay_ms(10);
+        }
+    }
+}
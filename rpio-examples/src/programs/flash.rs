@@ -2,7 +2,7 @@ use crate::io::*;
 
 pub fn flash<S, D, K, FlashSpi, FlashBuf>(
     io: Io<S, D, K>,
-    mut flash: Device<FlashSpi, FlashBuf>,
+    mut flash: Device<FlashSpi, FlashBuf, AnyFamily>,
 ) -> !
 where
     S: SpiDevice,
@@ -21,6 +21,8 @@ where
     //     flash.write_byte(0x1000 | i, (i % 256) as u8);
     // }
 
+    let sfdp = flash.sfdp();
+
     let mut addr = 0x1000;
     //let status = flash.read_status().ok().unwrap();
     let data = flash.read(0x1000, 4096).unwrap();
@@ -107,6 +109,27 @@ where
 
                         keypad.read_keyup();
                     }
+                    Some(0xC) => {
+                        offset!(4, 4);
+                        clear!();
+
+                        match &sfdp {
+                            Ok(sfdp) => {
+                                let erase = sfdp.erase_sizes();
+
+                                draw!(fmtln "SFDP {} KB", sfdp.len / 1024);
+                                draw!(fmtln "PAGE {} {:?}", sfdp.page_len, sfdp.address_bytes);
+                                draw!(fmtln "ERASE {} {} {}", erase.sector, erase.block32, erase.block64);
+                                draw!(fmtln "DUAL {} QUAD {}", sfdp.read_modes.dual_io, sfdp.read_modes.quad_io);
+                            }
+                            Err(err) => draw!(fmtln "NO SFDP {:?}", err),
+                        }
+
+                        update!();
+                        offset!(38, 28);
+
+                        keypad.read_keyup();
+                    }
                     _ => (),
                 };
             }
@@ -1,4 +1,5 @@
 mod adc;
+mod diagnostics;
 mod flash;
 //mod interrupt;
 mod draw;
@@ -9,6 +10,7 @@ mod read_irq;
 mod seq;
 
 pub use adc::adc;
+pub use diagnostics::diagnostics;
 pub use flash::flash;
 //pub use interrupt::interrupt;
 pub use self::pio::pio;
@@ -29,6 +31,7 @@ pub enum Program {
     ReadIrq,
     Draw,
     Pio,
+    Diagnostics,
 }
 
 impl Program {
@@ -45,6 +48,7 @@ impl Program {
             ReadIrq => "Read IRQ",
             Draw => "Draw",
             Pio => "Pio Example",
+            Diagnostics => "SPI Diagnostics",
         }
     }
 }
@@ -0,0 +1,475 @@
+use super::buffer::*;
+use super::chip::Chip;
+use super::device::{Result, BLOCK32_LEN, BLOCK64_LEN, SECTOR_LEN};
+use super::error::Error;
+use super::family::{Family, Sst25};
+use super::op::{AddressMode, Code, Type};
+use super::size::Size;
+use super::state::State;
+use super::status::Status;
+use super::step::{Busy, Plan, Step};
+use super::timeout::{Deadline, Timeouts, POLL_INTERVAL_US};
+use embedded_hal_async::delay::DelayNs;
+use rpio_spi::AsyncSpiDevice;
+
+/// The async counterpart of [`Device`](super::Device). The chip is
+/// configured and commands are encoded in the same way, with single-line
+/// reads only.
+///
+/// Busy polling waits on `delay` for [`POLL_INTERVAL_US`] between status
+/// reads, so that other tasks can run.
+#[derive(Debug)]
+pub struct AsyncDevice<SPI: AsyncSpiDevice, B: FlashBuffer, D: DelayNs, F: Family = Sst25> {
+    spi: SPI,
+    pub buf: B,
+    delay: D,
+    state: State<F>,
+}
+
+impl<SPI: AsyncSpiDevice, B: FlashBuffer, D: DelayNs> AsyncDevice<SPI, B, D> {
+    /// An SST25 chip of the given size, programmed with AAI.
+    pub fn new(spi: SPI, size: Size, buf: B, delay: D) -> Self {
+        Self {
+            spi,
+            buf,
+            delay,
+            state: State::new(size),
+        }
+    }
+}
+
+impl<SPI: AsyncSpiDevice, B: FlashBuffer, D: DelayNs, F: Family> AsyncDevice<SPI, B, D, F> {
+    /// Drive the chip as a member of another family. The rest of the
+    /// configuration is kept.
+    pub fn with_family<G: Family>(self, family: G) -> AsyncDevice<SPI, B, D, G> {
+        AsyncDevice {
+            spi: self.spi,
+            buf: self.buf,
+            delay: self.delay,
+            state: self.state.with_family(family),
+        }
+    }
+
+    /// Configure the device for a known chip, as
+    /// [`Device::with_chip`](super::Device::with_chip) does.
+    pub async fn with_chip(mut self, chip: &Chip) -> Result<Self> {
+        self.set_chip(chip).await?;
+        Ok(self)
+    }
+
+    pub async fn set_chip(&mut self, chip: &Chip) -> Result {
+        let mode = self.state.set_chip(chip)?;
+        self.set_address_mode(mode).await
+    }
+
+    pub fn address_mode(&self) -> AddressMode {
+        self.state.address_mode
+    }
+
+    /// Change how addresses are sent, entering or leaving the chip's 4-byte
+    /// address mode as needed.
+    pub async fn set_address_mode(&mut self, mode: AddressMode) -> Result {
+        if let Some(code) = self.state.address_mode_code(mode) {
+            self.buf.set_op(code);
+            self.send(Type::Op, 0).await?;
+        }
+
+        self.state.address_mode = mode;
+        Ok(())
+    }
+
+    pub fn family(&self) -> &F {
+        &self.state.family
+    }
+
+    pub fn size(&self) -> Size {
+        self.state.size
+    }
+
+    pub fn chip(&self) -> Option<&Chip> {
+        self.state.chip.as_ref()
+    }
+
+    /// Detect the end of each AAI word program from the busy level the chip
+    /// drives on MISO, rather than by reading the status register.
+    pub fn with_busy_output(mut self, busy_output: bool) -> Self {
+        self.state.busy_output = busy_output;
+        self
+    }
+
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.state.timeouts = timeouts;
+        self
+    }
+
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.state.timeouts = timeouts;
+    }
+
+    /// Measure [`Timeouts`] with `clock`, which returns the current time in
+    /// microseconds, rather than by counting the delays between polls.
+    pub fn with_clock(mut self, clock: fn() -> u64) -> Self {
+        self.state.clock = Some(clock);
+        self
+    }
+
+    pub async fn send(&mut self, op: Type, data_len: usize) -> Result {
+        let buf = self.buf.op(op, data_len);
+        self.spi.transfer(buf).await?;
+        Ok(())
+    }
+
+    pub async fn read_status(&mut self) -> Result<Status> {
+        self.buf.set_op(Code::ReadStatus);
+        self.send(Type::Op, 1).await?;
+        Ok(Status::from(*self.buf.get(0)))
+    }
+
+    pub async fn write_enable(&mut self) -> Result {
+        self.buf.set_op(Code::WriteEnable);
+        self.send(Type::Op, 0).await
+    }
+
+    pub async fn write_status_enable(&mut self) -> Result {
+        self.write_enable().await?;
+
+        if let Some(code) = self.state.family.status_write_enable() {
+            self.buf.set_op(code);
+            self.send(Type::Op, 0).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Read status register `index`, counting from 0, where the family has
+    /// more than one.
+    pub async fn read_status_register(&mut self, index: usize) -> Result<u8> {
+        let (code, _) = self.state.status_register(index)?;
+
+        self.buf.set_op(code);
+        self.send(Type::Op, 1).await?;
+        Ok(*self.buf.get(0))
+    }
+
+    /// Write status register `index`, counting from 0.
+    pub async fn write_status_register(&mut self, index: usize, value: u8) -> Result {
+        let (_, code) = self.state.status_register(index)?;
+
+        self.write_status_enable().await?;
+        self.buf.set_op(code);
+        *self.buf.get_mut(0) = value;
+        self.send(Type::Op, 1).await?;
+        self.state.status_written(index, value);
+        Ok(())
+    }
+
+    /// Write the block-protect bits, clearing the rest of the first status
+    /// register.
+    pub async fn write_block_protect_bits(&mut self, bp_bits: u8) -> Result {
+        let status = self.state.block_protect_status(bp_bits);
+        self.write_status_register(0, status).await
+    }
+
+    /// Read the block-protect bits from the status register, along with any
+    /// flags such as CMP in the other status registers. Until they are read
+    /// or written, the whole chip is treated as protected.
+    pub async fn read_block_protect_bits(&mut self) -> Result<u8> {
+        for index in 0..self.state.family.status_registers().len() {
+            if self.state.holds_protection(index) {
+                let value = self.read_status_register(index).await?;
+                self.state.status_read(index, value);
+            }
+        }
+
+        Ok(self.state.block_protect)
+    }
+
+    pub async fn write_byte(&mut self, addr: u32, byte: u8) -> Result {
+        let data = [byte];
+        let plan = self.state.program(addr, &data);
+        self.run(plan).await
+    }
+
+    /// Program `data` from `addr`, which must have been erased, as
+    /// [`Device::write`](super::Device::write) does.
+    pub async fn write(&mut self, addr: u32, data: &[u8]) -> Result {
+        let plan = self.state.write(addr, data, self.buf.len())?;
+        self.run(plan).await
+    }
+
+    /// Perform the steps of `plan`, as [`Device`](super::Device) does.
+    async fn run(&mut self, mut plan: impl Plan) -> Result {
+        let mut result = Ok(());
+
+        loop {
+            let step = match plan.next(&mut self.state, &mut self.buf) {
+                Ok(Some(step)) => self.step(step).await,
+                Ok(None) => return result,
+                Err(err) => Err(err),
+            };
+
+            if let Err(err) = step {
+                result = result.and(Err(err));
+
+                if !plan.fail() {
+                    return result;
+                }
+            }
+        }
+    }
+
+    async fn step(&mut self, step: Step) -> Result {
+        match step {
+            Step::Send(op, data_len) => self.send(op, data_len).await,
+            Step::Wait(busy, timeout_us) => self.wait_while(timeout_us, busy).await,
+        }
+    }
+
+    /// Erase the 4K sector containing `addr`.
+    pub async fn erase_sector(&mut self, addr: u32) -> Result {
+        let plan = self.state.erase(SECTOR_LEN, addr)?;
+        self.run(plan).await
+    }
+
+    /// Erase the 32K block containing `addr`.
+    pub async fn erase_block32(&mut self, addr: u32) -> Result {
+        let plan = self.state.erase(BLOCK32_LEN, addr)?;
+        self.run(plan).await
+    }
+
+    /// Erase the 64K block containing `addr`.
+    pub async fn erase_block64(&mut self, addr: u32) -> Result {
+        let plan = self.state.erase(BLOCK64_LEN, addr)?;
+        self.run(plan).await
+    }
+
+    /// Erase the whole chip, which must not be protected at all.
+    pub async fn erase_chip(&mut self) -> Result {
+        let plan = self.state.erase_chip()?;
+        self.run(plan).await
+    }
+
+    /// Erase every sector overlapping `len` bytes from `addr`, as
+    /// [`Device::erase_range`](super::Device::erase_range) does.
+    pub async fn erase_range(&mut self, addr: u32, len: u32) -> Result {
+        let plan = self.state.erase_range(addr, len)?;
+        self.run(plan).await
+    }
+
+    pub async fn read(&mut self, addr: u32, len: usize) -> Result<&[u8]> {
+        let op = self.state.set_op_addr(&mut self.buf, Code::Read, addr)?;
+        self.send(op, len).await?;
+        Ok(&self.buf.data()[..len])
+    }
+
+    pub async fn read_to_sector_end(&mut self, addr: u32) -> Result<&[u8]> {
+        self.read(addr, 4096 - (addr & 0xFFF) as usize).await
+    }
+
+    /// Poll the status register until the chip is no longer busy, for up to
+    /// the timeout of the last operation started.
+    pub async fn wait_ready(&mut self) -> Result {
+        let timeout_us = self.state.busy_us;
+        self.wait_ready_us(timeout_us).await
+    }
+
+    /// Poll the status register until the chip is no longer busy, for up to
+    /// `timeout_us`, waiting on the delay between polls.
+    pub async fn wait_ready_us(&mut self, timeout_us: u32) -> Result {
+        self.wait_while(timeout_us, Busy::Status).await
+    }
+
+    async fn wait_while(&mut self, timeout_us: u32, busy: Busy) -> Result {
+        let mut deadline = Deadline::new(self.state.clock, timeout_us);
+
+        while self.is_busy(busy).await? {
+            if deadline.is_expired() {
+                return Err(Error::Timeout);
+            }
+
+            self.delay.delay_us(POLL_INTERVAL_US).await;
+            deadline.delayed(POLL_INTERVAL_US);
+        }
+
+        Ok(())
+    }
+
+    async fn is_busy(&mut self, busy: Busy) -> Result<bool> {
+        Ok(match busy {
+            Busy::Status => self.read_status().await?.is_busy(),
+            Busy::Aai => {
+                let status = self.read_status().await?;
+                status.is_busy() || status.is_auto_increment_mode()
+            }
+            Busy::Output => {
+                let mut level = [0];
+                self.spi.transfer(&mut level).await?;
+                level[0] != 0xFF
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{AsyncDevice, Error};
+    use crate::{AddressMode, Buffer, Chip, JedecId, Size, W25q};
+    use core::{
+        cell::Cell,
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+    use embedded_hal_async::delay::DelayNs;
+    use rpio_spi::{AsyncSpiDevice, MockSpi, Spi, Transaction};
+    use std::{rc::Rc, vec::Vec};
+
+    /// Adds up the time waited, in nanoseconds, without waiting.
+    #[derive(Clone, Default)]
+    struct Delay(Rc<Cell<u32>>);
+
+    impl DelayNs for Delay {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.0.set(self.0.get() + ns);
+        }
+    }
+
+    fn device(mock: &MockSpi) -> AsyncDevice<impl AsyncSpiDevice, Buffer<9>, Delay> {
+        let spi = Spi::from_blocking(mock.clone()).init();
+        AsyncDevice::new(
+            spi,
+            Size::from_mb(1).unwrap(),
+            Buffer::new(),
+            Delay::default(),
+        )
+    }
+
+    /// Poll `future` to completion. Neither the mock nor the delay ever
+    /// leaves it pending.
+    fn block_on<T>(future: impl Future<Output = T>) -> T {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    fn headers(mock: &MockSpi) -> Vec<Vec<u8>> {
+        mock.log()
+            .into_iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Transfer(write, _) if write[0] != 0x05 && write[0] != 0x06 => {
+                    Some(write[..write.len().min(6)].to_vec())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn write() {
+        let mock = MockSpi::new();
+        let mut device = device(&mock);
+        let data = [0x10, 0x22, 0x34, 0x82, 0x96, 0x20];
+
+        assert_eq!(
+            block_on(device.write(0x1001, &data)),
+            Err(Error::SectorOutOfRange)
+        );
+
+        block_on(device.write_block_protect_bits(0)).unwrap();
+        block_on(device.write(0x1001, &data)).unwrap();
+
+        assert_eq!(
+            headers(&mock),
+            [
+                [0x50].to_vec(),
+                [0x01, 0x00].to_vec(),
+                [0x02, 0x00, 0x10, 0x01, 0x10].to_vec(),
+                [0xAD, 0x00, 0x10, 0x02, 0x22, 0x34].to_vec(),
+                [0xAD, 0x82, 0x96].to_vec(),
+                [0x04].to_vec(),
+                [0x02, 0x00, 0x10, 0x06, 0x20].to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn four_byte_addresses() {
+        let mock = MockSpi::new();
+        let chip = Chip::find(JedecId::from([0xEF, 0x40, 0x19])).unwrap();
+        let mut device = block_on(device(&mock).with_family(W25q).with_chip(chip)).unwrap();
+        block_on(device.write_block_protect_bits(0)).unwrap();
+
+        assert_eq!(device.address_mode(), AddressMode::Four);
+
+        block_on(device.read(0x1234567, 2)).unwrap();
+        block_on(device.write(0x1FFFFFE, &[0x10, 0x20])).unwrap();
+        block_on(device.erase_sector(0x1000000)).unwrap();
+
+        assert_eq!(
+            headers(&mock),
+            [
+                [0xB7].to_vec(),
+                [0x01, 0x00].to_vec(),
+                [0x03, 0x01, 0x23, 0x45, 0x67, 0x00].to_vec(),
+                [0x02, 0x01, 0xFF, 0xFF, 0xFE, 0x10].to_vec(),
+                [0x20, 0x01, 0x00, 0x00, 0x00].to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn wait_ready_delay() {
+        let mock = MockSpi::new();
+
+        for stale in [0, 1, 1] {
+            mock.expect_all(&[
+                Transaction::Select,
+                Transaction::transfer(&[0x05, stale], &[0, 0x01]),
+                Transaction::Deselect,
+            ]);
+        }
+
+        let delay = Delay::default();
+        let spi = Spi::from_blocking(mock.clone()).init();
+        let mut device = AsyncDevice::new(
+            spi,
+            Size::from_mb(1).unwrap(),
+            Buffer::<9>::new(),
+            delay.clone(),
+        );
+
+        assert_eq!(block_on(device.wait_ready_us(20)), Err(Error::Timeout));
+        assert_eq!(delay.0.get(), 20_000);
+        mock.done();
+    }
+
+    #[test]
+    fn wait_ready_clock() {
+        std::thread_local!(static NOW: core::cell::Cell<u64> = const { core::cell::Cell::new(0) });
+
+        fn clock() -> u64 {
+            NOW.with(|now| now.replace(now.get() + 15))
+        }
+
+        let mock = MockSpi::new();
+
+        for stale in [0, 1, 1] {
+            mock.expect_all(&[
+                Transaction::Select,
+                Transaction::transfer(&[0x05, stale], &[0, 0x01]),
+                Transaction::Deselect,
+            ]);
+        }
+
+        let mut device = device(&mock).with_clock(clock);
+        assert_eq!(block_on(device.wait_ready_us(40)), Err(Error::Timeout));
+        mock.done();
+    }
+}
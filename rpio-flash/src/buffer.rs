@@ -25,6 +25,9 @@ pub trait FlashBuffer {
     /// Set an op+addr instruction and return a slice
     fn set_op_addr(&mut self, op: Code, addr: u32) -> &mut Self;
 
+    /// Set an op+addr instruction with a 4-byte address
+    fn set_op_addr4(&mut self, op: Code, addr: u32) -> &mut Self;
+
     /// Set the highspeed instruction and return a slice
     fn set_read_highspeed(&mut self, addr: u32) -> &mut Self;
 
@@ -33,6 +36,9 @@ pub trait FlashBuffer {
 }
 
 // Can probably be generic
+/// Holds each instruction and its data. Transfers are up to `LEN` bytes, so
+/// a `rpio_spi::Retry` wrapping the transport needs at least `LEN` bytes to
+/// retry all of them.
 #[derive(Debug)]
 pub struct Buffer<const LEN: usize> {
     buf: [u8; LEN],
@@ -68,6 +74,12 @@ impl<const LEN: usize> FlashBuffer for Buffer<LEN> {
         self
     }
 
+    fn set_op_addr4(&mut self, op: Code, addr: u32) -> &mut Self {
+        self.buf[1..5].copy_from_slice(&addr.to_be_bytes());
+        self.buf[0] = op.to_instruction();
+        self
+    }
+
     fn set_read_highspeed(&mut self, addr: u32) -> &mut Self {
         let addr_space = &mut self.buf[..4];
         addr_space.copy_from_slice(&addr.to_be_bytes());
@@ -133,6 +145,22 @@ mod tests {
             &[hs_read, 0xAB, 0xCD, 0xEF, 0x56, 1, 2, 3, 4]
         );
 
+        buf.set_op_addr4(Code::Read4, 0x01234567);
+        assert_eq!(
+            buf.op(Type::OpAddr4, 4),
+            &[
+                Code::Read4.to_instruction(),
+                0x01,
+                0x23,
+                0x45,
+                0x67,
+                1,
+                2,
+                3,
+                4
+            ]
+        );
+
         let countdown: [u8; 8] = [8, 7, 6, 5, 4, 3, 2, 1];
         assert_eq!(buf.write_data(&countdown), 4);
         assert_eq!(buf.data(), &[8, 7, 6, 5]);
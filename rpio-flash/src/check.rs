@@ -0,0 +1,73 @@
+use super::id::JedecId;
+use super::op::Code;
+use rpio_spi::{
+    diagnostics::{self, ClockSweep},
+    Result, SpiDevice,
+};
+
+/// The outcome of reading the JEDEC ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdCheck {
+    Found(JedecId),
+    /// Every bit read was 0: MISO is not connected, or the chip is unpowered
+    /// or never selected.
+    StuckLow,
+    /// Every bit read was 1: MISO is floating high, or the chip is never
+    /// selected.
+    StuckHigh,
+    /// Two reads returned different IDs, from noise or a clock too fast for
+    /// the wiring.
+    Unstable(JedecId, JedecId),
+}
+
+/// The outcome of [`Device::check`](super::Device::check).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceCheck {
+    pub id: Result<IdCheck>,
+    /// `None` if the ID was not found or the transport does not control
+    /// clock speed.
+    pub clock: Option<Result<ClockSweep>>,
+}
+
+impl DeviceCheck {
+    pub fn is_passed(&self) -> bool {
+        matches!(self.id, Ok(IdCheck::Found(_)))
+            && !matches!(
+                self.clock,
+                Some(Err(_)) | Some(Ok(ClockSweep { highest: None, .. }))
+            )
+    }
+}
+
+fn read_id<S: SpiDevice>(spi: &mut S) -> Result<JedecId> {
+    let mut words = [Code::ReadJedecId.to_instruction(), 0, 0, 0];
+    spi.transfer(&mut words)?;
+    Ok(JedecId::from([words[1], words[2], words[3]]))
+}
+
+fn check_id<S: SpiDevice>(spi: &mut S) -> Result<IdCheck> {
+    let (first, second) = (read_id(spi)?, read_id(spi)?);
+
+    Ok(match first.to_bytes() {
+        [0x00, 0x00, 0x00] => IdCheck::StuckLow,
+        [0xFF, 0xFF, 0xFF] => IdCheck::StuckHigh,
+        _ if first != second => IdCheck::Unstable(first, second),
+        _ => IdCheck::Found(first),
+    })
+}
+
+/// Read the ID, then sweep `speeds` checking that the same ID is read back.
+pub(crate) fn run<S: SpiDevice>(spi: &mut S, speeds: &[u32]) -> DeviceCheck {
+    let id = check_id(spi);
+
+    let clock = match id {
+        Ok(IdCheck::Found(expected)) if spi.is_clock_speed() => {
+            Some(diagnostics::sweep_clock(spi, speeds, |spi| {
+                Ok(read_id(spi)? == expected)
+            }))
+        }
+        _ => None,
+    };
+
+    DeviceCheck { id, clock }
+}
@@ -0,0 +1,222 @@
+use super::family::AnyFamily;
+use super::id::JedecId;
+use super::op::ReadModes;
+use super::sfdp::AddressBytes;
+use super::size::Size;
+
+/// The erase instructions supported by a chip, besides chip erase.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EraseSizes {
+    /// 4K sector erase.
+    pub sector: bool,
+    /// 32K block erase.
+    pub block32: bool,
+    /// 64K block erase.
+    pub block64: bool,
+}
+
+impl EraseSizes {
+    pub const ALL: EraseSizes = EraseSizes {
+        sector: true,
+        block32: true,
+        block64: true,
+    };
+
+    /// Sector and 64K block erase, without 32K block erase.
+    pub const NO_BLOCK32: EraseSizes = EraseSizes {
+        sector: true,
+        block32: false,
+        block64: true,
+    };
+}
+
+/// A known SPI NOR flash part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chip {
+    pub name: &'static str,
+    pub id: JedecId,
+    pub family: AnyFamily,
+    pub size: Size,
+    /// The most bytes a single program instruction can write: 1 for chips
+    /// which only program a byte at a time.
+    pub page_len: u32,
+    pub erase: EraseSizes,
+    pub read_modes: ReadModes,
+    /// Whether the chip supports auto address increment (AAI) word
+    /// programming.
+    pub aai: bool,
+    /// The bits of the status register which hold the block-protect bits,
+    /// starting at bit 2.
+    pub protect_mask: u8,
+    /// The address lengths accepted. Chips larger than 16 MB are driven in
+    /// 4-byte address mode.
+    pub address_bytes: AddressBytes,
+}
+
+impl Chip {
+    /// Look up the chip with this JEDEC ID in [`CHIPS`].
+    pub fn find(id: JedecId) -> Option<&'static Chip> {
+        CHIPS.iter().find(|chip| chip.id == id)
+    }
+}
+
+const DUAL: ReadModes = ReadModes {
+    dual_output: true,
+    dual_io: true,
+    quad_output: false,
+    quad_io: false,
+};
+
+const DUAL_OUTPUT: ReadModes = ReadModes {
+    dual_output: true,
+    dual_io: false,
+    quad_output: false,
+    quad_io: false,
+};
+
+/// Chips with capacity codes above 0x18 are larger than 16 MB.
+const fn address_bytes(capacity: u8) -> AddressBytes {
+    match capacity {
+        0x19.. => AddressBytes::ThreeOrFour,
+        _ => AddressBytes::Three,
+    }
+}
+
+const fn sst25(name: &'static str, capacity: u8, mb: u32) -> Chip {
+    Chip {
+        name,
+        id: JedecId {
+            manufacturer: 0xBF,
+            memory_type: 0x25,
+            capacity,
+        },
+        family: AnyFamily::Sst25,
+        size: Size::mb(mb),
+        page_len: 1,
+        erase: EraseSizes::ALL,
+        read_modes: ReadModes {
+            dual_output: false,
+            dual_io: false,
+            quad_output: false,
+            quad_io: false,
+        },
+        aai: true,
+        protect_mask: 0x3C,
+        address_bytes: AddressBytes::Three,
+    }
+}
+
+const fn w25q(name: &'static str, capacity: u8) -> Chip {
+    Chip {
+        name,
+        id: JedecId {
+            manufacturer: 0xEF,
+            memory_type: 0x40,
+            capacity,
+        },
+        family: AnyFamily::W25q,
+        size: Size::mb(1 << (capacity - 0x14)),
+        page_len: 256,
+        erase: EraseSizes::ALL,
+        read_modes: ReadModes::ALL,
+        aai: false,
+        protect_mask: 0x1C,
+        address_bytes: address_bytes(capacity),
+    }
+}
+
+const fn mx25(name: &'static str, capacity: u8, erase: EraseSizes, read_modes: ReadModes) -> Chip {
+    Chip {
+        name,
+        id: JedecId {
+            manufacturer: 0xC2,
+            memory_type: 0x20,
+            capacity,
+        },
+        family: AnyFamily::Mx25,
+        size: Size::mb(1 << (capacity - 0x14)),
+        page_len: 256,
+        erase,
+        read_modes,
+        aai: false,
+        protect_mask: 0x3C,
+        address_bytes: address_bytes(capacity),
+    }
+}
+
+const fn gd25q(name: &'static str, capacity: u8) -> Chip {
+    Chip {
+        name,
+        id: JedecId {
+            manufacturer: 0xC8,
+            memory_type: 0x40,
+            capacity,
+        },
+        family: AnyFamily::W25q,
+        size: Size::mb(1 << (capacity - 0x14)),
+        page_len: 256,
+        erase: EraseSizes::ALL,
+        read_modes: ReadModes::ALL,
+        aai: false,
+        protect_mask: 0x7C,
+        address_bytes: address_bytes(capacity),
+    }
+}
+
+/// The chips recognised by [`Device::probe`](super::Device::probe).
+pub const CHIPS: [Chip; 22] = [
+    sst25("SST25VF080B", 0x8E, 1),
+    sst25("SST25VF016B", 0x41, 2),
+    sst25("SST25VF032B", 0x4A, 4),
+    Chip {
+        page_len: 256,
+        read_modes: DUAL,
+        aai: false,
+        ..sst25("SST25VF064C", 0x4B, 8)
+    },
+    w25q("W25Q80", 0x14),
+    w25q("W25Q16", 0x15),
+    w25q("W25Q32", 0x16),
+    w25q("W25Q64", 0x17),
+    w25q("W25Q128", 0x18),
+    Chip {
+        protect_mask: 0x3C,
+        ..w25q("W25Q256", 0x19)
+    },
+    mx25("MX25L8006E", 0x14, EraseSizes::NO_BLOCK32, DUAL_OUTPUT),
+    mx25("MX25L1606E", 0x15, EraseSizes::ALL, DUAL),
+    mx25("MX25L3206E", 0x16, EraseSizes::ALL, DUAL),
+    mx25("MX25L6406E", 0x17, EraseSizes::NO_BLOCK32, DUAL),
+    mx25("MX25L12835F", 0x18, EraseSizes::ALL, ReadModes::ALL),
+    mx25("MX25L25645G", 0x19, EraseSizes::ALL, ReadModes::ALL),
+    gd25q("GD25Q80", 0x14),
+    gd25q("GD25Q16", 0x15),
+    gd25q("GD25Q32", 0x16),
+    gd25q("GD25Q64", 0x17),
+    gd25q("GD25Q128", 0x18),
+    gd25q("GD25Q256", 0x19),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{Chip, JedecId, Size, CHIPS};
+
+    #[test]
+    fn chips() {
+        for (index, chip) in CHIPS.iter().enumerate() {
+            assert_eq!(Chip::find(chip.id), Some(chip));
+            assert!(CHIPS[..index].iter().all(|other| other.id != chip.id));
+            assert_eq!(chip.protect_mask & 0b11, 0);
+        }
+
+        let w25q64 = Chip::find(JedecId::from([0xEF, 0x40, 0x17])).unwrap();
+
+        assert_eq!(w25q64.name, "W25Q64");
+        assert_eq!(Size::from_mb(8), Ok(w25q64.size));
+        assert_eq!(w25q64.protect_mask, 0x1C);
+
+        let w25q256 = Chip::find(JedecId::from([0xEF, 0x40, 0x19])).unwrap();
+        assert_eq!(w25q256.protect_mask, 0x3C);
+        assert_eq!(Chip::find(JedecId::from([0xEF, 0x40, 0x20])), None);
+    }
+}
@@ -1,42 +1,162 @@
 use core::fmt::LowerHex;
 
 use super::buffer::*;
+use super::check::{self, DeviceCheck};
+use super::chip::{Chip, EraseSizes};
 use super::error::Error;
-use super::op::{Code, Type};
+use super::family::{AnyFamily, Family, Sst25};
+use super::id::JedecId;
+use super::op::{AddressMode, Code, ReadMode, ReadModes, Type};
+use super::sfdp::{self, ParameterHeader, Sfdp, BASIC_TABLE_ID, BASIC_TABLE_LEN};
 use super::size::Size;
+use super::state::State;
 use super::status::Status;
-use rpio_spi::{Error as SpiError, SpiDevice, Transfer};
+use super::step::{Busy, Plan, Step};
+use super::timeout::{Deadline, Timeouts, POLL_INTERVAL_US};
+use rpio_spi::{BackgroundTransfer, ChipSelect, Error as SpiError, ErrorKind, SpiDevice};
 
 pub type Result<T = ()> = core::result::Result<T, Error>;
 
+pub const SECTOR_LEN: u32 = 0x1000;
+pub const BLOCK32_LEN: u32 = 0x8000;
+pub const BLOCK64_LEN: u32 = 0x10000;
+
 #[derive(Debug)]
-pub struct Device<SPI: SpiDevice, B: FlashBuffer> {
+pub struct Device<SPI: SpiDevice, B: FlashBuffer, F: Family = Sst25> {
     spi: SPI,
     pub buf: B,
-    size: Size,
-    block_protect: u8,
+    state: State<F>,
 }
 
 impl<SPI: SpiDevice, B: FlashBuffer> Device<SPI, B> {
+    /// An SST25 chip of the given size, programmed with AAI.
     pub fn new(spi: SPI, size: Size, buf: B) -> Self {
         Self {
             spi,
-            size,
             buf,
-            block_protect: 0xF,
+            state: State::new(size),
+        }
+    }
+}
+
+impl<SPI: SpiDevice, B: FlashBuffer> Device<SPI, B, AnyFamily> {
+    /// Identify the chip from its JEDEC ID and configure the device for it,
+    /// whatever its family. Fails with [`Error::UnknownChip`] if it is not in
+    /// [`CHIPS`](super::CHIPS) and has no usable SFDP table.
+    pub fn probe(spi: SPI, buf: B) -> Result<Self> {
+        let mut device = Device::new(spi, Size::default(), buf).with_family(AnyFamily::default());
+        let id = device.identify()?;
+
+        match device.state.chip {
+            Some(_) => Ok(device),
+            None => Err(Error::UnknownChip(id)),
+        }
+    }
+}
+
+impl<SPI: SpiDevice, B: FlashBuffer, F: Family> Device<SPI, B, F> {
+    /// Drive the chip as a member of another family. The rest of the
+    /// configuration is kept.
+    pub fn with_family<G: Family>(self, family: G) -> Device<SPI, B, G> {
+        Device {
+            spi: self.spi,
+            buf: self.buf,
+            state: self.state.with_family(family),
+        }
+    }
+
+    /// Configure the size, read modes, erase sizes, programming and
+    /// block-protect bits for a known chip, entering 4-byte address mode if
+    /// it is larger than 16 MB. Fails with [`Error::NotSupported`] if the
+    /// chip is not in the device's family.
+    pub fn with_chip(mut self, chip: &Chip) -> Result<Self> {
+        self.set_chip(chip)?;
+        Ok(self)
+    }
+
+    pub fn set_chip(&mut self, chip: &Chip) -> Result {
+        let mode = self.state.set_chip(chip)?;
+        self.set_address_mode(mode)
+    }
+
+    pub fn address_mode(&self) -> AddressMode {
+        self.state.address_mode
+    }
+
+    /// Change how addresses are sent, entering or leaving the chip's 4-byte
+    /// address mode as needed.
+    pub fn set_address_mode(&mut self, mode: AddressMode) -> Result {
+        if let Some(code) = self.state.address_mode_code(mode) {
+            self.buf.set_op(code);
+            self.send(Type::Op, 0)?;
         }
+
+        self.state.address_mode = mode;
+        Ok(())
+    }
+
+    pub fn family(&self) -> &F {
+        &self.state.family
+    }
+
+    pub fn size(&self) -> Size {
+        self.state.size
+    }
+
+    /// The sectors and blocks the chip can erase.
+    pub fn erase_sizes(&self) -> EraseSizes {
+        self.state.erase
+    }
+
+    /// The chip found by [`identify`](Self::identify) or set with
+    /// [`with_chip`](Self::with_chip).
+    pub fn chip(&self) -> Option<&Chip> {
+        self.state.chip.as_ref()
+    }
+
+    /// Detect the end of each AAI word program from the busy level the chip
+    /// drives on MISO, rather than by reading the status register.
+    pub fn with_busy_output(mut self, busy_output: bool) -> Self {
+        self.state.busy_output = busy_output;
+        self
+    }
+
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.state.timeouts = timeouts;
+        self
+    }
+
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.state.timeouts = timeouts;
+    }
+
+    /// Measure [`Timeouts`] with `clock`, which returns the current time in
+    /// microseconds, rather than by counting the delays between polls.
+    pub fn with_clock(mut self, clock: fn() -> u64) -> Self {
+        self.state.clock = Some(clock);
+        self
+    }
+
+    /// Declare the multi-line reads supported by the chip, so that
+    /// [`read`](Self::read) can use the fastest one the transport allows.
+    pub fn with_read_modes(mut self, read_modes: ReadModes) -> Self {
+        self.state.read_modes = read_modes;
+        self
+    }
+
+    pub fn set_read_modes(&mut self, read_modes: ReadModes) {
+        self.state.read_modes = read_modes;
+    }
+
+    /// The mode used by [`read`](Self::read).
+    pub fn read_mode(&self) -> ReadMode {
+        self.state.read_modes.fastest(self.spi.max_lines())
     }
 
     pub fn send(&mut self, op: Type, data_len: usize) -> Result {
         let buf = self.buf.op(op, data_len);
 
-        self.spi.transfer(buf).map_err(|err| match err {
-            SpiError::Transfer => Error::SPITransfer,
-            SpiError::ChipSelect => Error::SPIChipSelect,
-            SpiError::ChipDeselect => Error::SPIChipDeselect,
-            SpiError::ClockSpeed => Error::SPISetClockSpeed,
-            SpiError::NotImplemented => unreachable!(),
-        })?;
+        self.spi.transfer(buf)?;
 
         Ok(())
     }
@@ -47,6 +167,90 @@ impl<SPI: SpiDevice, B: FlashBuffer> Device<SPI, B> {
         Ok(Status::from(*self.buf.get(0)))
     }
 
+    pub fn read_jedec_id(&mut self) -> Result<JedecId> {
+        self.buf.set_op(Code::ReadJedecId);
+        self.send(Type::Op, 3)?;
+        Ok(JedecId::from([
+            *self.buf.get(0),
+            *self.buf.get(1),
+            *self.buf.get(2),
+        ]))
+    }
+
+    /// Read the JEDEC ID and, if the chip is in [`CHIPS`](super::CHIPS) or
+    /// has a usable SFDP table, configure the device for it. Otherwise the
+    /// configuration is left unchanged. Fails with [`Error::NotSupported`] if
+    /// the chip is not in the device's family.
+    pub fn identify(&mut self) -> Result<JedecId> {
+        let id = self.read_jedec_id()?;
+
+        if let [0x00, 0x00, 0x00] | [0xFF, 0xFF, 0xFF] = id.to_bytes() {
+            return Err(Error::NoChip);
+        }
+
+        if let Some(chip) = Chip::find(id) {
+            self.set_chip(chip)?;
+            return Ok(id);
+        }
+
+        match self.sfdp().and_then(|sfdp| sfdp.to_chip(id)) {
+            Ok(chip) => self.set_chip(&chip)?,
+            Err(Error::Spi(err)) => return Err(err.into()),
+            Err(_) => (),
+        }
+
+        Ok(id)
+    }
+
+    /// Read `words.len()` bytes of the SFDP area from `addr`, as much as the
+    /// buffer holds at a time.
+    pub fn read_sfdp(&mut self, addr: u32, words: &mut [u8]) -> Result {
+        // Each read starts with a dummy byte.
+        let chunk_len = self.buf.len() - 1;
+
+        if chunk_len == 0 {
+            return Err(Error::NotSupported);
+        }
+
+        for (addr, chunk) in (addr..).step_by(chunk_len).zip(words.chunks_mut(chunk_len)) {
+            self.buf.set_op_addr(Code::ReadSfdp, addr);
+            self.buf.data_mut()[..=chunk.len()].fill(0);
+            self.send(Type::OpAddr, chunk.len() + 1)?;
+            chunk.copy_from_slice(&self.buf.data()[1..=chunk.len()]);
+        }
+
+        Ok(())
+    }
+
+    /// Read and parse the basic table of the chip's SFDP parameters.
+    pub fn sfdp(&mut self) -> Result<Sfdp> {
+        let mut header = [0; 8];
+        self.read_sfdp(0, &mut header)?;
+
+        for index in 0..sfdp::parameter_count(&header)? {
+            let mut bytes = [0; 8];
+            self.read_sfdp(8 + 8 * index as u32, &mut bytes)?;
+            let parameter = ParameterHeader::from(bytes);
+
+            if parameter.id == BASIC_TABLE_ID {
+                let mut table = [0; BASIC_TABLE_LEN * 4];
+                let table = &mut table[..(parameter.len as usize).min(BASIC_TABLE_LEN) * 4];
+
+                self.read_sfdp(parameter.pointer, table)?;
+                return Sfdp::parse_basic(table);
+            }
+        }
+
+        Err(Error::NoSfdp)
+    }
+
+    /// Check that the chip responds with a stable JEDEC ID, then find the
+    /// highest of `speeds` at which it still does. The transport is left at
+    /// that speed.
+    pub fn check(&mut self, speeds: &[u32]) -> DeviceCheck {
+        check::run(&mut self.spi, speeds)
+    }
+
     pub fn write_enable(&mut self) -> Result {
         self.buf.set_op(Code::WriteEnable);
         self.send(Type::Op, 0)
@@ -54,32 +258,190 @@ impl<SPI: SpiDevice, B: FlashBuffer> Device<SPI, B> {
 
     pub fn write_status_enable(&mut self) -> Result {
         self.write_enable()?;
-        self.buf.set_op(Code::WriteStatusEnable);
-        self.send(Type::Op, 0)
+
+        if let Some(code) = self.state.family.status_write_enable() {
+            self.buf.set_op(code);
+            self.send(Type::Op, 0)?;
+        }
+
+        Ok(())
     }
 
-    pub fn write_block_protect_bits(&mut self, bp_bits: u8) -> Result {
-        let bp_bits = (bp_bits & 0xF) << 2;
+    /// Read status register `index`, counting from 0, where the family has
+    /// more than one.
+    pub fn read_status_register(&mut self, index: usize) -> Result<u8> {
+        let (code, _) = self.state.status_register(index)?;
+
+        self.buf.set_op(code);
+        self.send(Type::Op, 1)?;
+        Ok(*self.buf.get(0))
+    }
+
+    /// Write status register `index`, counting from 0.
+    pub fn write_status_register(&mut self, index: usize, value: u8) -> Result {
+        let (_, code) = self.state.status_register(index)?;
 
         self.write_status_enable()?;
-        self.buf.set_op(Code::WriteStatus);
-        *self.buf.get_mut(0) = bp_bits;
-        self.send(Type::Op, 1)
+        self.buf.set_op(code);
+        *self.buf.get_mut(0) = value;
+        self.send(Type::Op, 1)?;
+        self.state.status_written(index, value);
+        Ok(())
+    }
+
+    /// Write the block-protect bits, clearing the rest of the first status
+    /// register.
+    pub fn write_block_protect_bits(&mut self, bp_bits: u8) -> Result {
+        let status = self.state.block_protect_status(bp_bits);
+        self.write_status_register(0, status)
+    }
+
+    /// Read the block-protect bits from the status register, for the erase
+    /// methods to check against, along with any flags such as CMP in the
+    /// other status registers. Until they are read or written, the whole chip
+    /// is treated as protected.
+    pub fn read_block_protect_bits(&mut self) -> Result<u8> {
+        for index in 0..self.state.family.status_registers().len() {
+            if self.state.holds_protection(index) {
+                let value = self.read_status_register(index)?;
+                self.state.status_read(index, value);
+            }
+        }
+
+        Ok(self.state.block_protect)
     }
 
     pub fn write_byte(&mut self, addr: u32, byte: u8) -> Result {
-        self.wait_ready()?;
-        self.write_enable()?;
+        let data = [byte];
+        let plan = self.state.program(addr, &data);
+        self.run(plan)
+    }
 
-        self.buf.set_op_addr(Code::WriteByte, addr);
-        *self.buf.get_mut(0) = byte;
+    /// Program `data` from `addr`, which must have been erased.
+    ///
+    /// Chips with pages are programmed a page at a time, or as much as the
+    /// buffer holds, split wherever a page ends. Otherwise, pairs of bytes at
+    /// even addresses are programmed with auto address increment (AAI) word
+    /// programming. A leading byte at an odd address and a trailing odd byte
+    /// are programmed with [`write_byte`](Self::write_byte), as is everything
+    /// when the buffer cannot hold two bytes of data or the chip does not
+    /// support AAI. AAI mode is always left afterwards.
+    pub fn write(&mut self, addr: u32, data: &[u8]) -> Result {
+        let plan = self.state.write(addr, data, self.buf.len())?;
+        self.run(plan)
+    }
 
-        self.send(Type::OpAddr, 1)
+    /// Perform the steps of `plan`, returning the first error. Steps which
+    /// must follow a failure, such as leaving AAI mode, are still performed.
+    fn run(&mut self, mut plan: impl Plan) -> Result {
+        let mut result = Ok(());
+
+        loop {
+            let step = match plan.next(&mut self.state, &mut self.buf) {
+                Ok(Some(step)) => self.step(step),
+                Ok(None) => return result,
+                Err(err) => Err(err),
+            };
+
+            if let Err(err) = step {
+                result = result.and(Err(err));
+
+                if !plan.fail() {
+                    return result;
+                }
+            }
+        }
+    }
+
+    fn step(&mut self, step: Step) -> Result {
+        match step {
+            Step::Send(op, data_len) => self.send(op, data_len),
+            Step::Wait(busy, timeout_us) => self.wait_while(timeout_us, busy),
+        }
+    }
+
+    /// Suspend the program or erase in progress, so that other parts of the
+    /// chip can be read, and wait for the chip to stop.
+    pub fn suspend(&mut self) -> Result {
+        let (code, _) = self
+            .state
+            .family
+            .suspend_resume()
+            .ok_or(Error::NotSupported)?;
+
+        self.buf.set_op(code);
+        self.send(Type::Op, 0)?;
+
+        let timeout_us = self.state.timeouts.status_us;
+        self.wait_ready_us(timeout_us)
+    }
+
+    /// Resume the suspended program or erase.
+    pub fn resume(&mut self) -> Result {
+        let (_, code) = self
+            .state
+            .family
+            .suspend_resume()
+            .ok_or(Error::NotSupported)?;
+
+        self.buf.set_op(code);
+        self.send(Type::Op, 0)
+    }
+
+    /// Erase the 4K sector containing `addr`.
+    pub fn erase_sector(&mut self, addr: u32) -> Result {
+        let plan = self.state.erase(SECTOR_LEN, addr)?;
+        self.run(plan)
+    }
+
+    /// Erase the 32K block containing `addr`.
+    pub fn erase_block32(&mut self, addr: u32) -> Result {
+        let plan = self.state.erase(BLOCK32_LEN, addr)?;
+        self.run(plan)
+    }
+
+    /// Erase the 64K block containing `addr`.
+    pub fn erase_block64(&mut self, addr: u32) -> Result {
+        let plan = self.state.erase(BLOCK64_LEN, addr)?;
+        self.run(plan)
+    }
+
+    /// Erase the whole chip, which must not be protected at all.
+    pub fn erase_chip(&mut self) -> Result {
+        let plan = self.state.erase_chip()?;
+        self.run(plan)
+    }
+
+    /// Erase every sector overlapping `len` bytes from `addr`, using the
+    /// largest aligned blocks which fit and the chip supports. The whole range
+    /// is checked before anything is erased.
+    pub fn erase_range(&mut self, addr: u32, len: u32) -> Result {
+        let plan = self.state.erase_range(addr, len)?;
+        self.run(plan)
     }
 
     pub fn read(&mut self, addr: u32, len: usize) -> Result<&[u8]> {
-        self.buf.set_op_addr(Code::Read, addr);
-        self.send(Type::OpAddr, len)?;
+        match self.read_mode() {
+            ReadMode::Single => {
+                let op = self.state.set_op_addr(&mut self.buf, Code::Read, addr)?;
+                self.send(op, len)?;
+            }
+            mode => {
+                let addr_bytes = addr.to_be_bytes();
+                let (code, address) = match self.state.address_mode {
+                    AddressMode::Three if addr > 0xFF_FFFF => return Err(Error::AddressOutOfRange),
+                    AddressMode::Three => (mode.code(), &addr_bytes[1..]),
+                    AddressMode::Four => (mode.code(), &addr_bytes[..]),
+                    AddressMode::FourByteCodes => (mode.code().to_four_byte(), &addr_bytes[..]),
+                };
+
+                let words = &mut self.buf.data_mut()[..len];
+                words.fill(0);
+                self.spi
+                    .read_multi_line(mode.phases(), code.to_instruction(), address, words)?;
+            }
+        }
+
         Ok(&self.buf.data()[..len])
     }
 
@@ -87,10 +449,716 @@ impl<SPI: SpiDevice, B: FlashBuffer> Device<SPI, B> {
         self.read(addr, 4096 - (addr & 0xFFF) as usize)
     }
 
+    /// Poll the status register until the chip is no longer busy, for up to
+    /// the timeout of the last operation started.
     pub fn wait_ready(&mut self) -> Result {
-        while self.read_status()?.is_busy() {}
+        self.wait_ready_us(self.state.busy_us)
+    }
+
+    /// Poll the status register until the chip is no longer busy, for up to
+    /// `timeout_us`.
+    pub fn wait_ready_us(&mut self, timeout_us: u32) -> Result {
+        self.wait_while(timeout_us, Busy::Status)
+    }
+
+    fn wait_while(&mut self, timeout_us: u32, busy: Busy) -> Result {
+        let mut deadline = Deadline::new(self.state.clock, timeout_us);
+
+        while self.is_busy(busy)? {
+            if deadline.is_expired() {
+                return Err(Error::Timeout);
+            }
+
+            match self.spi.delay_us(POLL_INTERVAL_US) {
+                Ok(()) => (),
+                Err(err) if err.kind() == ErrorKind::NotImplemented => (),
+                Err(err) => return Err(err.into()),
+            }
+
+            deadline.delayed(POLL_INTERVAL_US);
+        }
+
         Ok(())
     }
+
+    fn is_busy(&mut self, busy: Busy) -> Result<bool> {
+        Ok(match busy {
+            Busy::Status => self.read_status()?.is_busy(),
+            Busy::Aai => {
+                let status = self.read_status()?;
+                status.is_busy() || status.is_auto_increment_mode()
+            }
+            Busy::Output => {
+                let mut level = [0];
+                self.spi.transfer(&mut level)?;
+                level[0] != 0xFF
+            }
+        })
+    }
+}
+
+impl<SPI: BackgroundTransfer + ChipSelect, B: FlashBuffer, F: Family> Device<SPI, B, F> {
+    /// Start reading into `words` from `addr` in the background. The chip
+    /// stays selected until [`finish_read`](Self::finish_read).
+    pub fn start_read(&mut self, addr: u32, words: &'static mut [u8]) -> Result {
+        let op = self.state.set_op_addr(&mut self.buf, Code::Read, addr)?;
+        let header = self.buf.op(op, 0);
+
+        self.spi.select()?;
+        self.spi.raw_transfer_or_deselect(header)?;
+
+        words.fill(0);
+        self.spi
+            .raw_start_transfer(words)
+            .map_err(|err| self.spi.deselect().map_or(SpiError::ChipDeselect, |_| err))?;
+
+        Ok(())
+    }
+
+    /// Whether the read in progress has finished.
+    pub fn is_read_done(&self) -> bool {
+        self.spi.is_done()
+    }
+
+    /// Wait for the read in progress to finish and return its buffer.
+    pub fn finish_read(&mut self) -> Result<&'static mut [u8]> {
+        Ok(self.spi.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{AnyFamily, Buffer, Device, Error, JedecId, ReadMode, ReadModes, Size};
+    use crate::IdCheck;
+    use crate::{AddressMode, Chip, W25q};
+    use rpio_spi::{
+        diagnostics::{ClockSweep, SWEEP_ROUNDS},
+        Error as SpiError, Lines, MockSpi, Transaction,
+    };
+    use std::vec::Vec;
+
+    fn device(mock: &MockSpi) -> Device<MockSpi, Buffer<9>> {
+        Device::new(mock.clone(), Size::from_mb(1).unwrap(), Buffer::new())
+    }
+
+    #[test]
+    fn write_byte() {
+        let mock = MockSpi::with_expectations(&[
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0], &[0, 0x01]),
+            Transaction::Deselect,
+            Transaction::DelayUs(10),
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0x01], &[0, 0x00]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::write(&[0x06]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::write(&[0x02, 0x12, 0x34, 0x56, 0xAB]),
+            Transaction::Deselect,
+        ]);
+
+        device(&mock).write_byte(0x00123456, 0xAB).unwrap();
+        mock.done();
+    }
+
+    #[test]
+    fn write_empty() {
+        let mock = MockSpi::with_expectations(&[]);
+
+        device(&mock).write(0x00123456, &[]).unwrap();
+        device(&mock).erase_range(u32::MAX, 0).unwrap();
+        mock.done();
+    }
+
+    #[test]
+    fn read() {
+        let mock = MockSpi::with_expectations(&[
+            Transaction::Select,
+            Transaction::transfer(&[0x03, 0, 0x10, 0, 0, 0], &[0, 0, 0, 0, 1, 2]),
+            Transaction::Deselect,
+        ]);
+
+        assert_eq!(device(&mock).read(0x1000, 2), Ok(&[1, 2][..]));
+        mock.done();
+    }
+
+    #[test]
+    fn quad_read() {
+        let mock = MockSpi::with_expectations(&[Transaction::multi_line_read(
+            ReadMode::QuadIo.phases(),
+            0xEB,
+            &[0, 0x10, 0],
+            &[1, 2],
+        )]);
+        mock.set_max_lines(Lines::Quad);
+
+        let mut device = device(&mock).with_read_modes(ReadModes::ALL);
+
+        assert_eq!(device.read_mode(), ReadMode::QuadIo);
+        assert_eq!(device.read(0x1000, 2), Ok(&[1, 2][..]));
+        mock.done();
+    }
+
+    #[test]
+    fn wait_ready_timeout() {
+        let mock = MockSpi::with_expectations(&[
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0], &[0, 0x01]),
+            Transaction::Deselect,
+            Transaction::DelayUs(10),
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0x01], &[0, 0x01]),
+            Transaction::Deselect,
+            Transaction::DelayUs(10),
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0x01], &[0, 0x01]),
+            Transaction::Deselect,
+        ]);
+
+        assert_eq!(device(&mock).wait_ready_us(20), Err(Error::Timeout));
+        mock.done();
+    }
+
+    #[test]
+    fn wait_ready_without_delay() {
+        let mock = MockSpi::new();
+
+        for stale in [0, 1] {
+            mock.expect_all(&[
+                Transaction::Select,
+                Transaction::transfer(&[0x05, stale], &[0, 0x01]),
+                Transaction::Deselect,
+            ])
+            .expect_err(Transaction::DelayUs(10), SpiError::NotImplemented);
+        }
+
+        mock.expect_all(&[
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0x01], &[0, 0x01]),
+            Transaction::Deselect,
+        ]);
+
+        // Each poll counts as the interval even when the transport can't wait.
+        assert_eq!(device(&mock).wait_ready_us(20), Err(Error::Timeout));
+        mock.done();
+    }
+
+    #[test]
+    fn wait_ready_clock() {
+        std::thread_local!(static NOW: core::cell::Cell<u64> = const { core::cell::Cell::new(0) });
+
+        fn clock() -> u64 {
+            NOW.with(|now| now.replace(now.get() + 15))
+        }
+
+        let mock = MockSpi::new();
+
+        for stale in [0, 1] {
+            mock.expect_all(&[
+                Transaction::Select,
+                Transaction::transfer(&[0x05, stale], &[0, 0x01]),
+                Transaction::Deselect,
+            ])
+            .expect_err(Transaction::DelayUs(10), SpiError::NotImplemented);
+        }
+
+        mock.expect_all(&[
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0x01], &[0, 0x01]),
+            Transaction::Deselect,
+        ]);
+
+        let mut device = device(&mock).with_clock(clock);
+        assert_eq!(device.wait_ready_us(40), Err(Error::Timeout));
+        mock.done();
+    }
+
+    fn erases(mock: &MockSpi) -> Vec<Vec<u8>> {
+        mock.log()
+            .into_iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Transfer(write, _) if [0x20, 0x52, 0xD8, 0xC7].contains(&write[0]) => {
+                    Some(write)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn erase() {
+        let mock = MockSpi::new();
+        let mut device = device(&mock);
+
+        assert_eq!(device.erase_sector(0), Err(Error::SectorOutOfRange));
+        assert_eq!(device.erase_chip(), Err(Error::SectorOutOfRange));
+
+        device.write_block_protect_bits(0b0001).unwrap();
+        assert_eq!(device.erase_sector(0xF0000), Err(Error::SectorOutOfRange));
+        assert_eq!(device.erase_block64(0xE8000), Ok(()));
+
+        device.write_block_protect_bits(0).unwrap();
+        assert_eq!(device.erase_sector(0x100000), Err(Error::AddressOutOfRange));
+        assert_eq!(device.erase_sector(0x1234), Ok(()));
+        assert_eq!(device.erase_block32(0x9000), Ok(()));
+        assert_eq!(device.erase_chip(), Ok(()));
+
+        assert_eq!(
+            erases(&mock),
+            [
+                [0xD8, 0x0E, 0x00, 0x00].to_vec(),
+                [0x20, 0x00, 0x10, 0x00].to_vec(),
+                [0x52, 0x00, 0x80, 0x00].to_vec(),
+                [0xC7].to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn erase_range() {
+        let mock = MockSpi::new();
+        let mut device = device(&mock);
+        device.read_block_protect_bits().unwrap();
+
+        assert_eq!(
+            device.erase_range(0xFF000, 0x2000),
+            Err(Error::AddressOutOfRange)
+        );
+        device.erase_range(0x7800, 0x19000).unwrap();
+
+        assert_eq!(
+            erases(&mock),
+            [
+                [0x20, 0x00, 0x70, 0x00].to_vec(),
+                [0x52, 0x00, 0x80, 0x00].to_vec(),
+                [0xD8, 0x01, 0x00, 0x00].to_vec(),
+                [0x20, 0x02, 0x00, 0x00].to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn write() {
+        let mock = MockSpi::new();
+        let mut device = device(&mock);
+        let data = [0x10, 0x22, 0x34, 0x82, 0x96, 0x20];
+
+        assert_eq!(device.write(0x1001, &data), Err(Error::SectorOutOfRange));
+
+        device.write_block_protect_bits(0).unwrap();
+        device.write(0x1001, &data).unwrap();
+
+        let writes: Vec<Vec<u8>> = mock
+            .log()
+            .into_iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Transfer(write, _) if [0x02, 0xAD, 0x04].contains(&write[0]) => {
+                    Some(write)
+                }
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            writes,
+            [
+                [0x02, 0x00, 0x10, 0x01, 0x10].to_vec(),
+                [0xAD, 0x00, 0x10, 0x02, 0x22, 0x34].to_vec(),
+                [0xAD, 0x82, 0x96].to_vec(),
+                [0x04].to_vec(),
+                [0x02, 0x00, 0x10, 0x06, 0x20].to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn write_busy_output() {
+        let mock = MockSpi::with_expectations(&[
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0], &[0, 0]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::write(&[0x70]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::write(&[0x06]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::write(&[0xAD, 0x00, 0x20, 0x00, 0x12, 0x34]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::transfer(&[0], &[0]),
+            Transaction::Deselect,
+            Transaction::DelayUs(10),
+            Transaction::Select,
+            Transaction::transfer(&[0], &[0xFF]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::write(&[0x04]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0x12], &[0, 0]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::write(&[0x80]),
+            Transaction::Deselect,
+        ]);
+        let mut device = device(&mock).with_busy_output(true);
+        device.state.block_protect = 0;
+
+        device.write(0x2000, &[0x12, 0x34]).unwrap();
+        mock.done();
+    }
+
+    /// Expect SFDP reads of `data` from `addr`, 3 bytes at a time.
+    fn expect_sfdp(mock: &MockSpi, addr: u32, data: &[u8]) {
+        for (addr, chunk) in (addr..).step_by(3).zip(data.chunks(3)) {
+            let [_, a2, a1, a0] = addr.to_be_bytes();
+            let mut write = [0x5A, a2, a1, a0, 0, 0, 0, 0].to_vec();
+            let mut read = [0; 5].to_vec();
+
+            write.truncate(5 + chunk.len());
+            read.extend_from_slice(chunk);
+
+            mock.expect_all(&[
+                Transaction::Select,
+                Transaction::transfer(&write, &read),
+                Transaction::Deselect,
+            ]);
+        }
+    }
+
+    fn probe(
+        id: [u8; 3],
+        sfdp: &[(u32, &[u8])],
+    ) -> (
+        MockSpi,
+        Result<Device<MockSpi, Buffer<9>, AnyFamily>, Error>,
+    ) {
+        let mock = MockSpi::with_expectations(&[
+            Transaction::Select,
+            Transaction::transfer(&[0x9F, 0, 0, 0], &[0, id[0], id[1], id[2]]),
+            Transaction::Deselect,
+        ]);
+
+        for &(addr, data) in sfdp {
+            expect_sfdp(&mock, addr, data);
+        }
+
+        let device = Device::probe(mock.clone(), Buffer::new());
+        (mock, device)
+    }
+
+    #[test]
+    fn probe_known_chip() {
+        let (mock, device) = probe([0xC2, 0x20, 0x17], &[]);
+        let mut device = device.unwrap();
+        mock.done();
+
+        assert_eq!(device.chip().map(|chip| chip.name), Some("MX25L6406E"));
+        assert_eq!(device.state.size, Size::from_mb(8).unwrap());
+        assert_eq!(device.read_mode(), ReadMode::Single);
+        assert!(device.state.read_modes.dual_io && !device.state.read_modes.quad_io);
+
+        device.state.block_protect = 0;
+        assert_eq!(device.erase_block32(0), Err(Error::NotSupported));
+
+        let mock = MockSpi::new();
+        device.spi = mock.clone();
+        device.erase_range(0x8000, 3 * 0x8000).unwrap();
+
+        assert_eq!(
+            erases(&mock),
+            [
+                [0x20, 0x00, 0x80, 0x00].to_vec(),
+                [0x20, 0x00, 0x90, 0x00].to_vec(),
+                [0x20, 0x00, 0xA0, 0x00].to_vec(),
+                [0x20, 0x00, 0xB0, 0x00].to_vec(),
+                [0x20, 0x00, 0xC0, 0x00].to_vec(),
+                [0x20, 0x00, 0xD0, 0x00].to_vec(),
+                [0x20, 0x00, 0xE0, 0x00].to_vec(),
+                [0x20, 0x00, 0xF0, 0x00].to_vec(),
+                [0xD8, 0x01, 0x00, 0x00].to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn probe_unknown_chip() {
+        let (mock, device) = probe([0xEF, 0x40, 0x20], &[(0, &[0; 8])]);
+        mock.done();
+
+        assert_eq!(
+            device.err(),
+            Some(Error::UnknownChip(JedecId::from([0xEF, 0x40, 0x20])))
+        );
+        assert_eq!(probe([0xFF, 0xFF, 0xFF], &[]).1.err(), Some(Error::NoChip));
+        assert_eq!(probe([0x00, 0x00, 0x00], &[]).1.err(), Some(Error::NoChip));
+    }
+
+    #[test]
+    fn probe_sfdp() {
+        let header = [0x53, 0x46, 0x44, 0x50, 0x05, 0x01, 0x00, 0xFF];
+        let parameter = [0x00, 0x05, 0x01, 0x09, 0x10, 0x00, 0x00, 0xFF];
+        let basic = [
+            0xE5, 0x20, 0xF1, 0xFF, 0xFF, 0xFF, 0xFF, 0x03, 0x44, 0xEB, 0x08, 0x6B, 0x08, 0x3B,
+            0x42, 0xBB, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0xFF, 0xFF, 0x40, 0xEB,
+            0x0C, 0x20, 0x0F, 0x52, 0x10, 0xD8, 0x00, 0x00,
+        ];
+
+        let (mock, device) = probe(
+            [0xEF, 0x60, 0x17],
+            &[(0, &header), (8, &parameter), (0x10, &basic)],
+        );
+        let device = device.unwrap();
+        mock.done();
+
+        let chip = device.chip().unwrap();
+
+        assert_eq!(chip.name, "SFDP");
+        assert_eq!(*device.family(), AnyFamily::W25q);
+        assert_eq!(device.state.size, Size::from_mb(8).unwrap());
+        assert_eq!(device.state.page_len, 256);
+        assert_eq!(device.state.read_modes, ReadModes::ALL);
+    }
+
+    fn w25q64(mock: &MockSpi) -> Device<MockSpi, Buffer<9>, W25q> {
+        let chip = Chip::find(JedecId::from([0xEF, 0x40, 0x17])).unwrap();
+        let mut device = device(mock).with_family(W25q).with_chip(chip).unwrap();
+        device.state.block_protect = 0;
+        device
+    }
+
+    #[test]
+    fn page_write() {
+        let mock = MockSpi::new();
+        let mut device = w25q64(&mock);
+
+        device
+            .write(0xFE, &[0x10, 0x20, 0x30, 0x40, 0x50, 0x60, 0x70])
+            .unwrap();
+
+        let writes: Vec<Vec<u8>> = mock
+            .log()
+            .into_iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Transfer(write, _) if [0x02, 0x50].contains(&write[0]) => Some(write),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            writes,
+            [
+                [0x02, 0x00, 0x00, 0xFE, 0x10, 0x20].to_vec(),
+                [0x02, 0x00, 0x01, 0x00, 0x30, 0x40, 0x50, 0x60].to_vec(),
+                [0x02, 0x00, 0x01, 0x04, 0x70].to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn protect_flags() {
+        let mock = MockSpi::new();
+        let mut device = w25q64(&mock);
+
+        device.write_status_register(0, 0x20).unwrap();
+        assert_eq!(device.state.block_protect, 0);
+        assert_eq!(device.erase_sector(0), Err(Error::SectorOutOfRange));
+
+        device.write_block_protect_bits(0).unwrap();
+        device.write_status_register(1, 0x42).unwrap();
+        assert_eq!(device.erase_chip(), Err(Error::SectorOutOfRange));
+
+        // The unscripted mock reads back the last byte written: CMP.
+        let start = mock.log().len();
+        assert_eq!(device.read_block_protect_bits(), Ok(0));
+        assert_eq!(device.erase_sector(0), Err(Error::SectorOutOfRange));
+
+        device.write_status_register(1, 0x02).unwrap();
+        assert_eq!(device.read_block_protect_bits(), Ok(0));
+        assert_eq!(device.erase_sector(0), Ok(()));
+
+        let reads: Vec<u8> = mock.log()[start..]
+            .iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Transfer(write, _) if write.len() == 2 => Some(write[0]),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(reads[..2], [0x05, 0x35]);
+    }
+
+    #[test]
+    fn status_registers() {
+        let mock = MockSpi::with_expectations(&[
+            Transaction::Select,
+            Transaction::transfer(&[0x35, 0], &[0, 0x02]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::write(&[0x06]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::write(&[0x01, 0x08]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::write(&[0x75]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0x08], &[0, 0]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::write(&[0x7A]),
+            Transaction::Deselect,
+        ]);
+        let mut device = w25q64(&mock);
+
+        assert_eq!(device.read_status_register(1), Ok(0x02));
+        assert_eq!(device.read_status_register(3), Err(Error::NotSupported));
+
+        device.write_block_protect_bits(0b1010).unwrap();
+        assert_eq!(device.state.block_protect, 0b010);
+        assert_eq!(device.erase_sector(0x7C0000), Err(Error::SectorOutOfRange));
+
+        device.suspend().unwrap();
+        device.resume().unwrap();
+        mock.done();
+
+        assert_eq!(
+            Device::new(mock, Size::default(), Buffer::<9>::new()).suspend(),
+            Err(Error::NotSupported)
+        );
+    }
+
+    #[test]
+    fn four_byte_addresses() {
+        let mock = MockSpi::new();
+        let chip = Chip::find(JedecId::from([0xEF, 0x40, 0x19])).unwrap();
+        let mut device = device(&mock).with_family(W25q).with_chip(chip).unwrap();
+        device.state.block_protect = 0;
+
+        assert_eq!(device.address_mode(), AddressMode::Four);
+
+        device.read(0x1234567, 2).unwrap();
+        device.write(0x1FFFFFE, &[0x10, 0x20]).unwrap();
+        device.set_address_mode(AddressMode::FourByteCodes).unwrap();
+        device.read(0x1234567, 2).unwrap();
+        device.erase_sector(0x1000000).unwrap();
+
+        let headers: Vec<Vec<u8>> = mock
+            .log()
+            .into_iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Transfer(write, _) if write[0] != 0x05 && write[0] != 0x06 => {
+                    Some(write[..write.len().min(5)].to_vec())
+                }
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            headers,
+            [
+                [0xB7].to_vec(),
+                [0x03, 0x01, 0x23, 0x45, 0x67].to_vec(),
+                [0x02, 0x01, 0xFF, 0xFF, 0xFE].to_vec(),
+                [0xE9].to_vec(),
+                [0x13, 0x01, 0x23, 0x45, 0x67].to_vec(),
+                [0x21, 0x01, 0x00, 0x00, 0x00].to_vec(),
+            ]
+        );
+
+        let mut device = Device::new(mock, Size::from_mb(32).unwrap(), Buffer::<9>::new());
+        assert_eq!(device.read(0x1000000, 1), Err(Error::AddressOutOfRange));
+    }
+
+    fn read_id() -> [Transaction; 3] {
+        [
+            Transaction::Select,
+            Transaction::transfer(&[0x9F, 0, 0, 0], &[0, 0xBF, 0x25, 0x8E]),
+            Transaction::Deselect,
+        ]
+    }
+
+    #[test]
+    fn read_jedec_id() {
+        let mock = MockSpi::with_expectations(&read_id());
+
+        assert_eq!(
+            device(&mock).read_jedec_id(),
+            Ok(JedecId {
+                manufacturer: 0xBF,
+                memory_type: 0x25,
+                capacity: 0x8E,
+            })
+        );
+        mock.done();
+    }
+
+    #[test]
+    fn check() {
+        let mut expectations = Vec::new();
+        expectations.extend(read_id());
+        expectations.extend(read_id());
+        expectations.push(Transaction::ClockSpeed(1_000_000));
+        (0..SWEEP_ROUNDS).for_each(|_| expectations.extend(read_id()));
+        let mock = MockSpi::with_expectations(&expectations);
+
+        let check = device(&mock).check(&[1_000_000]);
+
+        assert_eq!(
+            check.id,
+            Ok(IdCheck::Found(JedecId::from([0xBF, 0x25, 0x8E])))
+        );
+        assert_eq!(
+            check.clock,
+            Some(Ok(ClockSweep {
+                highest: Some(1_000_000),
+                failed: None,
+            }))
+        );
+        assert!(check.is_passed());
+        mock.done();
+
+        let check = device(&MockSpi::new()).check(&[1_000_000]);
+
+        assert_eq!(check.id, Ok(IdCheck::StuckLow));
+        assert_eq!(check.clock, None);
+    }
+
+    #[test]
+    fn send_errors() {
+        let mock = MockSpi::new();
+        mock.expect_err(Transaction::Select, SpiError::ChipSelect)
+            .expect(Transaction::Select)
+            .expect_err(Transaction::write(&[0x06]), SpiError::Transfer)
+            .expect(Transaction::Deselect)
+            .expect(Transaction::Select)
+            .expect_err(Transaction::write(&[0x06]), SpiError::Transfer)
+            .expect_err(Transaction::Deselect, SpiError::ChipDeselect)
+            .expect(Transaction::Select)
+            .expect(Transaction::write(&[0x06]))
+            .expect_err(Transaction::Deselect, SpiError::ChipDeselect);
+
+        let mut device = device(&mock);
+
+        assert_eq!(device.write_enable(), Err(Error::Spi(SpiError::ChipSelect)));
+        assert_eq!(device.write_enable(), Err(Error::Spi(SpiError::Transfer)));
+        assert_eq!(
+            device.write_enable(),
+            Err(Error::Spi(SpiError::ChipDeselect))
+        );
+        assert_eq!(
+            device.write_enable(),
+            Err(Error::Spi(SpiError::ChipDeselect))
+        );
+        mock.done();
+    }
 }
 
 // Read = 0x03,
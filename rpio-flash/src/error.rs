@@ -1,11 +1,33 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use super::id::JedecId;
+use rpio_spi::Error as SpiError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Error {
     ChipSize,
-    SPIChipSelect,
-    SPIChipDeselect,
-    SPITransfer,
-    SPISetClockSpeed,
+    Spi(SpiError),
     FlashSizeNotSupported,
+    /// The address is beyond the end of the chip.
     AddressOutOfRange,
+    /// The sector is protected by the block-protect bits.
     SectorOutOfRange,
+    /// The chip stayed busy for longer than its [`Timeouts`](super::Timeouts)
+    /// allow.
+    Timeout,
+    /// The JEDEC ID read was all 0s or all 1s, so no chip answered.
+    NoChip,
+    /// The chip answered with an ID not in [`CHIPS`](super::CHIPS).
+    UnknownChip(JedecId),
+    /// The chip has no valid SFDP signature or basic parameter table.
+    NoSfdp,
+    /// The chip does not support the instruction.
+    NotSupported,
+    /// The address or length is not a multiple of the unit the operation
+    /// works in.
+    NotAligned,
+}
+
+impl From<SpiError> for Error {
+    fn from(err: SpiError) -> Self {
+        Error::Spi(err)
+    }
 }
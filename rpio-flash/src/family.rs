@@ -0,0 +1,237 @@
+use super::chip::Chip;
+use super::op::Code;
+use super::size::Size;
+
+/// The instructions and register layout shared by a family of chips, beyond
+/// those in [`Chip`].
+pub trait Family: Sized {
+    /// The family of `chip`, if this type can drive it.
+    fn from_chip(chip: &Chip) -> Option<Self>;
+
+    /// The instruction which must follow Write Enable before writing a status
+    /// register, if any.
+    fn status_write_enable(&self) -> Option<Code> {
+        None
+    }
+
+    /// The instructions reading and writing each status register, the first
+    /// register first.
+    fn status_registers(&self) -> &'static [(Code, Code)] {
+        &[(Code::ReadStatus, Code::WriteStatus)]
+    }
+
+    /// The bits of the first status register holding the block-protect bits,
+    /// for chips without a known layout such as those described by SFDP.
+    fn protect_mask(&self) -> u8 {
+        0x1C
+    }
+
+    /// The bits of a status register, counting from 0, which change the
+    /// region protected besides the block-protect bits, such as TB, SEC and
+    /// CMP. While any are set the whole chip is treated as protected.
+    fn protect_flags(&self, _index: usize) -> u8 {
+        0
+    }
+
+    /// The start of the region at the top of the chip protected by the
+    /// block-protect bits, if any. Each further bit doubles the region from
+    /// 64K by default.
+    fn protected_offset(&self, size: Size, bp_bits: u8) -> Option<u32> {
+        size.protected_offset(bp_bits)
+    }
+
+    /// The instructions which suspend and resume a program or erase, if the
+    /// family supports them.
+    fn suspend_resume(&self) -> Option<(Code, Code)> {
+        None
+    }
+}
+
+/// SST25 chips, which need Enable Write Status Register before writing the
+/// status register.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Sst25;
+
+impl Family for Sst25 {
+    fn from_chip(chip: &Chip) -> Option<Self> {
+        (chip.family == AnyFamily::Sst25).then_some(Sst25)
+    }
+
+    fn status_write_enable(&self) -> Option<Code> {
+        Some(Code::WriteStatusEnable)
+    }
+
+    /// BP0-3, with BPL and AAI above them.
+    fn protect_mask(&self) -> u8 {
+        0x3C
+    }
+}
+
+/// Macronix MX25 chips.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Mx25;
+
+impl Family for Mx25 {
+    fn from_chip(chip: &Chip) -> Option<Self> {
+        (chip.family == AnyFamily::Mx25).then_some(Mx25)
+    }
+}
+
+/// Winbond W25Q chips, and the GigaDevice GD25Q chips compatible with them:
+/// three status registers, suspend and resume, and a protected region which
+/// scales with the size of the chip.
+///
+/// The protected region is only worked out with the TB, SEC and CMP bits
+/// clear, which [`write_block_protect_bits`](super::Device::write_block_protect_bits)
+/// ensures for TB and SEC. While any of them are set the whole chip is
+/// treated as protected.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct W25q;
+
+impl Family for W25q {
+    fn from_chip(chip: &Chip) -> Option<Self> {
+        (chip.family == AnyFamily::W25q).then_some(W25q)
+    }
+
+    fn status_registers(&self) -> &'static [(Code, Code)] {
+        &[
+            (Code::ReadStatus, Code::WriteStatus),
+            (Code::ReadStatus2, Code::WriteStatus2),
+            (Code::ReadStatus3, Code::WriteStatus3),
+        ]
+    }
+
+    /// TB and SEC above BP0-2 in the first register, or TB above BP0-3 in
+    /// chips above 16 MB, and CMP in the second.
+    fn protect_flags(&self, index: usize) -> u8 {
+        match index {
+            0 => 0x60,
+            1 => 0x40,
+            _ => 0,
+        }
+    }
+
+    /// BP0-2 protect the top 1/64th of the chip, or 64K if more, doubling
+    /// with each further bit. Chips above 16 MB add BP3 and start from 64K.
+    fn protected_offset(&self, size: Size, bp_bits: u8) -> Option<u32> {
+        if bp_bits == 0 {
+            return None;
+        }
+
+        let unit = match size.size() > 0x100_0000 {
+            true => 0x10000,
+            false => (size.size() / 64).max(0x10000),
+        };
+        let region = match bp_bits {
+            1..=9 => unit << (bp_bits - 1),
+            _ => size.size(),
+        };
+
+        Some(size.size().saturating_sub(region))
+    }
+
+    fn suspend_resume(&self) -> Option<(Code, Code)> {
+        Some((Code::Suspend, Code::Resume))
+    }
+}
+
+/// Any of the families, chosen at run time, such as by
+/// [`Device::probe`](super::Device::probe).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AnyFamily {
+    #[default]
+    Sst25,
+    Mx25,
+    W25q,
+}
+
+impl Family for AnyFamily {
+    fn from_chip(chip: &Chip) -> Option<Self> {
+        Some(chip.family)
+    }
+
+    fn status_write_enable(&self) -> Option<Code> {
+        match self {
+            AnyFamily::Sst25 => Sst25.status_write_enable(),
+            AnyFamily::Mx25 => Mx25.status_write_enable(),
+            AnyFamily::W25q => W25q.status_write_enable(),
+        }
+    }
+
+    fn status_registers(&self) -> &'static [(Code, Code)] {
+        match self {
+            AnyFamily::Sst25 => Sst25.status_registers(),
+            AnyFamily::Mx25 => Mx25.status_registers(),
+            AnyFamily::W25q => W25q.status_registers(),
+        }
+    }
+
+    fn protect_mask(&self) -> u8 {
+        match self {
+            AnyFamily::Sst25 => Sst25.protect_mask(),
+            AnyFamily::Mx25 => Mx25.protect_mask(),
+            AnyFamily::W25q => W25q.protect_mask(),
+        }
+    }
+
+    fn protect_flags(&self, index: usize) -> u8 {
+        match self {
+            AnyFamily::Sst25 => Sst25.protect_flags(index),
+            AnyFamily::Mx25 => Mx25.protect_flags(index),
+            AnyFamily::W25q => W25q.protect_flags(index),
+        }
+    }
+
+    fn protected_offset(&self, size: Size, bp_bits: u8) -> Option<u32> {
+        match self {
+            AnyFamily::Sst25 => Sst25.protected_offset(size, bp_bits),
+            AnyFamily::Mx25 => Mx25.protected_offset(size, bp_bits),
+            AnyFamily::W25q => W25q.protected_offset(size, bp_bits),
+        }
+    }
+
+    fn suspend_resume(&self) -> Option<(Code, Code)> {
+        match self {
+            AnyFamily::Sst25 => Sst25.suspend_resume(),
+            AnyFamily::Mx25 => Mx25.suspend_resume(),
+            AnyFamily::W25q => W25q.suspend_resume(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnyFamily, Family, Size, Sst25, W25q};
+    use crate::{Chip, JedecId};
+
+    #[test]
+    fn protected_offset() {
+        let mb2 = Size::from_mb(2).unwrap();
+        let mb8 = Size::from_mb(8).unwrap();
+        let mb32 = Size::from_mb(32).unwrap();
+
+        assert_eq!(W25q.protected_offset(mb8, 0b000), None);
+        assert_eq!(W25q.protected_offset(mb8, 0b001), Some(0x7E0000));
+        assert_eq!(W25q.protected_offset(mb8, 0b110), Some(0x400000));
+        assert_eq!(W25q.protected_offset(mb8, 0b111), Some(0));
+        assert_eq!(W25q.protected_offset(mb8, 0b1001), Some(0));
+        assert_eq!(W25q.protected_offset(mb2, 0b001), Some(0x1F0000));
+        assert_eq!(W25q.protected_offset(mb2, 0b110), Some(0));
+        assert_eq!(W25q.protected_offset(mb32, 0b0001), Some(0x1FF0000));
+        assert_eq!(W25q.protected_offset(mb32, 0b1000), Some(0x1800000));
+        assert_eq!(W25q.protected_offset(mb32, 0b1001), Some(0x1000000));
+        assert_eq!(W25q.protected_offset(mb32, 0b1010), Some(0));
+
+        assert_eq!(Sst25.protected_offset(mb8, 0b001), Some(0x7F0000));
+        assert_eq!(AnyFamily::W25q.protected_offset(mb8, 0b001), Some(0x7E0000));
+    }
+
+    #[test]
+    fn from_chip() {
+        let gd25q64 = Chip::find(JedecId::from([0xC8, 0x40, 0x17])).unwrap();
+
+        assert_eq!(W25q::from_chip(gd25q64), Some(W25q));
+        assert_eq!(Sst25::from_chip(gd25q64), None);
+        assert_eq!(AnyFamily::from_chip(gd25q64), Some(AnyFamily::W25q));
+    }
+}
@@ -0,0 +1,36 @@
+/// The manufacturer, memory type and capacity bytes reported by the Read
+/// JEDEC ID instruction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct JedecId {
+    pub manufacturer: u8,
+    pub memory_type: u8,
+    pub capacity: u8,
+}
+
+impl JedecId {
+    /// The name of the manufacturer, for those with chips in
+    /// [`CHIPS`](super::CHIPS).
+    pub fn manufacturer_name(&self) -> Option<&'static str> {
+        match self.manufacturer {
+            0xBF => Some("SST"),
+            0xC2 => Some("Macronix"),
+            0xC8 => Some("GigaDevice"),
+            0xEF => Some("Winbond"),
+            _ => None,
+        }
+    }
+
+    pub fn to_bytes(self) -> [u8; 3] {
+        [self.manufacturer, self.memory_type, self.capacity]
+    }
+}
+
+impl From<[u8; 3]> for JedecId {
+    fn from([manufacturer, memory_type, capacity]: [u8; 3]) -> Self {
+        Self {
+            manufacturer,
+            memory_type,
+            capacity,
+        }
+    }
+}
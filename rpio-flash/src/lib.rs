@@ -1,15 +1,41 @@
 #![no_std]
 
 mod buffer;
+mod check;
+mod chip;
 mod device;
 mod error;
+mod family;
+mod id;
 mod op;
+mod sfdp;
 mod size;
+mod state;
 mod status;
+mod step;
+mod timeout;
+
+#[cfg(feature = "async")]
+mod asynch;
+
+#[cfg(feature = "async")]
+pub use asynch::*;
+
+#[cfg(feature = "storage")]
+mod storage;
+
+#[cfg(feature = "storage")]
+pub use storage::*;
 
 pub use buffer::*;
+pub use check::*;
+pub use chip::*;
 pub use device::*;
 pub use error::*;
+pub use family::*;
+pub use id::*;
 pub use op::*;
+pub use sfdp::*;
 pub use size::*;
 pub use status::*;
+pub use timeout::*;
@@ -1,38 +1,334 @@
-#[repr(usize)]
+use rpio_spi::{Decoder, Lines, Phases};
+
+/// The layout of the instruction and address before the data in the buffer.
 pub enum Type {
-    Op = 4,
-    OpAddr = 1,
-    ReadHighspeed = 0,
+    Op,
+    /// An instruction and 3-byte address.
+    OpAddr,
+    /// An instruction and 4-byte address.
+    OpAddr4,
+    ReadHighspeed,
 }
 
 impl Type {
+    /// Where the instruction starts in the buffer's 5 header bytes.
     pub fn to_offset(self) -> usize {
-        self as usize
+        match self {
+            Type::Op => 4,
+            Type::OpAddr => 1,
+            Type::OpAddr4 => 0,
+            Type::ReadHighspeed => 0,
+        }
     }
 }
 
+/// How addresses are sent to the chip.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AddressMode {
+    /// 3-byte addresses, reaching the first 16 MB.
+    #[default]
+    Three,
+    /// 4-byte addresses for every instruction, after Enter 4-Byte Address
+    /// Mode.
+    Four,
+    /// 4-byte addresses with the dedicated 4-byte instructions, leaving the
+    /// chip in 3-byte mode.
+    FourByteCodes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Code {
     Read = 0x03,
     ReadStatus = 0x05,
+    ReadStatus2 = 0x35,
+    ReadStatus3 = 0x15,
     ReadHighspeed = 0x0B,
+    ReadDualOutput = 0x3B,
+    ReadDualIo = 0xBB,
+    ReadQuadOutput = 0x6B,
+    ReadQuadIo = 0xEB,
+    Read4 = 0x13,
+    ReadHighspeed4 = 0x0C,
+    ReadDualOutput4 = 0x3C,
+    ReadDualIo4 = 0xBC,
+    ReadQuadOutput4 = 0x6C,
+    ReadQuadIo4 = 0xEC,
     ReadId = 0xAB,
     ReadJedecId = 0x9F,
+    ReadSfdp = 0x5A,
     WriteByte = 0x02,
+    WriteByte4 = 0x12,
     WriteAutoIncrement = 0xAD,
     WriteStatus = 0x01,
+    WriteStatus2 = 0x31,
+    WriteStatus3 = 0x11,
     EraseSector = 0x20,
     EraseBlock32 = 0x52,
     EraseBlock64 = 0xD8,
+    EraseSector4 = 0x21,
+    EraseBlock32Addr4 = 0x5C,
+    EraseBlock64Addr4 = 0xDC,
     EraseChip = 0xC7,
     WriteEnable = 0x06,
     WriteStatusEnable = 0x50,
     WriteDisable = 0x04,
     BusyStatusOutputEnable = 0x70,
     BusyStatusOutputDisable = 0x80,
+    Suspend = 0x75,
+    Resume = 0x7A,
+    Enter4ByteMode = 0xB7,
+    Exit4ByteMode = 0xE9,
 }
 
 impl Code {
     pub fn to_instruction(self) -> u8 {
         self as u8
     }
+
+    /// The dedicated 4-byte address instruction doing the same, if there is
+    /// one.
+    pub fn to_four_byte(self) -> Self {
+        match self {
+            Code::Read => Code::Read4,
+            Code::ReadHighspeed => Code::ReadHighspeed4,
+            Code::ReadDualOutput => Code::ReadDualOutput4,
+            Code::ReadDualIo => Code::ReadDualIo4,
+            Code::ReadQuadOutput => Code::ReadQuadOutput4,
+            Code::ReadQuadIo => Code::ReadQuadIo4,
+            Code::WriteByte => Code::WriteByte4,
+            Code::EraseSector => Code::EraseSector4,
+            Code::EraseBlock32 => Code::EraseBlock32Addr4,
+            Code::EraseBlock64 => Code::EraseBlock64Addr4,
+            code => code,
+        }
+    }
+
+    pub fn from_instruction(instruction: u8) -> Option<Self> {
+        Some(match instruction {
+            0x03 => Code::Read,
+            0x05 => Code::ReadStatus,
+            0x35 => Code::ReadStatus2,
+            0x15 => Code::ReadStatus3,
+            0x0B => Code::ReadHighspeed,
+            0x3B => Code::ReadDualOutput,
+            0xBB => Code::ReadDualIo,
+            0x6B => Code::ReadQuadOutput,
+            0xEB => Code::ReadQuadIo,
+            0x13 => Code::Read4,
+            0x0C => Code::ReadHighspeed4,
+            0x3C => Code::ReadDualOutput4,
+            0xBC => Code::ReadDualIo4,
+            0x6C => Code::ReadQuadOutput4,
+            0xEC => Code::ReadQuadIo4,
+            0xAB => Code::ReadId,
+            0x9F => Code::ReadJedecId,
+            0x5A => Code::ReadSfdp,
+            0x02 => Code::WriteByte,
+            0x12 => Code::WriteByte4,
+            0xAD => Code::WriteAutoIncrement,
+            0x01 => Code::WriteStatus,
+            0x31 => Code::WriteStatus2,
+            0x11 => Code::WriteStatus3,
+            0x20 => Code::EraseSector,
+            0x52 => Code::EraseBlock32,
+            0xD8 => Code::EraseBlock64,
+            0x21 => Code::EraseSector4,
+            0x5C => Code::EraseBlock32Addr4,
+            0xDC => Code::EraseBlock64Addr4,
+            0xC7 => Code::EraseChip,
+            0x06 => Code::WriteEnable,
+            0x50 => Code::WriteStatusEnable,
+            0x04 => Code::WriteDisable,
+            0x70 => Code::BusyStatusOutputEnable,
+            0x80 => Code::BusyStatusOutputDisable,
+            0x75 => Code::Suspend,
+            0x7A => Code::Resume,
+            0xB7 => Code::Enter4ByteMode,
+            0xE9 => Code::Exit4ByteMode,
+            _ => return None,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Code::Read => "Read",
+            Code::ReadStatus => "ReadStatus",
+            Code::ReadStatus2 => "ReadStatus2",
+            Code::ReadStatus3 => "ReadStatus3",
+            Code::ReadHighspeed => "ReadHighspeed",
+            Code::ReadDualOutput => "ReadDualOutput",
+            Code::ReadDualIo => "ReadDualIo",
+            Code::ReadQuadOutput => "ReadQuadOutput",
+            Code::ReadQuadIo => "ReadQuadIo",
+            Code::Read4 => "Read4",
+            Code::ReadHighspeed4 => "ReadHighspeed4",
+            Code::ReadDualOutput4 => "ReadDualOutput4",
+            Code::ReadDualIo4 => "ReadDualIo4",
+            Code::ReadQuadOutput4 => "ReadQuadOutput4",
+            Code::ReadQuadIo4 => "ReadQuadIo4",
+            Code::ReadId => "ReadId",
+            Code::ReadJedecId => "ReadJedecId",
+            Code::ReadSfdp => "ReadSfdp",
+            Code::WriteByte => "WriteByte",
+            Code::WriteByte4 => "WriteByte4",
+            Code::WriteAutoIncrement => "WriteAutoIncrement",
+            Code::WriteStatus => "WriteStatus",
+            Code::WriteStatus2 => "WriteStatus2",
+            Code::WriteStatus3 => "WriteStatus3",
+            Code::EraseSector => "EraseSector",
+            Code::EraseBlock32 => "EraseBlock32",
+            Code::EraseBlock64 => "EraseBlock64",
+            Code::EraseSector4 => "EraseSector4",
+            Code::EraseBlock32Addr4 => "EraseBlock32Addr4",
+            Code::EraseBlock64Addr4 => "EraseBlock64Addr4",
+            Code::EraseChip => "EraseChip",
+            Code::WriteEnable => "WriteEnable",
+            Code::WriteStatusEnable => "WriteStatusEnable",
+            Code::WriteDisable => "WriteDisable",
+            Code::BusyStatusOutputEnable => "BusyStatusOutputEnable",
+            Code::BusyStatusOutputDisable => "BusyStatusOutputDisable",
+            Code::Suspend => "Suspend",
+            Code::Resume => "Resume",
+            Code::Enter4ByteMode => "Enter4ByteMode",
+            Code::Exit4ByteMode => "Exit4ByteMode",
+        }
+    }
+}
+
+/// The ways of reading the array, from slowest to fastest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadMode {
+    Single,
+    DualOutput,
+    DualIo,
+    QuadOutput,
+    QuadIo,
+}
+
+impl ReadMode {
+    /// The multi-line modes, fastest first.
+    pub const MULTI_LINE: [ReadMode; 4] = [
+        ReadMode::QuadIo,
+        ReadMode::QuadOutput,
+        ReadMode::DualIo,
+        ReadMode::DualOutput,
+    ];
+
+    pub fn code(self) -> Code {
+        match self {
+            ReadMode::Single => Code::Read,
+            ReadMode::DualOutput => Code::ReadDualOutput,
+            ReadMode::DualIo => Code::ReadDualIo,
+            ReadMode::QuadOutput => Code::ReadQuadOutput,
+            ReadMode::QuadIo => Code::ReadQuadIo,
+        }
+    }
+
+    /// The line widths and dummy cycles of the read, after the instruction.
+    pub fn phases(self) -> Phases {
+        let (address, dummy_cycles, data) = match self {
+            ReadMode::Single => (Lines::Single, 0, Lines::Single),
+            ReadMode::DualOutput => (Lines::Single, 8, Lines::Dual),
+            ReadMode::DualIo => (Lines::Dual, 4, Lines::Dual),
+            ReadMode::QuadOutput => (Lines::Single, 8, Lines::Quad),
+            ReadMode::QuadIo => (Lines::Quad, 6, Lines::Quad),
+        };
+
+        Phases {
+            instruction: Lines::Single,
+            address,
+            dummy_cycles,
+            data,
+        }
+    }
+}
+
+/// The multi-line reads supported by the flash chip. Quad reads also need
+/// the chip's quad enable bit to be set, where it has one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReadModes {
+    pub dual_output: bool,
+    pub dual_io: bool,
+    pub quad_output: bool,
+    pub quad_io: bool,
+}
+
+impl ReadModes {
+    pub const ALL: ReadModes = ReadModes {
+        dual_output: true,
+        dual_io: true,
+        quad_output: true,
+        quad_io: true,
+    };
+
+    pub fn supports(&self, mode: ReadMode) -> bool {
+        match mode {
+            ReadMode::Single => true,
+            ReadMode::DualOutput => self.dual_output,
+            ReadMode::DualIo => self.dual_io,
+            ReadMode::QuadOutput => self.quad_output,
+            ReadMode::QuadIo => self.quad_io,
+        }
+    }
+
+    /// The fastest mode supported by both the chip and a transport with
+    /// `max_lines` data lines.
+    pub fn fastest(&self, max_lines: Lines) -> ReadMode {
+        ReadMode::MULTI_LINE
+            .into_iter()
+            .find(|&mode| self.supports(mode) && mode.phases().max_lines() <= max_lines)
+            .unwrap_or(ReadMode::Single)
+    }
+}
+
+/// Names the instruction at the start of each frame sent to the flash chip,
+/// for a [`Traced`](rpio_spi::Traced) transport.
+pub struct FlashDecoder;
+
+impl Decoder for FlashDecoder {
+    fn decode(&mut self, frame_start: bool, mosi: &[u8]) -> Option<&'static str> {
+        match (frame_start, mosi.first()) {
+            (true, Some(&instruction)) => {
+                Code::from_instruction(instruction).map(|code| code.name())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Code, FlashDecoder, ReadMode, ReadModes};
+    use rpio_spi::{Decoder, Lines};
+
+    #[test]
+    fn decoder() {
+        let mut decoder = FlashDecoder;
+
+        assert_eq!(decoder.decode(true, &[0x9F, 0, 0, 0]), Some("ReadJedecId"));
+        assert_eq!(decoder.decode(false, &[0x9F]), None);
+        assert_eq!(decoder.decode(true, &[0xFF]), None);
+        assert_eq!(decoder.decode(true, &[]), None);
+
+        for instruction in 0..=u8::MAX {
+            if let Some(code) = Code::from_instruction(instruction) {
+                assert_eq!(code.to_instruction(), instruction);
+            }
+        }
+    }
+
+    #[test]
+    fn fastest_read_mode() {
+        let dual = ReadModes {
+            dual_output: true,
+            dual_io: true,
+            ..ReadModes::default()
+        };
+
+        assert_eq!(ReadModes::ALL.fastest(Lines::Quad), ReadMode::QuadIo);
+        assert_eq!(ReadModes::ALL.fastest(Lines::Dual), ReadMode::DualIo);
+        assert_eq!(ReadModes::ALL.fastest(Lines::Single), ReadMode::Single);
+        assert_eq!(dual.fastest(Lines::Quad), ReadMode::DualIo);
+        assert_eq!(ReadModes::default().fastest(Lines::Quad), ReadMode::Single);
+    }
 }
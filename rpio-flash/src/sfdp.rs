@@ -0,0 +1,372 @@
+use super::chip::{Chip, EraseSizes};
+use super::error::Error;
+use super::family::{AnyFamily, Family};
+use super::id::JedecId;
+use super::op::{Code, ReadMode, ReadModes};
+use super::size::Size;
+use core::convert::TryFrom;
+
+/// "SFDP" in the order the bytes are read.
+pub const SIGNATURE: [u8; 4] = *b"SFDP";
+
+/// The ID of the Basic Flash Parameter Table.
+pub const BASIC_TABLE_ID: u16 = 0xFF00;
+
+/// The most dwords of the basic table which are understood, as of JESD216B.
+pub const BASIC_TABLE_LEN: usize = 16;
+
+/// The header of a parameter table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParameterHeader {
+    pub id: u16,
+    pub major: u8,
+    pub minor: u8,
+    /// The length of the table in dwords.
+    pub len: u8,
+    /// The SFDP address of the table.
+    pub pointer: u32,
+}
+
+impl From<[u8; 8]> for ParameterHeader {
+    fn from(bytes: [u8; 8]) -> Self {
+        Self {
+            id: u16::from_le_bytes([bytes[0], bytes[7]]),
+            minor: bytes[1],
+            major: bytes[2],
+            len: bytes[3],
+            pointer: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], 0]),
+        }
+    }
+}
+
+/// The number of parameter headers following the SFDP header.
+pub fn parameter_count(header: &[u8; 8]) -> Result<usize, Error> {
+    match header[..4] == SIGNATURE {
+        true => Ok(header[6] as usize + 1),
+        false => Err(Error::NoSfdp),
+    }
+}
+
+/// Which address lengths the chip accepts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AddressBytes {
+    #[default]
+    Three,
+    ThreeOrFour,
+    Four,
+}
+
+/// An erase instruction and the size it erases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EraseType {
+    pub len: u32,
+    pub instruction: u8,
+}
+
+/// The configuration described by the Basic Flash Parameter Table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sfdp {
+    /// The size of the array in bytes.
+    pub len: u64,
+    /// The page size, taken as 256 bytes by tables which only say it is 64
+    /// bytes or more, as before JESD216A.
+    pub page_len: u32,
+    pub erase_types: [Option<EraseType>; 4],
+    /// The multi-line reads supported with the same instructions and dummy
+    /// cycles as [`ReadMode`].
+    pub read_modes: ReadModes,
+    pub address_bytes: AddressBytes,
+}
+
+impl Sfdp {
+    /// Parse an SFDP dump read from address 0, long enough to include the
+    /// basic table.
+    pub fn parse(dump: &[u8]) -> Result<Self, Error> {
+        let header = dump.get(..8).ok_or(Error::NoSfdp)?;
+        let count = parameter_count(header.try_into().unwrap())?;
+
+        let basic = dump[8..]
+            .chunks_exact(8)
+            .take(count)
+            .map(|bytes| ParameterHeader::from(<[u8; 8]>::try_from(bytes).unwrap()))
+            .find(|header| header.id == BASIC_TABLE_ID)
+            .ok_or(Error::NoSfdp)?;
+
+        let start = basic.pointer as usize;
+        let end = start + basic.len as usize * 4;
+        Self::parse_basic(dump.get(start..end).ok_or(Error::NoSfdp)?)
+    }
+
+    /// Parse the Basic Flash Parameter Table, of at least the 9 dwords of
+    /// JESD216.
+    pub fn parse_basic(table: &[u8]) -> Result<Self, Error> {
+        if table.len() < 9 * 4 {
+            return Err(Error::NoSfdp);
+        }
+
+        let dword = |index: usize| {
+            let bytes = &table[(index - 1) * 4..index * 4];
+            u32::from_le_bytes(bytes.try_into().unwrap())
+        };
+
+        let first = dword(1);
+        let density = dword(2);
+
+        let len = match density & 0x8000_0000 {
+            0 => (density as u64 + 1) / 8,
+            _ => {
+                1u64.checked_shl(density & 0x7FFF_FFFF)
+                    .ok_or(Error::ChipSize)?
+                    / 8
+            }
+        };
+
+        let page_len = match (table.len() >= 11 * 4, first & 0x4) {
+            (true, _) => 1 << (dword(11) >> 4 & 0xF),
+            (false, 0) => 1,
+            (false, _) => 256,
+        };
+
+        let mut erase_types = [None; 4];
+
+        for (index, erase_type) in erase_types.iter_mut().enumerate() {
+            let half = (dword(8 + index / 2) >> (index % 2 * 16)) as u16;
+            let [exponent, instruction] = half.to_le_bytes();
+
+            if exponent != 0 {
+                *erase_type = Some(EraseType {
+                    len: 1u32.checked_shl(exponent as u32).ok_or(Error::NoSfdp)?,
+                    instruction,
+                });
+            }
+        }
+
+        // The dummy and mode clocks, and instruction, of each fast read.
+        let read = |dword: u32, shift: u32| {
+            let half = dword >> shift;
+            ((half & 0x1F) + (half >> 5 & 0x7), (half >> 8 & 0xFF) as u8)
+        };
+
+        let usable = |supported: bool, mode: ReadMode, (cycles, instruction)| {
+            supported
+                && cycles == mode.phases().dummy_cycles as u32
+                && instruction == mode.code().to_instruction()
+        };
+
+        let read_modes = ReadModes {
+            dual_output: usable(
+                first & 1 << 16 != 0,
+                ReadMode::DualOutput,
+                read(dword(4), 0),
+            ),
+            dual_io: usable(first & 1 << 20 != 0, ReadMode::DualIo, read(dword(4), 16)),
+            quad_output: usable(
+                first & 1 << 22 != 0,
+                ReadMode::QuadOutput,
+                read(dword(3), 16),
+            ),
+            quad_io: usable(first & 1 << 21 != 0, ReadMode::QuadIo, read(dword(3), 0)),
+        };
+
+        let address_bytes = match first >> 17 & 0x3 {
+            0 => AddressBytes::Three,
+            1 => AddressBytes::ThreeOrFour,
+            2 => AddressBytes::Four,
+            _ => return Err(Error::NoSfdp),
+        };
+
+        Ok(Self {
+            len,
+            page_len,
+            erase_types,
+            read_modes,
+            address_bytes,
+        })
+    }
+
+    /// The erase sizes supported with the instructions used by
+    /// [`Device`](super::Device).
+    pub fn erase_sizes(&self) -> EraseSizes {
+        let supports = |len: u32, code: Code| {
+            self.erase_types
+                .iter()
+                .flatten()
+                .any(|erase| erase.len == len && erase.instruction == code.to_instruction())
+        };
+
+        EraseSizes {
+            sector: supports(0x1000, Code::EraseSector),
+            block32: supports(0x8000, Code::EraseBlock32),
+            block64: supports(0x10000, Code::EraseBlock64),
+        }
+    }
+
+    /// The configuration of a chip with this table and JEDEC ID.
+    ///
+    /// SST chips are driven as [`Sst25`](super::Sst25) and Winbond and
+    /// GigaDevice chips as [`W25q`](super::W25q). Others are driven as
+    /// [`Mx25`](super::Mx25), which only uses the common instructions. The
+    /// block-protect bits are those of the family, as the table does not
+    /// describe them.
+    pub fn to_chip(&self, id: JedecId) -> Result<Chip, Error> {
+        let len = u32::try_from(self.len).or(Err(Error::ChipSize))?;
+
+        let family = match id.manufacturer {
+            0xBF => AnyFamily::Sst25,
+            0xEF | 0xC8 => AnyFamily::W25q,
+            _ => AnyFamily::Mx25,
+        };
+
+        Ok(Chip {
+            name: "SFDP",
+            id,
+            family,
+            size: Size::try_from(len)?,
+            page_len: self.page_len,
+            erase: self.erase_sizes(),
+            read_modes: self.read_modes,
+            aai: false,
+            protect_mask: family.protect_mask(),
+            address_bytes: self.address_bytes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AddressBytes, EraseType, Error, Sfdp};
+    use crate::{AnyFamily, EraseSizes, JedecId, ReadModes, Size};
+
+    /// As read from a W25Q64FV: JESD216 revision 1.5 with a 9 dword basic
+    /// table at 0x80.
+    const W25Q64FV: [u8; 0xA4] = {
+        let mut dump = [0xFF; 0xA4];
+        let header = [
+            0x53, 0x46, 0x44, 0x50, 0x05, 0x01, 0x00, 0xFF, // SFDP header
+            0x00, 0x05, 0x01, 0x09, 0x80, 0x00, 0x00, 0xFF, // basic table
+        ];
+        let basic = [
+            0xE5, 0x20, 0xF1, 0xFF, 0xFF, 0xFF, 0xFF, 0x03, 0x44, 0xEB, 0x08, 0x6B, 0x08, 0x3B,
+            0x42, 0xBB, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0xFF, 0xFF, 0x40, 0xEB,
+            0x0C, 0x20, 0x0F, 0x52, 0x10, 0xD8, 0x00, 0x00,
+        ];
+
+        let mut i = 0;
+        while i < header.len() {
+            dump[i] = header[i];
+            i += 1;
+        }
+
+        let mut i = 0;
+        while i < basic.len() {
+            dump[0x80 + i] = basic[i];
+            i += 1;
+        }
+
+        dump
+    };
+
+    /// A JESD216B table for a 256 Mbit chip taking 3 or 4 address bytes,
+    /// after a 4-byte address instruction table, with 4-byte erase
+    /// instructions as erase types 3 and 4 and no 1-1-2 read.
+    const JESD216B: [u8; 0x70] = {
+        let mut dump = [0xFF; 0x70];
+        let header = [
+            0x53, 0x46, 0x44, 0x50, 0x06, 0x01, 0x01, 0xFF, // SFDP header
+            0x84, 0x00, 0x01, 0x02, 0x28, 0x00, 0x00, 0xFF, // 4-byte instructions
+            0x00, 0x06, 0x01, 0x10, 0x30, 0x00, 0x00, 0xFF, // basic table
+        ];
+        let basic = [
+            0xE5, 0x20, 0xF2, 0xFF, 0x1C, 0x00, 0x00, 0x80, 0x44, 0xEB, 0x08, 0x6B, 0x00, 0xFF,
+            0x42, 0xBB, 0xEE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0xFF, 0xFF, 0xFF, 0x44, 0xEB,
+            0x0C, 0x20, 0x10, 0xD8, 0x0C, 0x21, 0x10, 0xDC, 0x00, 0x36, 0x00, 0x00, 0x82, 0x44,
+            0x9F, 0x00,
+        ];
+
+        let mut i = 0;
+        while i < header.len() {
+            dump[i] = header[i];
+            i += 1;
+        }
+
+        let mut i = 0;
+        while i < basic.len() {
+            dump[0x30 + i] = basic[i];
+            i += 1;
+        }
+
+        dump
+    };
+
+    #[test]
+    fn w25q64fv() {
+        let sfdp = Sfdp::parse(&W25Q64FV).unwrap();
+
+        assert_eq!(sfdp.len, 8 * Size::MB as u64);
+        assert_eq!(sfdp.page_len, 256);
+        assert_eq!(sfdp.read_modes, ReadModes::ALL);
+        assert_eq!(sfdp.address_bytes, AddressBytes::Three);
+        assert_eq!(
+            sfdp.erase_types,
+            [
+                Some(EraseType {
+                    len: 0x1000,
+                    instruction: 0x20
+                }),
+                Some(EraseType {
+                    len: 0x8000,
+                    instruction: 0x52
+                }),
+                Some(EraseType {
+                    len: 0x10000,
+                    instruction: 0xD8
+                }),
+                None,
+            ]
+        );
+
+        let chip = sfdp.to_chip(JedecId::from([0xEF, 0x40, 0x17])).unwrap();
+
+        assert_eq!(chip.family, AnyFamily::W25q);
+        assert_eq!(chip.size, Size::from_mb(8).unwrap());
+        assert_eq!(chip.erase, EraseSizes::ALL);
+        assert_eq!(chip.protect_mask, 0x1C);
+
+        let chip = sfdp.to_chip(JedecId::from([0xBF, 0x25, 0x4B])).unwrap();
+
+        assert_eq!(chip.family, AnyFamily::Sst25);
+        assert_eq!(chip.protect_mask, 0x3C);
+    }
+
+    #[test]
+    fn jesd216b() {
+        let sfdp = Sfdp::parse(&JESD216B).unwrap();
+
+        assert_eq!(sfdp.len, 32 * Size::MB as u64);
+        assert_eq!(sfdp.page_len, 256);
+        assert_eq!(sfdp.address_bytes, AddressBytes::ThreeOrFour);
+        assert_eq!(
+            sfdp.read_modes,
+            ReadModes {
+                dual_output: false,
+                ..ReadModes::ALL
+            }
+        );
+        assert_eq!(sfdp.erase_sizes(), EraseSizes::NO_BLOCK32);
+
+        let chip = sfdp.to_chip(JedecId::from([0xC2, 0x20, 0x19])).unwrap();
+
+        assert_eq!(chip.family, AnyFamily::Mx25);
+        assert_eq!(chip.size, Size::from_mb(32).unwrap());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut dump = W25Q64FV;
+        dump[0] = 0xFF;
+        assert_eq!(Sfdp::parse(&dump), Err(Error::NoSfdp));
+
+        assert_eq!(Sfdp::parse(&W25Q64FV[..0x90]), Err(Error::NoSfdp));
+        assert_eq!(Sfdp::parse(&JESD216B[..16]), Err(Error::NoSfdp));
+    }
+}
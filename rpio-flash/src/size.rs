@@ -8,6 +8,11 @@ impl Size {
     // pub const MBIT: u32 = 131072;
     pub const MB: u32 = 1048576;
 
+    /// A size of `mb` megabytes, which must be valid.
+    pub(crate) const fn mb(mb: u32) -> Self {
+        Size(mb * Self::MB)
+    }
+
     pub fn is_addr(&self, addr: u32) -> bool {
         addr < self.0
     }
@@ -0,0 +1,345 @@
+use super::buffer::FlashBuffer;
+use super::chip::{Chip, EraseSizes};
+use super::device::{Result, BLOCK32_LEN, BLOCK64_LEN, SECTOR_LEN};
+use super::error::Error;
+use super::family::{Family, Sst25};
+use super::op::{AddressMode, Code, ReadModes, Type};
+use super::sfdp::AddressBytes;
+use super::size::Size;
+use super::step::{Erase, Write};
+use super::timeout::Timeouts;
+
+/// The configuration of a chip and of the operation in progress, shared by
+/// [`Device`](super::Device) and `AsyncDevice`.
+///
+/// Its methods work out which instructions to send and encode them into the
+/// [`FlashBuffer`], leaving the transfers to the devices. Operations taking
+/// several instructions are planned as [`Plan`](super::step::Plan)s.
+#[derive(Debug)]
+pub(crate) struct State<F: Family> {
+    pub family: F,
+    pub size: Size,
+    pub block_protect: u8,
+    /// The [`protect_flags`](Family::protect_flags) set in each status
+    /// register.
+    pub protect_flags: [u8; 3],
+    pub read_modes: ReadModes,
+    pub erase: EraseSizes,
+    pub page_len: u32,
+    pub aai: bool,
+    pub protect_mask: u8,
+    pub address_mode: AddressMode,
+    pub chip: Option<Chip>,
+    pub timeouts: Timeouts,
+    pub clock: Option<fn() -> u64>,
+    /// The timeout of the operation which may be in progress.
+    pub busy_us: u32,
+    pub busy_output: bool,
+}
+
+impl State<Sst25> {
+    /// An SST25 chip of the given size, programmed with AAI.
+    pub fn new(size: Size) -> Self {
+        Self {
+            family: Sst25,
+            size,
+            block_protect: 0xF,
+            protect_flags: [0; 3],
+            read_modes: ReadModes::default(),
+            erase: EraseSizes::ALL,
+            page_len: 1,
+            aai: true,
+            protect_mask: 0x3C,
+            address_mode: AddressMode::Three,
+            chip: None,
+            timeouts: Timeouts::default(),
+            clock: None,
+            busy_us: Timeouts::default().chip_erase_us,
+            busy_output: false,
+        }
+    }
+}
+
+impl<F: Family> State<F> {
+    pub fn with_family<G: Family>(self, family: G) -> State<G> {
+        State {
+            family,
+            size: self.size,
+            block_protect: self.block_protect,
+            protect_flags: self.protect_flags,
+            read_modes: self.read_modes,
+            erase: self.erase,
+            page_len: self.page_len,
+            aai: self.aai,
+            protect_mask: self.protect_mask,
+            address_mode: self.address_mode,
+            chip: self.chip,
+            timeouts: self.timeouts,
+            clock: self.clock,
+            busy_us: self.busy_us,
+            busy_output: self.busy_output,
+        }
+    }
+
+    /// Configure a known chip and return the address mode to drive it in.
+    pub fn set_chip(&mut self, chip: &Chip) -> Result<AddressMode> {
+        self.family = F::from_chip(chip).ok_or(Error::NotSupported)?;
+        self.size = chip.size;
+        self.read_modes = chip.read_modes;
+        self.erase = chip.erase;
+        self.page_len = chip.page_len;
+        self.aai = chip.aai;
+        self.protect_mask = chip.protect_mask;
+        self.chip = Some(*chip);
+
+        Ok(match chip.address_bytes {
+            AddressBytes::Three => AddressMode::Three,
+            _ if chip.size.size() <= 0x100_0000 => AddressMode::Three,
+            _ => AddressMode::Four,
+        })
+    }
+
+    /// The instruction which switches the chip from the current address mode
+    /// to `mode`, if any.
+    pub fn address_mode_code(&self, mode: AddressMode) -> Option<Code> {
+        match (self.address_mode, mode) {
+            (AddressMode::Four, AddressMode::Four) => None,
+            (_, AddressMode::Four) => Some(Code::Enter4ByteMode),
+            (AddressMode::Four, _) => Some(Code::Exit4ByteMode),
+            _ => None,
+        }
+    }
+
+    /// Set up an instruction with an address for the address mode and return
+    /// the layout to send. Fails with [`Error::AddressOutOfRange`] for
+    /// addresses beyond 16 MB in 3-byte mode.
+    pub fn set_op_addr<B: FlashBuffer>(&self, buf: &mut B, code: Code, addr: u32) -> Result<Type> {
+        match self.address_mode {
+            AddressMode::Three if addr > 0xFF_FFFF => return Err(Error::AddressOutOfRange),
+            AddressMode::Three => buf.set_op_addr(code, addr),
+            AddressMode::Four => buf.set_op_addr4(code, addr),
+            AddressMode::FourByteCodes => buf.set_op_addr4(code.to_four_byte(), addr),
+        };
+
+        Ok(match self.address_mode {
+            AddressMode::Three => Type::OpAddr,
+            _ => Type::OpAddr4,
+        })
+    }
+
+    /// Set up a single byte or page program of `data`, which must fit in the
+    /// buffer and not cross the end of a page.
+    pub fn set_program<B: FlashBuffer>(&self, buf: &mut B, addr: u32, data: &[u8]) -> Result<Type> {
+        let op = self.set_op_addr(buf, Code::WriteByte, addr)?;
+        buf.data_mut()[..data.len()].copy_from_slice(data);
+        Ok(op)
+    }
+
+    /// Set up the AAI program of the word at `index`. Only the first word
+    /// carries the address.
+    pub fn set_aai_word<B: FlashBuffer>(
+        &self,
+        buf: &mut B,
+        index: usize,
+        addr: u32,
+        word: &[u8],
+    ) -> Result<Type> {
+        let op = match index {
+            0 => self.set_op_addr(buf, Code::WriteAutoIncrement, addr)?,
+            _ => {
+                buf.set_op(Code::WriteAutoIncrement);
+                Type::Op
+            }
+        };
+
+        buf.data_mut()[..2].copy_from_slice(word);
+        Ok(op)
+    }
+
+    /// Plan a [`write`](super::Device::write) of `data` from `addr`, once
+    /// checked.
+    pub fn write<'d>(&self, addr: u32, data: &'d [u8], buf_len: usize) -> Result<Write<'d>> {
+        if !data.is_empty() {
+            self.check_write(addr, data.len())?;
+        }
+
+        Ok(Write::new(addr, data, self.writes_aai(buf_len)))
+    }
+
+    /// Plan a single byte or page program of `data`, which must fit in the
+    /// buffer and not cross the end of a page.
+    pub fn program<'d>(&self, addr: u32, data: &'d [u8]) -> Write<'d> {
+        Write::new(addr, data, false)
+    }
+
+    /// Whether [`write`](super::Device::write) programs pairs of bytes with
+    /// AAI, rather than pages.
+    pub fn writes_aai(&self, buf_len: usize) -> bool {
+        buf_len >= 2 && self.aai
+    }
+
+    /// Check that `len` bytes can be written from `addr`.
+    pub fn check_write(&self, addr: u32, len: usize) -> Result {
+        let len = u32::try_from(len).or(Err(Error::AddressOutOfRange))?;
+        addr.checked_add(len).ok_or(Error::AddressOutOfRange)?;
+        self.check_range(addr, len)
+    }
+
+    /// The bytes of `remaining` to program from `addr` in one instruction: as
+    /// many as the buffer holds, split wherever a page ends.
+    pub fn program_len(&self, addr: u32, remaining: usize, buf_len: usize) -> usize {
+        let page_left = (self.page_len - addr % self.page_len) as usize;
+        remaining.min(page_left).min(buf_len)
+    }
+
+    /// The instructions reading and writing status register `index`.
+    pub fn status_register(&self, index: usize) -> Result<(Code, Code)> {
+        self.family
+            .status_registers()
+            .get(index)
+            .copied()
+            .ok_or(Error::NotSupported)
+    }
+
+    /// Record that status register `index` was written with `value`.
+    pub fn status_written(&mut self, index: usize, value: u8) {
+        self.busy_us = self.timeouts.status_us;
+        self.status_read(index, value);
+    }
+
+    /// Record the block-protect bits and flags from status register `index`.
+    pub fn status_read(&mut self, index: usize, value: u8) {
+        let mut flags = value & self.family.protect_flags(index);
+
+        if index == 0 {
+            self.block_protect = (value & self.protect_mask) >> 2;
+            flags &= !self.protect_mask;
+        }
+
+        if let Some(recorded) = self.protect_flags.get_mut(index) {
+            *recorded = flags;
+        }
+    }
+
+    /// Whether status register `index` holds block-protect bits or flags,
+    /// and so is read by `read_block_protect_bits`.
+    pub fn holds_protection(&self, index: usize) -> bool {
+        index == 0 || self.family.protect_flags(index) != 0
+    }
+
+    /// The value of the first status register holding `bp_bits`.
+    pub fn block_protect_status(&self, bp_bits: u8) -> u8 {
+        (bp_bits << 2) & self.protect_mask
+    }
+
+    /// The instruction and timeout erasing a sector or block of `unit` bytes,
+    /// if the chip supports it.
+    pub fn erase_instruction(&self, unit: u32) -> Result<(Code, u32)> {
+        let (supported, code, timeout_us) = match unit {
+            SECTOR_LEN => (self.erase.sector, Code::EraseSector, self.timeouts.erase_us),
+            BLOCK32_LEN => (
+                self.erase.block32,
+                Code::EraseBlock32,
+                self.timeouts.block_erase_us,
+            ),
+            _ => (
+                self.erase.block64,
+                Code::EraseBlock64,
+                self.timeouts.block_erase_us,
+            ),
+        };
+
+        match supported {
+            true => Ok((code, timeout_us)),
+            false => Err(Error::NotSupported),
+        }
+    }
+
+    /// Plan the erase of the sector or block of `unit` bytes containing
+    /// `addr`, once checked.
+    pub fn erase(&self, unit: u32, addr: u32) -> Result<Erase> {
+        self.erase_instruction(unit)?;
+        let start = addr & !(unit - 1);
+        self.check_range(start, unit)?;
+        Ok(Erase::units(unit, start, start + unit))
+    }
+
+    /// Plan the erase of every sector overlapping `len` bytes from `addr`,
+    /// once the whole range is checked.
+    pub fn erase_range(&self, addr: u32, len: u32) -> Result<Erase> {
+        let (start, end) = self.erase_span(addr, len)?.unwrap_or((addr, addr));
+        Ok(Erase::range(start, end))
+    }
+
+    /// Plan the erase of the whole chip, once checked.
+    pub fn erase_chip(&self) -> Result<Erase> {
+        self.check_chip_erase()?;
+        Ok(Erase::chip())
+    }
+
+    /// The sectors overlapping `len` bytes from `addr`, once checked, or
+    /// `None` if there are none.
+    fn erase_span(&self, addr: u32, len: u32) -> Result<Option<(u32, u32)>> {
+        if len == 0 {
+            return Ok(None);
+        }
+
+        let start = addr & !(SECTOR_LEN - 1);
+        let end = addr
+            .checked_add(len)
+            .and_then(|end| end.checked_next_multiple_of(SECTOR_LEN))
+            .ok_or(Error::AddressOutOfRange)?;
+
+        self.check_range(start, end - start)?;
+        Ok(Some((start, end)))
+    }
+
+    /// The largest aligned sector or block from `addr` which fits before
+    /// `end` and the chip can erase.
+    pub fn erase_unit(&self, addr: u32, end: u32) -> u32 {
+        let fits = |unit: u32| addr.is_multiple_of(unit) && end - addr >= unit;
+
+        if self.erase.block64 && fits(BLOCK64_LEN) {
+            BLOCK64_LEN
+        } else if self.erase.block32 && fits(BLOCK32_LEN) {
+            BLOCK32_LEN
+        } else {
+            SECTOR_LEN
+        }
+    }
+
+    /// Check that the whole chip can be erased.
+    pub fn check_chip_erase(&self) -> Result {
+        match self.protected_offset() {
+            Some(_) => Err(Error::SectorOutOfRange),
+            None => Ok(()),
+        }
+    }
+
+    /// The start of the protected region at the top of the chip, if any: all
+    /// of it while any of the flags are set.
+    fn protected_offset(&self) -> Option<u32> {
+        match self.protect_flags.iter().any(|&flags| flags != 0) {
+            true => Some(0),
+            false => self.family.protected_offset(self.size, self.block_protect),
+        }
+    }
+
+    /// Check that `len` bytes from `start` are on the chip and not protected.
+    /// An empty range is always allowed.
+    pub fn check_range(&self, start: u32, len: u32) -> Result {
+        if len == 0 {
+            return Ok(());
+        }
+
+        let last = start.checked_add(len - 1).ok_or(Error::AddressOutOfRange)?;
+
+        if !self.size.is_addr(last) {
+            Err(Error::AddressOutOfRange)
+        } else if self.protected_offset().is_some_and(|offset| last >= offset) {
+            Err(Error::SectorOutOfRange)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use super::buffer::FlashBuffer;
+use super::device::Result;
+use super::family::Family;
+use super::op::{Code, Type};
+use super::state::State;
+
+/// What is polled while the chip is busy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Busy {
+    /// The busy bit of the status register.
+    Status,
+    /// The busy and AAI bits of the status register.
+    Aai,
+    /// The level the chip drives on MISO, with busy output enabled.
+    Output,
+}
+
+/// One transfer or wait of an operation taking several instructions.
+pub(crate) enum Step {
+    /// Send the instruction set up in the buffer with `len` bytes of data.
+    Send(Type, usize),
+    /// Poll until the chip is no longer busy, for up to the timeout.
+    Wait(Busy, u32),
+}
+
+/// Works out the steps of an operation one at a time, leaving the transfers
+/// to [`Device`](super::Device) and `AsyncDevice`.
+pub(crate) trait Plan {
+    /// Set up the next step, or return `None` once the operation is done.
+    /// It is only called again when the step before it succeeded.
+    fn next<F: Family, B: FlashBuffer>(
+        &mut self,
+        state: &mut State<F>,
+        buf: &mut B,
+    ) -> Result<Option<Step>>;
+
+    /// Record that the last step failed, returning whether there are steps
+    /// which must still be performed, such as leaving AAI mode.
+    fn fail(&mut self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum WriteStage {
+    /// Start programming the rest of the data.
+    Start,
+    Enable(usize),
+    Program(usize),
+    Programmed(usize),
+    BusyOutputOn(usize),
+    AaiEnable(usize),
+    /// Program the AAI word at the index.
+    Word(usize, usize),
+    WordWait(usize, usize),
+    /// Leave AAI mode, whether or not the words were programmed.
+    Disable(usize),
+    DisableWait(usize),
+    BusyOutputOff(usize),
+    Exited(usize),
+}
+
+/// The steps of [`write`](super::Device::write): each part of the data is
+/// programmed once the chip is ready, with a single instruction or with AAI
+/// words.
+#[derive(Debug)]
+pub(crate) struct Write<'d> {
+    addr: u32,
+    data: &'d [u8],
+    aai: bool,
+    stage: WriteStage,
+    failed: bool,
+}
+
+impl<'d> Write<'d> {
+    /// Program `data` from `addr`, in pairs of bytes with AAI if `aai`.
+    pub fn new(addr: u32, data: &'d [u8], aai: bool) -> Self {
+        Self {
+            addr,
+            data,
+            aai,
+            stage: WriteStage::Start,
+            failed: false,
+        }
+    }
+
+    /// Move on to the part of the data after the `len` bytes programmed.
+    fn advance(&mut self, len: usize) {
+        self.addr += len as u32;
+        self.data = &self.data[len..];
+        self.stage = WriteStage::Start;
+    }
+}
+
+impl Plan for Write<'_> {
+    fn next<F: Family, B: FlashBuffer>(
+        &mut self,
+        state: &mut State<F>,
+        buf: &mut B,
+    ) -> Result<Option<Step>> {
+        use WriteStage::*;
+
+        loop {
+            let (stage, step) = match self.stage {
+                Start if self.data.is_empty() => return Ok(None),
+                Start => {
+                    let len = match self.aai {
+                        true if self.addr % 2 == 1 || self.data.len() == 1 => 1,
+                        true => self.data.len() & !1,
+                        false => state.program_len(self.addr, self.data.len(), buf.len()),
+                    };
+
+                    let stage = match self.aai && len > 1 {
+                        true => BusyOutputOn(len),
+                        false => Enable(len),
+                    };
+
+                    (stage, Step::Wait(Busy::Status, state.busy_us))
+                }
+                Enable(len) => {
+                    buf.set_op(Code::WriteEnable);
+                    (Program(len), Step::Send(Type::Op, 0))
+                }
+                Program(len) => {
+                    let op = state.set_program(buf, self.addr, &self.data[..len])?;
+                    (Programmed(len), Step::Send(op, len))
+                }
+                Programmed(len) => {
+                    state.busy_us = state.timeouts.program_us;
+                    self.advance(len);
+                    continue;
+                }
+                BusyOutputOn(len) if state.busy_output => {
+                    buf.set_op(Code::BusyStatusOutputEnable);
+                    (AaiEnable(len), Step::Send(Type::Op, 0))
+                }
+                BusyOutputOn(len) => {
+                    self.stage = AaiEnable(len);
+                    continue;
+                }
+                AaiEnable(len) => {
+                    buf.set_op(Code::WriteEnable);
+                    (Word(len, 0), Step::Send(Type::Op, 0))
+                }
+                Word(len, index) if 2 * index >= len => {
+                    self.stage = Disable(len);
+                    continue;
+                }
+                Word(len, index) => {
+                    let word = &self.data[2 * index..2 * index + 2];
+                    let op = state.set_aai_word(buf, index, self.addr, word)?;
+                    (WordWait(len, index), Step::Send(op, 2))
+                }
+                WordWait(len, index) => {
+                    let busy = match state.busy_output {
+                        true => Busy::Output,
+                        false => Busy::Status,
+                    };
+
+                    (
+                        Word(len, index + 1),
+                        Step::Wait(busy, state.timeouts.program_us),
+                    )
+                }
+                Disable(len) => {
+                    state.busy_us = state.timeouts.program_us;
+                    buf.set_op(Code::WriteDisable);
+                    (DisableWait(len), Step::Send(Type::Op, 0))
+                }
+                DisableWait(len) => (
+                    BusyOutputOff(len),
+                    Step::Wait(Busy::Aai, state.timeouts.program_us),
+                ),
+                BusyOutputOff(len) if state.busy_output => {
+                    buf.set_op(Code::BusyStatusOutputDisable);
+                    (Exited(len), Step::Send(Type::Op, 0))
+                }
+                BusyOutputOff(len) => {
+                    self.stage = Exited(len);
+                    continue;
+                }
+                Exited(_) if self.failed => return Ok(None),
+                Exited(len) => {
+                    self.advance(len);
+                    continue;
+                }
+            };
+
+            self.stage = stage;
+            return Ok(Some(step));
+        }
+    }
+
+    /// A failure while programming AAI words still leaves AAI mode, after
+    /// which the write stops.
+    fn fail(&mut self) -> bool {
+        match self.stage {
+            WriteStage::Word(len, _) | WriteStage::WordWait(len, _) if !self.failed => {
+                self.stage = WriteStage::Disable(len);
+                self.failed = true;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Extent {
+    Chip,
+    /// Sectors or blocks of a fixed size.
+    Unit(u32),
+    /// The largest sectors or blocks which fit.
+    Range,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum EraseStage {
+    Ready,
+    Enable(u32),
+    Erase(u32),
+    Erased(u32),
+}
+
+/// The steps of the erase methods: each sector or block from `addr` to
+/// `end` is erased once the chip is ready.
+#[derive(Debug)]
+pub(crate) struct Erase {
+    extent: Extent,
+    addr: u32,
+    end: u32,
+    stage: EraseStage,
+}
+
+impl Erase {
+    /// Erase the whole chip with a single instruction.
+    pub fn chip() -> Self {
+        Self::new(Extent::Chip, 0, 1)
+    }
+
+    /// Erase the sectors or blocks of `unit` bytes from `addr` to `end`.
+    pub fn units(unit: u32, addr: u32, end: u32) -> Self {
+        Self::new(Extent::Unit(unit), addr, end)
+    }
+
+    /// Erase from `addr` to `end` with the largest units which fit.
+    pub fn range(addr: u32, end: u32) -> Self {
+        Self::new(Extent::Range, addr, end)
+    }
+
+    fn new(extent: Extent, addr: u32, end: u32) -> Self {
+        Self {
+            extent,
+            addr,
+            end,
+            stage: EraseStage::Ready,
+        }
+    }
+}
+
+impl Plan for Erase {
+    fn next<F: Family, B: FlashBuffer>(
+        &mut self,
+        state: &mut State<F>,
+        buf: &mut B,
+    ) -> Result<Option<Step>> {
+        use EraseStage::*;
+
+        let (stage, step) = match self.stage {
+            Ready if self.addr >= self.end => return Ok(None),
+            Ready => {
+                let unit = match self.extent {
+                    Extent::Chip => 1,
+                    Extent::Unit(unit) => unit,
+                    Extent::Range => state.erase_unit(self.addr, self.end),
+                };
+
+                if !matches!(self.extent, Extent::Chip) {
+                    state.erase_instruction(unit)?;
+                }
+
+                (Enable(unit), Step::Wait(Busy::Status, state.busy_us))
+            }
+            Enable(unit) => {
+                buf.set_op(Code::WriteEnable);
+                (Erase(unit), Step::Send(Type::Op, 0))
+            }
+            Erase(unit) => {
+                let op = match self.extent {
+                    Extent::Chip => {
+                        buf.set_op(Code::EraseChip);
+                        Type::Op
+                    }
+                    _ => {
+                        let (code, _) = state.erase_instruction(unit)?;
+                        state.set_op_addr(buf, code, self.addr)?
+                    }
+                };
+
+                (Erased(unit), Step::Send(op, 0))
+            }
+            Erased(unit) => {
+                state.busy_us = match self.extent {
+                    Extent::Chip => state.timeouts.chip_erase_us,
+                    _ => state.erase_instruction(unit)?.1,
+                };
+
+                self.addr += unit;
+                self.stage = Ready;
+                return self.next(state, buf);
+            }
+        };
+
+        self.stage = stage;
+        Ok(Some(step))
+    }
+}
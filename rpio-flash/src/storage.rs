@@ -0,0 +1,234 @@
+use super::buffer::FlashBuffer;
+use super::device::{Device, SECTOR_LEN};
+use super::error::Error;
+use super::family::{Family, Sst25};
+use embedded_storage::nor_flash::{
+    ErrorType, MultiwriteNorFlash, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
+};
+use rpio_spi::SpiDevice;
+
+impl NorFlashError for Error {
+    fn kind(&self) -> NorFlashErrorKind {
+        match self {
+            Error::AddressOutOfRange => NorFlashErrorKind::OutOfBounds,
+            Error::NotAligned => NorFlashErrorKind::NotAligned,
+            _ => NorFlashErrorKind::Other,
+        }
+    }
+}
+
+/// A [`Device`] driven through the `embedded-storage` traits.
+#[derive(Debug)]
+pub struct Storage<SPI: SpiDevice, B: FlashBuffer, F: Family = Sst25> {
+    device: Device<SPI, B, F>,
+}
+
+impl<SPI: SpiDevice, B: FlashBuffer, F: Family> Storage<SPI, B, F> {
+    /// Wrap `device`, reading its block-protect bits so that unprotected
+    /// sectors can be written and erased straight away. Fails with
+    /// [`Error::NotSupported`] if the chip can't erase 4K sectors.
+    pub fn new(mut device: Device<SPI, B, F>) -> Result<Self, Error> {
+        if !device.erase_sizes().sector {
+            return Err(Error::NotSupported);
+        }
+
+        device.read_block_protect_bits()?;
+        Ok(Self { device })
+    }
+
+    /// The wrapped device, such as for changing the block-protect bits.
+    pub fn device_mut(&mut self) -> &mut Device<SPI, B, F> {
+        &mut self.device
+    }
+
+    pub fn into_inner(self) -> Device<SPI, B, F> {
+        self.device
+    }
+
+    /// Check that `len` bytes from `offset` are on the chip.
+    fn check_bounds(&self, offset: u32, len: usize) -> Result<(), Error> {
+        u32::try_from(len)
+            .ok()
+            .and_then(|len| offset.checked_add(len))
+            .filter(|&end| end <= self.device.size().size())
+            .map(|_| ())
+            .ok_or(Error::AddressOutOfRange)
+    }
+}
+
+impl<SPI: SpiDevice, B: FlashBuffer, F: Family> ErrorType for Storage<SPI, B, F> {
+    type Error = Error;
+}
+
+impl<SPI: SpiDevice, B: FlashBuffer, F: Family> ReadNorFlash for Storage<SPI, B, F> {
+    const READ_SIZE: usize = 1;
+
+    /// Wait for any program or erase to finish, then read in chunks of the
+    /// buffer's length.
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Error> {
+        self.check_bounds(offset, bytes.len())?;
+        self.device.wait_ready()?;
+
+        let chunk_len = self.device.buf.len();
+
+        for (addr, chunk) in (offset..)
+            .step_by(chunk_len)
+            .zip(bytes.chunks_mut(chunk_len))
+        {
+            chunk.copy_from_slice(self.device.read(addr, chunk.len())?);
+        }
+
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.device.size().size() as usize
+    }
+}
+
+/// Every chip programs single bytes, so that is the write size whatever the
+/// chip. The sizes are associated consts, the same for every chip of the
+/// family, so the erase size can't follow the chip: it is the 4K sector,
+/// which [`Storage::new`] checks the chip can erase. Larger aligned ranges
+/// are erased with the biggest blocks the chip supports, as with
+/// [`Device::erase_range`].
+///
+/// Protected sectors fail with [`NorFlashErrorKind::Other`], so clear the
+/// block-protect bits first. Both [`erase`](NorFlash::erase) and
+/// [`write`](NorFlash::write) wait for the chip to finish before returning.
+impl<SPI: SpiDevice, B: FlashBuffer, F: Family> NorFlash for Storage<SPI, B, F> {
+    const WRITE_SIZE: usize = 1;
+    const ERASE_SIZE: usize = SECTOR_LEN as usize;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), Error> {
+        if from > to || to > self.device.size().size() {
+            return Err(Error::AddressOutOfRange);
+        }
+
+        if !from.is_multiple_of(SECTOR_LEN) || !to.is_multiple_of(SECTOR_LEN) {
+            return Err(Error::NotAligned);
+        }
+
+        self.device.erase_range(from, to - from)?;
+        self.device.wait_ready()
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Error> {
+        self.check_bounds(offset, bytes.len())?;
+        self.device.write(offset, bytes)?;
+        self.device.wait_ready()
+    }
+}
+
+/// Programming only clears bits, so a word may be written again as long as
+/// no bit goes from 0 to 1.
+impl<SPI: SpiDevice, B: FlashBuffer, F: Family> MultiwriteNorFlash for Storage<SPI, B, F> {}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{Error, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash, Storage};
+    use crate::{Buffer, Chip, Device, EraseSizes, JedecId, Size};
+    use rpio_spi::{MockSpi, Transaction};
+    use std::{vec, vec::Vec};
+
+    /// The status read by [`Storage::new`].
+    fn read_status() -> [Transaction; 3] {
+        [
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0], &[0, 0]),
+            Transaction::Deselect,
+        ]
+    }
+
+    fn device(mock: &MockSpi) -> Device<MockSpi, Buffer<9>> {
+        Device::new(mock.clone(), Size::from_mb(1).unwrap(), Buffer::new())
+    }
+
+    fn storage(mock: &MockSpi) -> Storage<MockSpi, Buffer<9>> {
+        Storage::new(device(mock)).unwrap()
+    }
+
+    #[test]
+    fn new() {
+        let chip = Chip {
+            erase: EraseSizes {
+                sector: false,
+                ..EraseSizes::ALL
+            },
+            ..*Chip::find(JedecId::from([0xBF, 0x25, 0x8E])).unwrap()
+        };
+        let mock = MockSpi::with_expectations(&[]);
+        let device = device(&mock).with_chip(&chip).unwrap();
+
+        assert_eq!(Storage::new(device).unwrap_err(), Error::NotSupported);
+        mock.done();
+    }
+
+    #[test]
+    fn read_chunks() {
+        let mock = MockSpi::with_expectations(&read_status());
+        mock.expect_all(&[
+            Transaction::Select,
+            Transaction::transfer(&[0x05, 0], &[0, 0]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::transfer(&[0x03, 0, 0x10, 0, 0, 0, 0, 0], &[0, 0, 0, 0, 1, 2, 3, 4]),
+            Transaction::Deselect,
+            Transaction::Select,
+            Transaction::transfer(&[0x03, 0, 0x10, 0x04, 1, 2], &[0, 0, 0, 0, 5, 6]),
+            Transaction::Deselect,
+        ]);
+
+        let mut flash = storage(&mock);
+        let mut bytes = [0; 6];
+
+        assert_eq!(flash.capacity(), 0x100000);
+        flash.read(0x1000, &mut bytes).unwrap();
+        assert_eq!(bytes, [1, 2, 3, 4, 5, 6]);
+        mock.done();
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mock = MockSpi::with_expectations(&read_status());
+        let mut flash = storage(&mock);
+        let mut bytes = [0; 2];
+
+        let err = flash.read(0xFFFFF, &mut bytes).unwrap_err();
+        assert_eq!(err.kind(), NorFlashErrorKind::OutOfBounds);
+
+        let err = flash.write(u32::MAX, &bytes).unwrap_err();
+        assert_eq!(err.kind(), NorFlashErrorKind::OutOfBounds);
+
+        let err = flash.erase(0xFF000, 0x101000).unwrap_err();
+        assert_eq!(err.kind(), NorFlashErrorKind::OutOfBounds);
+
+        let err = flash.erase(0x800, 0x1000).unwrap_err();
+        assert_eq!(err.kind(), NorFlashErrorKind::NotAligned);
+        assert_eq!(Error::Timeout.kind(), NorFlashErrorKind::Other);
+        mock.done();
+    }
+
+    #[test]
+    fn erase() {
+        let mock = MockSpi::new();
+        let mut flash = storage(&mock);
+
+        flash.erase(0x10000, 0x21000).unwrap();
+
+        let erases: Vec<Vec<u8>> = mock
+            .log()
+            .into_iter()
+            .filter_map(|transaction| match transaction {
+                Transaction::Transfer(write, _) if [0x20, 0x52, 0xD8].contains(&write[0]) => {
+                    Some(write)
+                }
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(erases, [vec![0xD8, 0x01, 0, 0], vec![0x20, 0x02, 0, 0]]);
+    }
+}
@@ -0,0 +1,65 @@
+/// The wait between status reads while the chip is busy.
+pub const POLL_INTERVAL_US: u32 = 10;
+
+/// How long the chip may stay busy after each kind of operation before
+/// [`Error::Timeout`](super::Error::Timeout) is returned.
+///
+/// Time is measured with the clock given to
+/// [`Device::with_clock`](super::Device::with_clock). Without one, each
+/// status read counts as [`POLL_INTERVAL_US`]. A transport which cannot
+/// [`delay_us`](rpio_spi::SpiDevice::delay_us) polls without waiting, so it
+/// gives up after as many polls, which may be sooner than the timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    pub program_us: u32,
+    pub status_us: u32,
+    pub erase_us: u32,
+    pub block_erase_us: u32,
+    pub chip_erase_us: u32,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            program_us: 5_000,
+            status_us: 15_000,
+            erase_us: 400_000,
+            block_erase_us: 2_000_000,
+            chip_erase_us: 200_000_000,
+        }
+    }
+}
+
+/// The time spent waiting for a busy chip, against the timeout of the
+/// operation in progress.
+pub(crate) struct Deadline {
+    clock: Option<fn() -> u64>,
+    start: u64,
+    waited_us: u64,
+    timeout_us: u32,
+}
+
+impl Deadline {
+    pub fn new(clock: Option<fn() -> u64>, timeout_us: u32) -> Self {
+        Self {
+            clock,
+            start: clock.map_or(0, |clock| clock()),
+            waited_us: 0,
+            timeout_us,
+        }
+    }
+
+    /// Count the wait between status reads, for when there is no clock.
+    pub fn delayed(&mut self, us: u32) {
+        self.waited_us += us as u64;
+    }
+
+    pub fn is_expired(&self) -> bool {
+        let elapsed = match self.clock {
+            Some(clock) => clock().wrapping_sub(self.start),
+            None => self.waited_us,
+        };
+
+        elapsed >= self.timeout_us as u64
+    }
+}
@@ -0,0 +1,87 @@
+use core::fmt::Debug;
+use embedded_hal::digital::v2;
+use embedded_hal_1::digital::{self, ErrorKind, ErrorType};
+
+/// The error of an embedded-hal 0.2 pin, as an embedded-hal 1.0
+/// [`Error`](digital::Error).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Error<E: Debug>(pub E);
+
+impl<E: Debug> digital::Error for Error<E> {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// Use an embedded-hal 0.2 [`OutputPin`](v2::OutputPin) where an
+/// embedded-hal 1.0 [`OutputPin`](digital::OutputPin) is expected, such as
+/// for the chip select of an embedded-hal 1.0 driver.
+pub struct OutputPin<PIN: v2::OutputPin> {
+    pin: PIN,
+}
+
+impl<PIN: v2::OutputPin> OutputPin<PIN> {
+    pub fn new(pin: PIN) -> Self {
+        Self { pin }
+    }
+
+    pub fn free(self) -> PIN {
+        self.pin
+    }
+}
+
+impl<PIN: v2::OutputPin> ErrorType for OutputPin<PIN>
+where
+    PIN::Error: Debug,
+{
+    type Error = Error<PIN::Error>;
+}
+
+impl<PIN: v2::OutputPin> digital::OutputPin for OutputPin<PIN>
+where
+    PIN::Error: Debug,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_low().map_err(Error)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_high().map_err(Error)
+    }
+}
+
+/// Use an embedded-hal 0.2 [`InputPin`](v2::InputPin) where an embedded-hal
+/// 1.0 [`InputPin`](digital::InputPin) is expected.
+pub struct InputPin<PIN: v2::InputPin> {
+    pin: PIN,
+}
+
+impl<PIN: v2::InputPin> InputPin<PIN> {
+    pub fn new(pin: PIN) -> Self {
+        Self { pin }
+    }
+
+    pub fn free(self) -> PIN {
+        self.pin
+    }
+}
+
+impl<PIN: v2::InputPin> ErrorType for InputPin<PIN>
+where
+    PIN::Error: Debug,
+{
+    type Error = Error<PIN::Error>;
+}
+
+impl<PIN: v2::InputPin> digital::InputPin for InputPin<PIN>
+where
+    PIN::Error: Debug,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_high().map_err(Error)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_low().map_err(Error)
+    }
+}
@@ -3,6 +3,7 @@
 
 pub use embedded_hal::digital::v2::{InputPin, OutputPin};
 
+pub mod hal1;
 mod io;
 
 #[cfg(feature = "rp2040")]
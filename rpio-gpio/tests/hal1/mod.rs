@@ -0,0 +1,52 @@
+use embedded_hal::digital::v2;
+use embedded_hal_1::digital::{Error as _, ErrorKind, InputPin, OutputPin};
+use rpio_gpio::hal1;
+
+struct Pin {
+    high: bool,
+}
+
+impl v2::OutputPin for Pin {
+    type Error = ();
+
+    fn set_low(&mut self) -> Result<(), ()> {
+        self.high = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), ()> {
+        self.high = true;
+        Ok(())
+    }
+}
+
+impl v2::InputPin for Pin {
+    type Error = u8;
+
+    fn is_high(&self) -> Result<bool, u8> {
+        Err(7)
+    }
+
+    fn is_low(&self) -> Result<bool, u8> {
+        Ok(!self.high)
+    }
+}
+
+#[test]
+fn output_pin() {
+    let mut pin = hal1::OutputPin::new(Pin { high: false });
+
+    pin.set_high().unwrap();
+    assert!(pin.free().high);
+}
+
+#[test]
+fn input_pin() {
+    let mut pin = hal1::InputPin::new(Pin { high: true });
+
+    assert_eq!(pin.is_low(), Ok(false));
+
+    let err = pin.is_high().unwrap_err();
+    assert_eq!(err, hal1::Error(7));
+    assert_eq!(err.kind(), ErrorKind::Other);
+}
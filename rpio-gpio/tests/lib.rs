@@ -1,2 +1,3 @@
+mod hal1;
 mod io;
 mod pinout;
@@ -0,0 +1,22 @@
+use super::AsyncSpiDevice;
+use crate::{Operation, Result, SpiDevice};
+
+pub struct Spi<S: SpiDevice> {
+    spi: S,
+}
+
+impl<S: SpiDevice> Spi<S> {
+    pub fn new(spi: S) -> Self {
+        Self { spi }
+    }
+}
+
+impl<S: SpiDevice> AsyncSpiDevice for Spi<S> {
+    async fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.spi.transfer(words)
+    }
+
+    async fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result {
+        self.spi.transaction(operations)
+    }
+}
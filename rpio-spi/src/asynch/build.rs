@@ -0,0 +1,40 @@
+use super::{blocking, hal1};
+use crate::{Spi, SpiDevice};
+use embedded_hal_async::spi::SpiDevice as AsyncHalSpiDevice;
+
+impl Spi {
+    /// Construct an async transport from an embedded-hal-async
+    /// [`SpiDevice`](AsyncHalSpiDevice), which handles chip select.
+    pub fn from_async<D: AsyncHalSpiDevice<u8>>(spi: D) -> AsyncBuilder<D> {
+        AsyncBuilder { spi }
+    }
+
+    /// Use a blocking transport where an
+    /// [`AsyncSpiDevice`](super::AsyncSpiDevice) is expected. Each transfer
+    /// blocks until it is complete.
+    pub fn from_blocking<S: SpiDevice>(spi: S) -> BlockingBuilder<S> {
+        BlockingBuilder { spi }
+    }
+}
+
+pub struct AsyncBuilder<D: AsyncHalSpiDevice<u8>> {
+    spi: D,
+}
+
+impl<D: AsyncHalSpiDevice<u8>> AsyncBuilder<D> {
+    /// Initialize the transport.
+    pub fn init(self) -> hal1::Spi<D> {
+        hal1::Spi::new(self.spi)
+    }
+}
+
+pub struct BlockingBuilder<S: SpiDevice> {
+    spi: S,
+}
+
+impl<S: SpiDevice> BlockingBuilder<S> {
+    /// Initialize the transport.
+    pub fn init(self) -> blocking::Spi<S> {
+        blocking::Spi::new(self.spi)
+    }
+}
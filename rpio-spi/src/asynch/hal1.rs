@@ -0,0 +1,34 @@
+use super::AsyncSpiDevice;
+use crate::{Error, ErrorKind, Operation, Result};
+use embedded_hal_async::spi::SpiDevice as AsyncHalSpiDevice;
+
+pub struct Spi<D: AsyncHalSpiDevice<u8>> {
+    spi: D,
+}
+
+impl<D: AsyncHalSpiDevice<u8>> Spi<D> {
+    pub fn new(spi: D) -> Self {
+        Self { spi }
+    }
+}
+
+impl<D: AsyncHalSpiDevice<u8>> AsyncSpiDevice for Spi<D> {
+    async fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.spi
+            .transfer_in_place(words)
+            .await
+            .map_err(|err| Error::with_hal(ErrorKind::Transfer, err))?;
+
+        Ok(words)
+    }
+
+    /// At most 8 operations are supported.
+    async fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result {
+        let (mut operations, len) = crate::hal1::operations(operations)?;
+
+        self.spi
+            .transaction(&mut operations[..len])
+            .await
+            .map_err(|err| Error::with_hal(ErrorKind::Transfer, err))
+    }
+}
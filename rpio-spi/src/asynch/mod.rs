@@ -0,0 +1,6 @@
+mod blocking;
+mod build;
+mod hal1;
+mod traits;
+
+pub use traits::AsyncSpiDevice;
@@ -0,0 +1,12 @@
+use crate::{Operation, Result};
+
+/// The async counterpart of [`SpiDevice`](crate::SpiDevice). Transfers and
+/// transactions select the chip at the start and deselect it at the end.
+#[allow(async_fn_in_trait)]
+pub trait AsyncSpiDevice {
+    /// Exchange bytes with the chip.
+    async fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]>;
+
+    /// Perform the operations in order while the chip is selected.
+    async fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result;
+}
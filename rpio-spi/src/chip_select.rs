@@ -0,0 +1,54 @@
+use crate::{Error, ErrorKind, OutputPin, Result, SpiDevice};
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal_1::spi::ErrorKind as HalKind;
+
+/// How a transport drives its chip select pin.
+///
+/// The delays are in microseconds and need the transport to be able to
+/// [`delay_us`](SpiDevice::delay_us).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ChipSelectConfig {
+    /// Drive the pin high to select the chip.
+    pub active_high: bool,
+    /// Wait after selecting, before the first clock edge.
+    pub setup_us: u32,
+    /// Wait after the last clock edge, before deselecting.
+    pub hold_us: u32,
+    /// Keep the chip deselected for this long after deselecting.
+    pub gap_us: u32,
+}
+
+impl ChipSelectConfig {
+    pub(crate) fn select<CS: OutputPin>(&self, cs: &mut CS) -> Result {
+        match self.active_high {
+            true => cs.set_high(),
+            false => cs.set_low(),
+        }
+        .map_err(|_| Error::with_hal(ErrorKind::ChipSelect, HalKind::ChipSelectFault))
+    }
+
+    pub(crate) fn deselect<CS: OutputPin>(&self, cs: &mut CS) -> Result {
+        match self.active_high {
+            true => cs.set_low(),
+            false => cs.set_high(),
+        }
+        .map_err(|_| Error::with_hal(ErrorKind::ChipDeselect, HalKind::ChipSelectFault))
+    }
+}
+
+/// Wait using the transport, unless there is nothing to wait for.
+pub(crate) fn delay<S: SpiDevice>(spi: &mut S, us: u32) -> Result {
+    match us {
+        0 => Ok(()),
+        us => spi.delay_us(us),
+    }
+}
+
+/// The delay provider of a transport which was not given one.
+pub enum NoDelay {}
+
+impl DelayUs<u32> for NoDelay {
+    fn delay_us(&mut self, _us: u32) {
+        match *self {}
+    }
+}
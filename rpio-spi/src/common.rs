@@ -5,11 +5,57 @@ macro_rules! impl_cs_common {
         }
 
         fn select(&mut self) -> Result {
-            self.cs.set_low().or(Err(Error::ChipSelect))
+            let config = self.cs_config;
+
+            config.select(&mut self.cs)?;
+            crate::chip_select::delay(self, config.setup_us)
         }
 
         fn deselect(&mut self) -> Result {
-            self.cs.set_high().or(Err(Error::ChipDeselect))
+            let config = self.cs_config;
+
+            crate::chip_select::delay(self, config.hold_us)?;
+            config.deselect(&mut self.cs)?;
+            crate::chip_select::delay(self, config.gap_us)
+        }
+    };
+}
+
+macro_rules! impl_delay_common {
+    () => {
+        fn delay_us(&mut self, us: u32) -> Result {
+            let delay = self.delay.as_mut().ok_or(Error::NotImplemented)?;
+
+            delay.delay_us(us);
+            Ok(())
+        }
+    };
+}
+
+macro_rules! impl_cs_builder_common {
+    () => {
+        /// Drive the chip select pin high to select the chip.
+        pub fn with_active_high(mut self) -> Self {
+            self.cs_config.active_high = true;
+            self
+        }
+
+        /// Wait after selecting the chip, before the first clock edge.
+        pub fn with_setup_us(mut self, us: u32) -> Self {
+            self.cs_config.setup_us = us;
+            self
+        }
+
+        /// Wait after the last clock edge, before deselecting the chip.
+        pub fn with_hold_us(mut self, us: u32) -> Self {
+            self.cs_config.hold_us = us;
+            self
+        }
+
+        /// Keep the chip deselected for a minimum time between transfers.
+        pub fn with_gap_us(mut self, us: u32) -> Self {
+            self.cs_config.gap_us = us;
+            self
         }
     };
 }
@@ -19,7 +65,17 @@ macro_rules! impl_auto_transfer_common {
         type Error = Error;
 
         fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
-            self.spi.transfer(words).or(Err(Error::Transfer))
+            self.spi
+                .transfer(words)
+                .map_err(|err| Error::with_hal(crate::ErrorKind::Transfer, err))
+        }
+    };
+}
+
+macro_rules! impl_auto_raw_common {
+    () => {
+        fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+            self.transfer(words)
         }
     };
 }
@@ -35,3 +91,14 @@ macro_rules! impl_cs_transfer_common {
         }
     };
 }
+
+macro_rules! impl_hal1_common {
+    () => {
+        fn transaction(
+            &mut self,
+            operations: &mut [embedded_hal_1::spi::Operation<'_, u8>],
+        ) -> Result {
+            crate::hal1::transaction(self, operations)
+        }
+    };
+}
@@ -0,0 +1,182 @@
+//! Checks for telling wiring, clock and device faults apart.
+//!
+//! The loopback checks expect MOSI to be jumpered to MISO, so that every byte
+//! written is read back unchanged.
+
+use crate::{Error, ErrorKind, Result, SpiDevice};
+
+/// The bytes exchanged by the loopback checks: every bit both high and low,
+/// alternating and walking patterns.
+pub const PATTERN: [u8; 8] = [0x00, 0xFF, 0xAA, 0x55, 0x01, 0x80, 0x0F, 0xF0];
+
+/// The clock speeds tried by [`run`], slowest first.
+pub const SPEEDS: [u32; 8] = [
+    100_000, 500_000, 1_000_000, 2_000_000, 5_000_000, 10_000_000, 20_000_000, 40_000_000,
+];
+
+/// How many times each speed is checked by a clock sweep.
+pub const SWEEP_ROUNDS: usize = 4;
+
+/// The outcome of a loopback check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loopback {
+    Passed,
+    /// Every byte read was 0x00: MISO is not connected or held low.
+    StuckLow,
+    /// Every byte read was 0xFF: MISO is floating high or held high.
+    StuckHigh,
+    /// The first byte read back differently, from noise, a clock too fast for
+    /// the wiring or a mode mismatch.
+    Corrupt {
+        index: usize,
+        sent: u8,
+        received: u8,
+    },
+}
+
+impl Loopback {
+    pub fn is_passed(&self) -> bool {
+        *self == Loopback::Passed
+    }
+}
+
+/// The outcome of a clock sweep.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSweep {
+    /// The highest speed which passed, along with every speed below it.
+    pub highest: Option<u32>,
+    /// The first speed which failed, or which the transport rejected.
+    pub failed: Option<u32>,
+}
+
+/// The outcome of the chip select check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChipSelectCheck {
+    /// The transport does not control chip select.
+    Unsupported,
+    Passed,
+    /// Selecting, exchanging bytes or deselecting failed.
+    Failed(Error),
+    /// The transfer while selected did not loop back.
+    Loopback(Loopback),
+}
+
+/// The outcome of [`run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub loopback: Result<Loopback>,
+    /// `None` if the transport does not control clock speed.
+    pub clock: Option<Result<ClockSweep>>,
+    pub chip_select: ChipSelectCheck,
+}
+
+impl Report {
+    /// Whether every check that could run passed.
+    pub fn is_passed(&self) -> bool {
+        self.loopback == Ok(Loopback::Passed)
+            && !matches!(
+                self.clock,
+                Some(Err(_)) | Some(Ok(ClockSweep { highest: None, .. }))
+            )
+            && matches!(
+                self.chip_select,
+                ChipSelectCheck::Passed | ChipSelectCheck::Unsupported
+            )
+    }
+}
+
+/// Compare the bytes read back with [`PATTERN`].
+pub fn compare(received: &[u8]) -> Loopback {
+    let mismatch = PATTERN
+        .iter()
+        .zip(received)
+        .enumerate()
+        .find(|(_, (sent, received))| sent != received);
+
+    match mismatch {
+        None => Loopback::Passed,
+        Some(_) if received.iter().all(|&byte| byte == 0x00) => Loopback::StuckLow,
+        Some(_) if received.iter().all(|&byte| byte == 0xFF) => Loopback::StuckHigh,
+        Some((index, (&sent, &received))) => Loopback::Corrupt {
+            index,
+            sent,
+            received,
+        },
+    }
+}
+
+/// Exchange [`PATTERN`] in one transfer and compare what was read back.
+pub fn loopback<S: SpiDevice>(spi: &mut S) -> Result<Loopback> {
+    let mut words = PATTERN;
+    spi.transfer(&mut words)?;
+    Ok(compare(&words))
+}
+
+/// Try each of `speeds` in turn, slowest first, until `check` fails or
+/// returns false. The transport is left at the highest speed which passed, if
+/// any.
+pub fn sweep_clock<S, F>(spi: &mut S, speeds: &[u32], mut check: F) -> Result<ClockSweep>
+where
+    S: SpiDevice,
+    F: FnMut(&mut S) -> Result<bool>,
+{
+    let mut sweep = ClockSweep::default();
+
+    for &speed in speeds {
+        let passed = match spi.set_clock_speed(speed) {
+            Ok(()) => (0..SWEEP_ROUNDS).all(|_| check(spi).unwrap_or(false)),
+            Err(err) if err.kind() == ErrorKind::NotImplemented => return Err(err),
+            Err(_) => false,
+        };
+
+        if !passed {
+            sweep.failed = Some(speed);
+            break;
+        }
+
+        sweep.highest = Some(speed);
+    }
+
+    if let (Some(highest), Some(_)) = (sweep.highest, sweep.failed) {
+        spi.set_clock_speed(highest)?;
+    }
+
+    Ok(sweep)
+}
+
+/// Select the chip, exchange [`PATTERN`] without reselecting and deselect.
+pub fn check_chip_select<S: SpiDevice>(spi: &mut S) -> ChipSelectCheck {
+    if !spi.is_chip_select() {
+        return ChipSelectCheck::Unsupported;
+    }
+
+    let mut words = PATTERN;
+    let result = spi
+        .select()
+        .and_then(|_| spi.raw_transfer_or_deselect(&mut words).and(Ok(())))
+        .and_then(|_| spi.deselect());
+
+    match (result, compare(&words)) {
+        (Err(err), _) => ChipSelectCheck::Failed(err),
+        (Ok(()), Loopback::Passed) => ChipSelectCheck::Passed,
+        (Ok(()), loopback) => ChipSelectCheck::Loopback(loopback),
+    }
+}
+
+/// Run every check against a transport with MOSI jumpered to MISO, sweeping
+/// through [`SPEEDS`].
+pub fn run<S: SpiDevice>(spi: &mut S) -> Report {
+    let loopback = loopback(spi);
+
+    let clock = spi.is_clock_speed().then(|| {
+        sweep_clock(spi, &SPEEDS, |spi| {
+            Ok(self::loopback(spi)? == Loopback::Passed)
+        })
+    });
+
+    Report {
+        loopback,
+        clock,
+        chip_select: check_chip_select(spi),
+    }
+}
@@ -1,33 +1,174 @@
-/// Indicates an SPI error. The [`Transport`](super::traits::Transport)
-/// in use determines which errors are possible.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Error {
+#[cfg(feature = "std")]
+use std::{boxed::Box, sync::Arc};
+
+/// The operation which failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Init,
     Transfer,
     ChipSelect,
     ChipDeselect,
     ClockSpeed,
+    Mode,
+    WordSize,
+    BitOrder,
+    Busy,
+    Overflow,
+    Timeout,
     NotImplemented,
 }
 
+/// The error reported by the backend, when the transport has one.
+#[derive(Debug, Clone)]
+pub enum ErrorSource {
+    None,
+    /// The kind of an embedded-hal 1.0 SPI error.
+    Hal(embedded_hal_1::spi::ErrorKind),
+    /// An error from a backend with `std`, such as rppal.
+    #[cfg(feature = "std")]
+    Std(Arc<dyn std::error::Error + Send + Sync>),
+}
+
+/// Indicates an SPI error: the [`ErrorKind`] of operation which failed and
+/// the [`ErrorSource`] error from the backend. The
+/// [`SpiDevice`](super::traits::SpiDevice) in use determines which errors are
+/// possible.
+///
+/// Errors compare equal when their kinds are equal.
+#[derive(Debug, Clone)]
+pub struct Error {
+    kind: ErrorKind,
+    source: ErrorSource,
+}
+
+/// Errors without a source, one for each [`ErrorKind`].
+#[allow(non_upper_case_globals)]
+impl Error {
+    pub const Init: Error = Error::new(ErrorKind::Init);
+    pub const Transfer: Error = Error::new(ErrorKind::Transfer);
+    pub const ChipSelect: Error = Error::new(ErrorKind::ChipSelect);
+    pub const ChipDeselect: Error = Error::new(ErrorKind::ChipDeselect);
+    pub const ClockSpeed: Error = Error::new(ErrorKind::ClockSpeed);
+    pub const Mode: Error = Error::new(ErrorKind::Mode);
+    pub const WordSize: Error = Error::new(ErrorKind::WordSize);
+    pub const BitOrder: Error = Error::new(ErrorKind::BitOrder);
+    pub const Busy: Error = Error::new(ErrorKind::Busy);
+    pub const Overflow: Error = Error::new(ErrorKind::Overflow);
+    pub const Timeout: Error = Error::new(ErrorKind::Timeout);
+    pub const NotImplemented: Error = Error::new(ErrorKind::NotImplemented);
+}
+
+impl Error {
+    pub const fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            source: ErrorSource::None,
+        }
+    }
+
+    /// An error caused by an embedded-hal 1.0 SPI error.
+    pub fn with_hal<E: embedded_hal_1::spi::Error>(kind: ErrorKind, err: E) -> Self {
+        Self {
+            kind,
+            source: ErrorSource::Hal(err.kind()),
+        }
+    }
+
+    /// An error caused by a backend error.
+    #[cfg(feature = "std")]
+    pub fn with_source<E: std::error::Error + Send + Sync + 'static>(
+        kind: ErrorKind,
+        err: E,
+    ) -> Self {
+        let source: Box<dyn std::error::Error + Send + Sync> = Box::new(err);
+
+        Self {
+            kind,
+            source: ErrorSource::Std(source.into()),
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// The error reported by the backend.
+    pub fn backend(&self) -> &ErrorSource {
+        &self.source
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Self::new(kind)
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+impl Eq for Error {}
+
 /// Result where the Err is an SPI [`Error`].
 pub type Result<T = ()> = core::result::Result<T, Error>;
 
 #[cfg(feature = "std")]
-impl std::fmt::Display for Error {
+impl std::fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
             "{}",
             match self {
-                Error::Transfer => "SPI transfer error",
-                Error::ChipSelect => "Select SPI chip error",
-                Error::ChipDeselect => "Deselect SPI chip error",
-                Error::ClockSpeed => "Set SPI clock speed error",
-                Error::NotImplemented => "That feature is not implemented",
+                ErrorKind::Init => "Initialize SPI transport error",
+                ErrorKind::Transfer => "SPI transfer error",
+                ErrorKind::ChipSelect => "Select SPI chip error",
+                ErrorKind::ChipDeselect => "Deselect SPI chip error",
+                ErrorKind::ClockSpeed => "Set SPI clock speed error",
+                ErrorKind::Mode => "Set SPI mode error",
+                ErrorKind::WordSize => "Set SPI word size error",
+                ErrorKind::BitOrder => "Set SPI bit order error",
+                ErrorKind::Busy => "SPI background transfer in progress",
+                ErrorKind::Overflow => "SPI receive buffer overflow",
+                ErrorKind::Timeout => "SPI operation timed out",
+                ErrorKind::NotImplemented => "That feature is not implemented",
             }
         )
     }
 }
 
 #[cfg(feature = "std")]
-impl std::error::Error for Error {}
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.source {
+            ErrorSource::None => write!(f, "{}", self.kind),
+            ErrorSource::Hal(kind) => write!(f, "{}: {}", self.kind, kind),
+            ErrorSource::Std(err) => write!(f, "{}: {}", self.kind, err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.source {
+            ErrorSource::Std(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl embedded_hal_1::spi::Error for Error {
+    fn kind(&self) -> embedded_hal_1::spi::ErrorKind {
+        use embedded_hal_1::spi::ErrorKind as HalKind;
+
+        match (self.kind, &self.source) {
+            (_, ErrorSource::Hal(kind)) => *kind,
+            (ErrorKind::ChipSelect | ErrorKind::ChipDeselect, _) => HalKind::ChipSelectFault,
+            (ErrorKind::Overflow, _) => HalKind::Overrun,
+            _ => HalKind::Other,
+        }
+    }
+}
@@ -1,17 +1,20 @@
+use super::HalBus;
 use crate::{Error, Result, SpiDevice, Transfer};
 
-pub struct Spi<SPI: Transfer<u8>> {
+pub struct Spi<SPI: HalBus> {
     spi: SPI,
 }
 
-impl<SPI: Transfer<u8>> Spi<SPI> {
+impl<SPI: HalBus> Spi<SPI> {
     pub fn new(spi: SPI) -> Self {
         Self { spi }
     }
 }
 
-impl<SPI: Transfer<u8>> Transfer<u8> for Spi<SPI> {
+impl<SPI: HalBus> Transfer<u8> for Spi<SPI> {
     impl_auto_transfer_common!();
 }
 
-impl<SPI: Transfer<u8>> SpiDevice for Spi<SPI> {}
+impl<SPI: HalBus> SpiDevice for Spi<SPI> {
+    impl_auto_raw_common!();
+}
@@ -1,21 +1,39 @@
-use super::{auto, cs};
-use crate::{OutputPin, Spi, Transfer};
+use super::{auto, cs, hal1, HalBus};
+use crate::{ChipSelectConfig, NoDelay, OutputPin, Spi};
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal_1::spi::SpiDevice as Hal1SpiDevice;
 
 impl Spi {
-    /// Construct a transport from any [`Transfer<u8>`](Transfer).
-    pub fn from_hal<SPI: Transfer<u8>>(spi: SPI) -> HalBuilder<SPI> {
+    /// Construct a transport from an embedded-hal 0.2 bus whose errors have
+    /// an embedded-hal 1.0 kind. See [`HalBus`].
+    ///
+    /// An embedded-hal 1.0 bus and chip select pin can be used by wrapping
+    /// them in [`hal1::SpiBus`](crate::hal1::SpiBus) and
+    /// [`hal1::OutputPin`](crate::hal1::OutputPin).
+    pub fn from_hal<SPI: HalBus>(spi: SPI) -> HalBuilder<SPI> {
         HalBuilder { spi }
     }
+
+    /// Construct a transport from an embedded-hal 1.0
+    /// [`SpiDevice`](Hal1SpiDevice), which handles chip select.
+    pub fn from_hal1<D: Hal1SpiDevice<u8>>(spi: D) -> Hal1Builder<D> {
+        Hal1Builder { spi }
+    }
 }
 
-pub struct HalBuilder<SPI: Transfer<u8>> {
+pub struct HalBuilder<SPI: HalBus> {
     spi: SPI,
 }
 
-impl<SPI: Transfer<u8>> HalBuilder<SPI> {
+impl<SPI: HalBus> HalBuilder<SPI> {
     /// Use the provided chip select pin.
     pub fn with_cs<CS: OutputPin>(self, cs: CS) -> HalChipSelectBuilder<SPI, CS> {
-        HalChipSelectBuilder { spi: self.spi, cs }
+        HalChipSelectBuilder {
+            spi: self.spi,
+            cs,
+            cs_config: ChipSelectConfig::default(),
+            delay: None,
+        }
     }
 
     /// Initialize the transport.
@@ -26,14 +44,40 @@ impl<SPI: Transfer<u8>> HalBuilder<SPI> {
     }
 }
 
-pub struct HalChipSelectBuilder<SPI: Transfer<u8>, CS: OutputPin> {
+pub struct HalChipSelectBuilder<SPI: HalBus, CS: OutputPin, D: DelayUs<u32> = NoDelay> {
     spi: SPI,
     cs: CS,
+    cs_config: ChipSelectConfig,
+    delay: Option<D>,
+}
+
+impl<SPI: HalBus, CS: OutputPin, D: DelayUs<u32>> HalChipSelectBuilder<SPI, CS, D> {
+    impl_cs_builder_common!();
+
+    /// Use the provided delay for chip select timing and
+    /// [`delay_us`](crate::SpiDevice::delay_us).
+    pub fn with_delay<T: DelayUs<u32>>(self, delay: T) -> HalChipSelectBuilder<SPI, CS, T> {
+        HalChipSelectBuilder {
+            spi: self.spi,
+            cs: self.cs,
+            cs_config: self.cs_config,
+            delay: Some(delay),
+        }
+    }
+
+    /// Initialize the transport.
+    pub fn init(self) -> cs::Spi<SPI, CS, D> {
+        cs::Spi::new(self.spi, self.cs, self.cs_config, self.delay)
+    }
+}
+
+pub struct Hal1Builder<D: Hal1SpiDevice<u8>> {
+    spi: D,
 }
 
-impl<SPI: Transfer<u8>, CS: OutputPin> HalChipSelectBuilder<SPI, CS> {
+impl<D: Hal1SpiDevice<u8>> Hal1Builder<D> {
     /// Initialize the transport.
-    pub fn init(self) -> cs::Spi<SPI, CS> {
-        cs::Spi::new(self.spi, self.cs)
+    pub fn init(self) -> hal1::Spi<D> {
+        hal1::Spi::new(self.spi)
     }
 }
@@ -1,30 +1,54 @@
-use super::super::{Error, Result};
-use crate::{ChipSelect, OutputPin, SpiDevice, Transfer};
+use super::super::{Error, ErrorKind, Result};
+use super::HalBus;
+use crate::{ChipSelect, ChipSelectConfig, NoDelay, OutputPin, SpiDevice, Transfer};
+use embedded_hal::blocking::delay::DelayUs;
 
-pub struct Spi<SPI: Transfer<u8>, CS: OutputPin> {
+pub struct Spi<SPI: HalBus, CS: OutputPin, D: DelayUs<u32> = NoDelay> {
     spi: SPI,
     cs: CS,
+    cs_config: ChipSelectConfig,
+    delay: Option<D>,
 }
 
-impl<SPI: Transfer<u8>, CS: OutputPin> Spi<SPI, CS> {
-    pub fn new(spi: SPI, cs: CS) -> Self {
-        let mut transport = Self { spi, cs };
+impl<SPI: HalBus, CS: OutputPin, D: DelayUs<u32>> Spi<SPI, CS, D> {
+    pub fn new(spi: SPI, cs: CS, cs_config: ChipSelectConfig, delay: Option<D>) -> Self {
+        let mut transport = Self {
+            spi,
+            cs,
+            cs_config,
+            delay,
+        };
 
         transport.deselect().ok();
         transport
     }
 }
 
-impl<SPI: Transfer<u8>, CS: OutputPin> Transfer<u8> for Spi<SPI, CS> {
+impl<SPI: HalBus, CS: OutputPin, D: DelayUs<u32>> Transfer<u8> for Spi<SPI, CS, D> {
     impl_cs_transfer_common!();
 }
 
-impl<SPI: Transfer<u8>, CS: OutputPin> SpiDevice for Spi<SPI, CS> {
+impl<SPI: HalBus, CS: OutputPin, D: DelayUs<u32>> SpiDevice for Spi<SPI, CS, D> {
     impl_cs_common!();
+    impl_delay_common!();
 
     fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
-        self.spi.transfer(words).or(Err(Error::Transfer))
+        self.spi
+            .transfer(words)
+            .map_err(|err| Error::with_hal(ErrorKind::Transfer, err))
     }
 }
 
-impl<SPI: Transfer<u8>, CS: OutputPin> ChipSelect for Spi<SPI, CS> {}
+impl<SPI: HalBus, CS: OutputPin, D: DelayUs<u32>> embedded_hal_1::spi::ErrorType
+    for Spi<SPI, CS, D>
+{
+    type Error = Error;
+}
+
+impl<SPI: HalBus, CS: OutputPin, D: DelayUs<u32>> embedded_hal_1::spi::SpiDevice
+    for Spi<SPI, CS, D>
+{
+    impl_hal1_common!();
+}
+
+impl<SPI: HalBus, CS: OutputPin, D: DelayUs<u32>> ChipSelect for Spi<SPI, CS, D> {}
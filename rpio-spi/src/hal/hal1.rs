@@ -0,0 +1,40 @@
+use super::super::{Error, ErrorKind, Result};
+use crate::{Operation, SpiDevice, Transfer};
+use embedded_hal_1::spi::SpiDevice as Hal1SpiDevice;
+
+pub struct Spi<D: Hal1SpiDevice<u8>> {
+    spi: D,
+}
+
+impl<D: Hal1SpiDevice<u8>> Spi<D> {
+    pub fn new(spi: D) -> Self {
+        Self { spi }
+    }
+}
+
+impl<D: Hal1SpiDevice<u8>> Transfer<u8> for Spi<D> {
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.spi
+            .transfer_in_place(words)
+            .map_err(|err| Error::with_hal(ErrorKind::Transfer, err))?;
+
+        Ok(words)
+    }
+}
+
+impl<D: Hal1SpiDevice<u8>> SpiDevice for Spi<D> {
+    impl_auto_raw_common!();
+
+    /// Performs the operations as one embedded-hal 1.0 transaction, so chip
+    /// select is held by the device throughout. At most 8 operations are
+    /// supported.
+    fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result {
+        let (mut operations, len) = crate::hal1::operations(operations)?;
+
+        self.spi
+            .transaction(&mut operations[..len])
+            .map_err(|err| Error::with_hal(ErrorKind::Transfer, err))
+    }
+}
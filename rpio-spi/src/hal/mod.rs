@@ -1,3 +1,13 @@
 mod auto;
 mod build;
 mod cs;
+mod hal1;
+
+use crate::Transfer;
+
+/// An embedded-hal 0.2 bus whose errors have an embedded-hal 1.0
+/// [`ErrorKind`](embedded_hal_1::spi::ErrorKind), which transfer errors keep
+/// as their [`ErrorSource`](crate::ErrorSource).
+pub trait HalBus: Transfer<u8, Error: embedded_hal_1::spi::Error> {}
+
+impl<T: Transfer<u8, Error: embedded_hal_1::spi::Error>> HalBus for T {}
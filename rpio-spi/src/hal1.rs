@@ -0,0 +1,139 @@
+use crate::{Result, SpiDevice, Transfer};
+use embedded_hal::digital::v2;
+use embedded_hal_1::{
+    digital,
+    spi::{self, Operation},
+};
+
+/// Use an embedded-hal 1.0 [`SpiBus`](spi::SpiBus) where an embedded-hal 0.2
+/// [`Transfer<u8>`] is expected, such as [`Spi::from_hal`](crate::Spi).
+pub struct SpiBus<BUS: spi::SpiBus<u8>> {
+    bus: BUS,
+}
+
+impl<BUS: spi::SpiBus<u8>> SpiBus<BUS> {
+    pub fn new(bus: BUS) -> Self {
+        Self { bus }
+    }
+
+    pub fn free(self) -> BUS {
+        self.bus
+    }
+}
+
+impl<BUS: spi::SpiBus<u8>> Transfer<u8> for SpiBus<BUS> {
+    type Error = BUS::Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> core::result::Result<&'w [u8], BUS::Error> {
+        self.bus.transfer_in_place(words)?;
+        self.bus.flush()?;
+        Ok(words)
+    }
+}
+
+/// Use an embedded-hal 1.0 [`OutputPin`](digital::OutputPin) where an
+/// embedded-hal 0.2 [`OutputPin`](v2::OutputPin) is expected, such as for chip
+/// select or the `PicoOled` data/command pin.
+pub struct OutputPin<PIN: digital::OutputPin> {
+    pin: PIN,
+}
+
+impl<PIN: digital::OutputPin> OutputPin<PIN> {
+    pub fn new(pin: PIN) -> Self {
+        Self { pin }
+    }
+
+    pub fn free(self) -> PIN {
+        self.pin
+    }
+}
+
+impl<PIN: digital::OutputPin> v2::OutputPin for OutputPin<PIN> {
+    type Error = PIN::Error;
+
+    fn set_low(&mut self) -> core::result::Result<(), PIN::Error> {
+        self.pin.set_low()
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), PIN::Error> {
+        self.pin.set_high()
+    }
+}
+
+/// Perform embedded-hal 1.0 operations in order while the chip is selected.
+/// The chip is deselected at the end, or when an operation fails.
+#[cfg(any(
+    feature = "hal",
+    feature = "soft",
+    feature = "mock",
+    feature = "rppal",
+    feature = "linux",
+    feature = "rp2040"
+))]
+pub(crate) fn transaction<S: SpiDevice>(
+    spi: &mut S,
+    operations: &mut [Operation<'_, u8>],
+) -> Result {
+    spi.select()?;
+
+    for operation in operations.iter_mut() {
+        raw_operation(spi, operation)
+            .map_err(|err| spi.deselect().map_or(crate::Error::ChipDeselect, |_| err))?;
+    }
+
+    spi.deselect()
+}
+
+/// Perform an embedded-hal 1.0 operation without selecting or deselecting the
+/// chip.
+pub(crate) fn raw_operation<S: SpiDevice>(
+    spi: &mut S,
+    operation: &mut Operation<'_, u8>,
+) -> Result {
+    match operation {
+        Operation::Read(words) => spi.raw_read(words),
+        Operation::Write(words) => spi.raw_write(words),
+        Operation::Transfer(read, write) => {
+            let len = read.len().min(write.len());
+
+            read[..len].copy_from_slice(&write[..len]);
+            spi.raw_transfer(&mut read[..len])?;
+            spi.raw_write(&write[len..])?;
+            spi.raw_read(&mut read[len..])
+        }
+        Operation::TransferInPlace(words) => spi.raw_transfer(words).and(Ok(())),
+        Operation::DelayNs(ns) => spi.delay_us(ns.div_ceil(1000)),
+    }
+}
+
+/// The most operations which can be converted into one embedded-hal 1.0
+/// transaction.
+#[cfg(any(feature = "hal", feature = "async"))]
+pub(crate) const MAX_OPERATIONS: usize = 8;
+
+/// Convert operations for an embedded-hal 1.0 transaction. Returns the
+/// converted operations and how many of them are in use.
+#[cfg(any(feature = "hal", feature = "async"))]
+pub(crate) fn operations<'a>(
+    operations: &'a mut [crate::Operation<'_>],
+) -> Result<([Operation<'a, u8>; MAX_OPERATIONS], usize)> {
+    let len = operations.len();
+
+    if len > MAX_OPERATIONS {
+        return Err(crate::Error::NotImplemented);
+    }
+
+    let mut hal1: [Operation<'a, u8>; MAX_OPERATIONS] =
+        core::array::from_fn(|_| Operation::DelayNs(0));
+
+    for (operation, hal1) in operations.iter_mut().zip(hal1.iter_mut()) {
+        *hal1 = match operation {
+            crate::Operation::Write(words) => Operation::Write(words),
+            crate::Operation::Read(words) => Operation::Read(words),
+            crate::Operation::Transfer(words) => Operation::TransferInPlace(words),
+            crate::Operation::DelayUs(us) => Operation::DelayNs(us.saturating_mul(1000)),
+        };
+    }
+
+    Ok((hal1, len))
+}
@@ -1,7 +1,11 @@
 #![no_std]
 #![warn(clippy::all)]
 
-pub use embedded_hal::{blocking::spi::Transfer, digital::v2::OutputPin};
+pub use embedded_hal::{
+    blocking::spi::Transfer,
+    digital::v2::OutputPin,
+    spi::{Phase, Polarity, MODE_0, MODE_1, MODE_2, MODE_3},
+};
 
 #[cfg(feature = "std")]
 extern crate std;
@@ -9,28 +13,76 @@ extern crate std;
 #[macro_use]
 mod common;
 
+mod chip_select;
 mod error;
+mod retry;
+mod shared;
+mod slave;
+mod trace;
 mod traits;
 
+pub mod diagnostics;
+pub mod hal1;
+
 #[derive(Debug, Default)]
 pub struct Spi;
 
 #[cfg(feature = "hal")]
 mod hal;
 
+#[cfg(feature = "hal")]
+pub use hal::HalBus;
+
+#[cfg(feature = "soft")]
+mod soft;
+
+#[cfg(feature = "async")]
+mod asynch;
+
+#[cfg(feature = "async")]
+pub use asynch::AsyncSpiDevice;
+
+#[cfg(feature = "mock")]
+mod mock;
+
+#[cfg(feature = "mock")]
+pub use mock::{MockSpi, Transaction};
+
+#[cfg(feature = "std")]
+pub use trace::WriteSink;
+
 #[cfg(feature = "rppal")]
 mod rppal;
 
 #[cfg(feature = "rppal")]
-pub use _rppal::spi::{Bus, Mode, SlaveSelect};
+pub use _rppal::spi::{Bus, Mode, Segment, SlaveSelect};
+
+#[cfg(feature = "rppal")]
+pub use rppal::RppalBus;
+
+#[cfg(feature = "linux")]
+mod linux;
 
 #[cfg(feature = "rp2040")]
 mod rp2040;
 
 #[cfg(feature = "rp2040")]
-pub use embedded_hal::spi::{Mode, MODE_0, MODE_1, MODE_2, MODE_3};
+pub use embedded_hal::spi::Mode;
+
+#[cfg(feature = "rp2040")]
+pub use rp2040::DmaChannel;
 
 pub use {
-    error::{Error, Result},
-    traits::{ChipSelect, ClockSpeed, SpiDevice},
+    chip_select::{ChipSelectConfig, NoDelay},
+    error::{Error, ErrorKind, ErrorSource, Result},
+    retry::{Retry, RetryPolicy},
+    shared::*,
+    slave::{SlaveQueue, MAX_TRANSACTIONS},
+    trace::{
+        Decoder, Entry, NoDecoder, Record, RingBuffer, Text, TraceEvent, TraceSink, Traced, Vcd,
+    },
+    traits::{
+        BackgroundTransfer, BitOrder, ChipSelect, ClockSpeed, FirstBit, Lines, MultiLine,
+        Operation, Phases, SpiDevice, SpiMode, SpiSlave, WordSize,
+    },
 };
@@ -0,0 +1,96 @@
+use crate::{
+    BitOrder, ClockSpeed, Error, FirstBit, Lines, MultiLine, Operation, Phases, Result, SpiDevice,
+    SpiMode, Transfer, WordSize,
+};
+use embedded_hal::spi::Mode;
+use spidev::Spidev;
+
+pub struct Spi {
+    spi: Spidev,
+    lines: Lines,
+}
+
+impl Spi {
+    pub fn new(spi: Spidev, lines: Lines) -> Self {
+        Self { spi, lines }
+    }
+}
+
+impl Transfer<u8> for Spi {
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        super::transfer(&mut self.spi, words)
+    }
+}
+
+impl SpiDevice for Spi {
+    impl_auto_raw_common!();
+
+    fn is_clock_speed(&self) -> bool {
+        true
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        super::set_clock_speed(&mut self.spi, speed)
+    }
+
+    fn is_mode(&self) -> bool {
+        true
+    }
+
+    fn is_word_size(&self) -> bool {
+        true
+    }
+
+    fn is_bit_order(&self) -> bool {
+        true
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        super::set_mode(&mut self.spi, mode, self.lines)
+    }
+
+    fn set_word_size(&mut self, bits: u8) -> Result {
+        super::set_word_size(&mut self.spi, bits)
+    }
+
+    fn set_bit_order(&mut self, order: FirstBit) -> Result {
+        super::set_bit_order(&mut self.spi, order)
+    }
+
+    fn delay_us(&mut self, us: u32) -> Result {
+        super::delay_us(us)
+    }
+
+    fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result {
+        super::transaction(&mut self.spi, operations)
+    }
+
+    fn max_lines(&self) -> Lines {
+        self.lines
+    }
+
+    fn read_multi_line(
+        &mut self,
+        phases: Phases,
+        instruction: u8,
+        address: &[u8],
+        words: &mut [u8],
+    ) -> Result {
+        super::read_multi_line(
+            &mut self.spi,
+            self.lines,
+            phases,
+            instruction,
+            address,
+            words,
+        )
+    }
+}
+
+impl ClockSpeed for Spi {}
+impl SpiMode for Spi {}
+impl WordSize for Spi {}
+impl BitOrder for Spi {}
+impl MultiLine for Spi {}
@@ -0,0 +1,113 @@
+use super::{auto, cs, line_flags, mode};
+use crate::{ChipSelectConfig, Error, ErrorKind, Lines, Spi as EmbeddedSpi};
+use embedded_hal::spi::{Mode, MODE_0};
+use gpio_cdev::{Chip, LineHandle, LineRequestFlags};
+use spidev::{Spidev, SpidevOptions};
+use std::{io, path::Path};
+
+const CONSUMER: &str = "rpio-spi";
+
+impl EmbeddedSpi {
+    /// Open a Linux spidev device, such as `/dev/spidev0.0`.
+    pub fn new_linux<P: AsRef<Path>>(path: P) -> LinuxBuilder {
+        LinuxBuilder {
+            spi: Spidev::open(path),
+            options: SpidevOptions::new(),
+            mode: None,
+            lines: Lines::Single,
+        }
+    }
+
+    /// Construct a transport from an already opened [`Spidev`].
+    pub fn from_linux(spi: Spidev) -> LinuxBuilder {
+        LinuxBuilder {
+            spi: Ok(spi),
+            options: SpidevOptions::new(),
+            mode: None,
+            lines: Lines::Single,
+        }
+    }
+}
+
+pub struct LinuxBuilder {
+    spi: io::Result<Spidev>,
+    options: SpidevOptions,
+    mode: Option<Mode>,
+    lines: Lines,
+}
+
+impl LinuxBuilder {
+    /// Set the clock speed when initializing.
+    pub fn with_clock_speed(mut self, speed: u32) -> Self {
+        self.options.max_speed_hz(speed);
+        self
+    }
+
+    /// Set the SPI mode when initializing.
+    pub fn with_mode(mut self, value: Mode) -> Self {
+        self.mode = Some(value);
+        self
+    }
+
+    /// Allow multi-line reads on up to `lines` data lines, if the controller
+    /// and its device tree node support them.
+    pub fn with_lines(mut self, lines: Lines) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    /// Drive `line` of the GPIO character device at `chip` (such as
+    /// `/dev/gpiochip0`) for chip select.
+    pub fn with_cs<P: AsRef<Path>>(self, chip: P, line: u32) -> LinuxChipSelectBuilder {
+        let cs = Chip::new(chip)
+            .and_then(|mut chip| chip.get_line(line))
+            .and_then(|line| line.request(LineRequestFlags::OUTPUT, 1, CONSUMER));
+
+        LinuxChipSelectBuilder {
+            builder: self,
+            cs,
+            cs_config: ChipSelectConfig::default(),
+        }
+    }
+
+    fn open(mut self) -> Result<(Spidev, Lines), Error> {
+        let mut spi = self
+            .spi
+            .map_err(|err| Error::with_source(ErrorKind::Init, err))?;
+
+        if self.mode.is_some() || self.lines != Lines::Single {
+            let value = self.mode.unwrap_or(MODE_0);
+            self.options.mode(mode(value) | line_flags(self.lines));
+        }
+
+        spi.configure(&self.options.build())
+            .map_err(|err| Error::with_source(ErrorKind::Init, err))?;
+        Ok((spi, self.lines))
+    }
+
+    /// Initialize the transport.
+    pub fn init(self) -> Result<auto::Spi, Error> {
+        let (spi, lines) = self.open()?;
+        Ok(auto::Spi::new(spi, lines))
+    }
+}
+
+pub struct LinuxChipSelectBuilder {
+    builder: LinuxBuilder,
+    cs: Result<LineHandle, gpio_cdev::Error>,
+    cs_config: ChipSelectConfig,
+}
+
+impl LinuxChipSelectBuilder {
+    impl_cs_builder_common!();
+
+    /// Initialize the transport.
+    pub fn init(self) -> Result<cs::Spi, Error> {
+        let cs = self
+            .cs
+            .map_err(|err| Error::with_source(ErrorKind::ChipSelect, err))?;
+
+        let (spi, lines) = self.builder.open()?;
+        Ok(cs::Spi::new(spi, lines, cs, self.cs_config))
+    }
+}
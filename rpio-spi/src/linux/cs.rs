@@ -0,0 +1,142 @@
+use crate::{
+    BitOrder, ChipSelect, ChipSelectConfig, ClockSpeed, Error, ErrorKind, FirstBit, Lines,
+    MultiLine, Phases, Result, SpiDevice, SpiMode, Transfer, WordSize,
+};
+use embedded_hal::spi::Mode;
+use gpio_cdev::LineHandle;
+use spidev::Spidev;
+
+pub struct Spi {
+    spi: Spidev,
+    lines: Lines,
+    cs: LineHandle,
+    cs_config: ChipSelectConfig,
+}
+
+impl Spi {
+    pub fn new(spi: Spidev, lines: Lines, cs: LineHandle, cs_config: ChipSelectConfig) -> Self {
+        let mut transport = Self {
+            spi,
+            lines,
+            cs,
+            cs_config,
+        };
+
+        transport.deselect().ok();
+        transport
+    }
+
+    fn set_cs(&mut self, active: bool, kind: ErrorKind) -> Result {
+        let level = active == self.cs_config.active_high;
+
+        self.cs
+            .set_value(level.into())
+            .map_err(|err| Error::with_source(kind, err))
+    }
+}
+
+impl SpiDevice for Spi {
+    fn is_chip_select(&self) -> bool {
+        true
+    }
+
+    fn select(&mut self) -> Result {
+        let config = self.cs_config;
+
+        self.set_cs(true, ErrorKind::ChipSelect)?;
+        crate::chip_select::delay(self, config.setup_us)
+    }
+
+    fn deselect(&mut self) -> Result {
+        let config = self.cs_config;
+
+        crate::chip_select::delay(self, config.hold_us)?;
+        self.set_cs(false, ErrorKind::ChipDeselect)?;
+        crate::chip_select::delay(self, config.gap_us)
+    }
+
+    fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        super::transfer(&mut self.spi, words)
+    }
+
+    fn is_clock_speed(&self) -> bool {
+        true
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        super::set_clock_speed(&mut self.spi, speed)
+    }
+
+    fn is_mode(&self) -> bool {
+        true
+    }
+
+    fn is_word_size(&self) -> bool {
+        true
+    }
+
+    fn is_bit_order(&self) -> bool {
+        true
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        super::set_mode(&mut self.spi, mode, self.lines)
+    }
+
+    fn set_word_size(&mut self, bits: u8) -> Result {
+        super::set_word_size(&mut self.spi, bits)
+    }
+
+    fn set_bit_order(&mut self, order: FirstBit) -> Result {
+        super::set_bit_order(&mut self.spi, order)
+    }
+
+    fn delay_us(&mut self, us: u32) -> Result {
+        super::delay_us(us)
+    }
+
+    fn max_lines(&self) -> Lines {
+        self.lines
+    }
+
+    fn read_multi_line(
+        &mut self,
+        phases: Phases,
+        instruction: u8,
+        address: &[u8],
+        words: &mut [u8],
+    ) -> Result {
+        self.select()?;
+
+        super::read_multi_line(
+            &mut self.spi,
+            self.lines,
+            phases,
+            instruction,
+            address,
+            words,
+        )
+        .map_err(|err| self.deselect().map_or(Error::ChipDeselect, |_| err))?;
+
+        self.deselect()
+    }
+}
+
+impl Transfer<u8> for Spi {
+    impl_cs_transfer_common!();
+}
+
+impl embedded_hal_1::spi::ErrorType for Spi {
+    type Error = Error;
+}
+
+impl embedded_hal_1::spi::SpiDevice for Spi {
+    impl_hal1_common!();
+}
+
+impl ChipSelect for Spi {}
+impl ClockSpeed for Spi {}
+impl SpiMode for Spi {}
+impl WordSize for Spi {}
+impl BitOrder for Spi {}
+impl MultiLine for Spi {}
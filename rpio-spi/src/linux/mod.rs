@@ -0,0 +1,158 @@
+mod auto;
+mod build;
+mod cs;
+
+use crate::{Error, ErrorKind, FirstBit, Lines, Operation, Phases, Result};
+use embedded_hal::spi::{Mode, Phase, Polarity};
+use spidev::{SpiModeFlags, Spidev, SpidevOptions, SpidevTransfer};
+use std::{thread, time::Duration, vec::Vec};
+
+fn mode(mode: Mode) -> SpiModeFlags {
+    match (mode.polarity, mode.phase) {
+        (Polarity::IdleLow, Phase::CaptureOnFirstTransition) => SpiModeFlags::SPI_MODE_0,
+        (Polarity::IdleLow, Phase::CaptureOnSecondTransition) => SpiModeFlags::SPI_MODE_1,
+        (Polarity::IdleHigh, Phase::CaptureOnFirstTransition) => SpiModeFlags::SPI_MODE_2,
+        (Polarity::IdleHigh, Phase::CaptureOnSecondTransition) => SpiModeFlags::SPI_MODE_3,
+    }
+}
+
+fn line_flags(lines: Lines) -> SpiModeFlags {
+    match lines {
+        Lines::Single => SpiModeFlags::empty(),
+        Lines::Dual => SpiModeFlags::SPI_TX_DUAL | SpiModeFlags::SPI_RX_DUAL,
+        Lines::Quad => SpiModeFlags::SPI_TX_QUAD | SpiModeFlags::SPI_RX_QUAD,
+    }
+}
+
+fn configure(spi: &mut Spidev, options: &mut SpidevOptions, kind: ErrorKind) -> Result {
+    spi.configure(&options.build())
+        .map_err(|err| Error::with_source(kind, err))
+}
+
+fn set_clock_speed(spi: &mut Spidev, speed: u32) -> Result {
+    configure(
+        spi,
+        SpidevOptions::new().max_speed_hz(speed),
+        ErrorKind::ClockSpeed,
+    )
+}
+
+fn set_mode(spi: &mut Spidev, value: Mode, lines: Lines) -> Result {
+    configure(
+        spi,
+        SpidevOptions::new().mode(mode(value) | line_flags(lines)),
+        ErrorKind::Mode,
+    )
+}
+
+fn set_word_size(spi: &mut Spidev, bits: u8) -> Result {
+    configure(
+        spi,
+        SpidevOptions::new().bits_per_word(bits),
+        ErrorKind::WordSize,
+    )
+}
+
+fn set_bit_order(spi: &mut Spidev, order: FirstBit) -> Result {
+    configure(
+        spi,
+        SpidevOptions::new().lsb_first(order == FirstBit::Lsb),
+        ErrorKind::BitOrder,
+    )
+}
+
+fn transfer<'w>(spi: &mut Spidev, words: &'w mut [u8]) -> Result<&'w [u8]> {
+    let write = words.to_vec();
+
+    spi.transfer(&mut SpidevTransfer::read_write(&write, words))
+        .map_err(|err| Error::with_source(ErrorKind::Transfer, err))?;
+
+    Ok(words)
+}
+
+fn delay_us(us: u32) -> Result {
+    thread::sleep(Duration::from_micros(us.into()));
+    Ok(())
+}
+
+/// Performs the operations as a single message, so the kernel keeps its
+/// chip select active throughout.
+fn transaction(spi: &mut Spidev, operations: &mut [Operation<'_>]) -> Result {
+    let writes: Vec<Vec<u8>> = operations
+        .iter()
+        .map(|operation| match operation {
+            Operation::Transfer(words) => words.to_vec(),
+            _ => Vec::new(),
+        })
+        .collect();
+
+    let mut transfers = Vec::with_capacity(operations.len());
+
+    for (operation, write) in operations.iter_mut().zip(&writes) {
+        match operation {
+            Operation::Write(words) => transfers.push(SpidevTransfer::write(words)),
+            Operation::Read(words) => transfers.push(SpidevTransfer::read(words)),
+            Operation::Transfer(words) => transfers.push(SpidevTransfer::read_write(write, words)),
+            Operation::DelayUs(us) => {
+                let mut remaining = *us;
+
+                while remaining > 0 {
+                    let delay = remaining.min(u16::MAX.into());
+
+                    transfers.push(SpidevTransfer::delay(delay as u16));
+                    remaining -= delay;
+                }
+            }
+        }
+    }
+
+    spi.transfer_multiple(&mut transfers)
+        .map_err(|err| Error::with_source(ErrorKind::Transfer, err))
+}
+
+fn with_lines(mut transfer: SpidevTransfer<'_, '_>, lines: Lines) -> SpidevTransfer<'_, '_> {
+    transfer.tx_nbits = lines as u8;
+    transfer.rx_nbits = lines as u8;
+    transfer
+}
+
+/// Performs the phases as a single message, so the kernel keeps its chip
+/// select active throughout.
+fn read_multi_line(
+    spi: &mut Spidev,
+    lines: Lines,
+    phases: Phases,
+    instruction: u8,
+    address: &[u8],
+    words: &mut [u8],
+) -> Result {
+    if phases.max_lines() > lines {
+        return Err(Error::NotImplemented);
+    }
+
+    let instruction = [instruction];
+    let dummy = [0; 128];
+    let dummy_len = (phases.dummy_cycles as usize * phases.address as usize).div_ceil(8);
+    let mut transfers = Vec::with_capacity(4);
+
+    transfers.push(with_lines(
+        SpidevTransfer::write(&instruction),
+        phases.instruction,
+    ));
+
+    if !address.is_empty() {
+        transfers.push(with_lines(SpidevTransfer::write(address), phases.address));
+    }
+
+    if dummy_len > 0 {
+        transfers.push(with_lines(
+            SpidevTransfer::write(&dummy[..dummy_len]),
+            phases.address,
+        ));
+    }
+
+    transfers.push(with_lines(SpidevTransfer::read(words), phases.data));
+
+    spi.transfer_multiple(&mut transfers)
+        .map_err(|err| Error::with_source(ErrorKind::Transfer, err))
+}
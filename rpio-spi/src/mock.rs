@@ -0,0 +1,327 @@
+use crate::{
+    BackgroundTransfer, BitOrder, ChipSelect, ClockSpeed, Error, FirstBit, Lines, MultiLine,
+    Phases, Result, SpiDevice, SpiMode, Transfer, WordSize,
+};
+use embedded_hal::spi::{Mode, Phase, Polarity};
+use std::{cell::RefCell, collections::VecDeque, rc::Rc, vec::Vec};
+
+/// An SPI event expected or recorded by a [`MockSpi`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transaction {
+    Select,
+    Deselect,
+    ClockSpeed(u32),
+    /// The SPI mode number, from 0 to 3.
+    Mode(u8),
+    WordSize(u8),
+    BitOrder(FirstBit),
+    DelayUs(u32),
+    /// The bytes written to the chip and the bytes read back.
+    Transfer(Vec<u8>, Vec<u8>),
+    /// A whole multi-line read: the phases, the instruction and address,
+    /// and the bytes read.
+    MultiLineRead(Phases, Vec<u8>, Vec<u8>),
+}
+
+impl Transaction {
+    /// A transfer writing `write` and responding with `read`, which must be
+    /// the same length.
+    pub fn transfer(write: &[u8], read: &[u8]) -> Self {
+        assert_eq!(
+            write.len(),
+            read.len(),
+            "MockSpi: transfer write and read lengths differ"
+        );
+        Self::Transfer(write.into(), read.into())
+    }
+
+    /// A change to the provided SPI mode.
+    pub fn mode(mode: Mode) -> Self {
+        let polarity = (mode.polarity == Polarity::IdleHigh) as u8;
+        let phase = (mode.phase == Phase::CaptureOnSecondTransition) as u8;
+
+        Self::Mode(polarity << 1 | phase)
+    }
+
+    /// A multi-line read of `instruction` and `address`, responding with
+    /// `read`.
+    pub fn multi_line_read(phases: Phases, instruction: u8, address: &[u8], read: &[u8]) -> Self {
+        let mut header = Vec::from([instruction]);
+        header.extend_from_slice(address);
+
+        Self::MultiLineRead(phases, header, read.into())
+    }
+
+    /// A transfer writing `write` and responding with the same bytes.
+    pub fn write(write: &[u8]) -> Self {
+        Self::Transfer(write.into(), write.into())
+    }
+
+    /// Whether `actual` satisfies this expectation. Only the bytes written
+    /// are compared for transfers.
+    fn matches(&self, actual: &Transaction) -> bool {
+        match (self, actual) {
+            (Self::Transfer(expected, _), Self::Transfer(actual, _)) => expected == actual,
+            (
+                Self::MultiLineRead(expected_phases, expected, _),
+                Self::MultiLineRead(actual_phases, actual, _),
+            ) => expected_phases == actual_phases && expected == actual,
+            (expected, actual) => expected == actual,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    expected: VecDeque<(Transaction, Option<Error>)>,
+    log: Vec<Transaction>,
+    scripted: bool,
+    max_lines: Lines,
+    /// The buffer of the background transfer in progress.
+    background: Option<&'static mut [u8]>,
+}
+
+/// An SPI double for testing drivers on the host.
+///
+/// Every select, deselect, transfer, delay and clock speed change is recorded.
+/// Once expectations are loaded, each event must match the next expectation
+/// or the mock panics showing both. Transfers then respond with the scripted
+/// bytes, and an expectation can fail with any [`Error`] instead.
+///
+/// Background transfers are performed as soon as they start, and the buffer
+/// is held until they are finished.
+///
+/// Clones share the same state, so one can be given to the driver while the
+/// test keeps another to inspect.
+#[derive(Debug, Clone, Default)]
+pub struct MockSpi {
+    state: Rc<RefCell<State>>,
+}
+
+impl MockSpi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a mock expecting the provided transactions in order.
+    pub fn with_expectations(expected: &[Transaction]) -> Self {
+        let mock = Self::new();
+        mock.expect_all(expected);
+        mock
+    }
+
+    /// Expect a transaction after those already expected.
+    pub fn expect(&self, transaction: Transaction) -> &Self {
+        self.push(transaction, None)
+    }
+
+    /// Expect the transactions in order after those already expected.
+    pub fn expect_all(&self, transactions: &[Transaction]) -> &Self {
+        for transaction in transactions {
+            self.expect(transaction.clone());
+        }
+
+        self
+    }
+
+    /// Expect a transaction which fails with `err`.
+    pub fn expect_err(&self, transaction: Transaction, err: Error) -> &Self {
+        self.push(transaction, Some(err))
+    }
+
+    /// Accept multi-line reads on up to `lines` data lines.
+    pub fn set_max_lines(&self, lines: Lines) -> &Self {
+        self.state.borrow_mut().max_lines = lines;
+        self
+    }
+
+    /// The transactions performed so far.
+    pub fn log(&self) -> Vec<Transaction> {
+        self.state.borrow().log.clone()
+    }
+
+    /// Panic if any expected transaction has not been performed.
+    pub fn done(&self) {
+        let state = self.state.borrow();
+
+        assert!(
+            state.expected.is_empty(),
+            "MockSpi: {} expected transactions not performed: {:?}",
+            state.expected.len(),
+            state.expected
+        );
+    }
+
+    fn push(&self, transaction: Transaction, err: Option<Error>) -> &Self {
+        let mut state = self.state.borrow_mut();
+        state.scripted = true;
+        state.expected.push_back((transaction, err));
+        drop(state);
+        self
+    }
+
+    /// Check a transaction against the next expectation and record it,
+    /// with the scripted response for transfers.
+    fn perform(&self, actual: Transaction) -> Result<Transaction> {
+        let mut state = self.state.borrow_mut();
+        let index = state.log.len();
+
+        let (performed, result) = if state.scripted {
+            let (expected, err) = match state.expected.pop_front() {
+                Some(next) => next,
+                None => panic!("MockSpi: unexpected transaction {index}: {actual:?}"),
+            };
+
+            if !expected.matches(&actual) {
+                panic!(
+                    "MockSpi: transaction {index} mismatch\n\
+                     expected: {expected:?}\n  \
+                     actual: {actual:?}"
+                );
+            }
+
+            match (actual, expected, err) {
+                (actual, _, Some(err)) => (actual, Err(err)),
+                (Transaction::Transfer(write, _), Transaction::Transfer(_, read), None) => {
+                    (Transaction::Transfer(write, read), Ok(()))
+                }
+                (
+                    Transaction::MultiLineRead(phases, header, _),
+                    Transaction::MultiLineRead(_, _, read),
+                    None,
+                ) => (Transaction::MultiLineRead(phases, header, read), Ok(())),
+                (actual, _, None) => (actual, Ok(())),
+            }
+        } else {
+            (actual, Ok(()))
+        };
+
+        state.log.push(performed.clone());
+        result.and(Ok(performed))
+    }
+}
+
+impl SpiDevice for MockSpi {
+    fn is_chip_select(&self) -> bool {
+        true
+    }
+
+    fn is_clock_speed(&self) -> bool {
+        true
+    }
+
+    fn is_mode(&self) -> bool {
+        true
+    }
+
+    fn is_word_size(&self) -> bool {
+        true
+    }
+
+    fn is_bit_order(&self) -> bool {
+        true
+    }
+
+    fn select(&mut self) -> Result {
+        self.perform(Transaction::Select).and(Ok(()))
+    }
+
+    fn deselect(&mut self) -> Result {
+        self.perform(Transaction::Deselect).and(Ok(()))
+    }
+
+    fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        if !self.is_done() {
+            return Err(Error::Busy);
+        }
+
+        if let Transaction::Transfer(_, read) = self.perform(Transaction::write(words))? {
+            words.copy_from_slice(&read);
+        }
+
+        Ok(words)
+    }
+
+    fn delay_us(&mut self, us: u32) -> Result {
+        self.perform(Transaction::DelayUs(us)).and(Ok(()))
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        self.perform(Transaction::ClockSpeed(speed)).and(Ok(()))
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        self.perform(Transaction::mode(mode)).and(Ok(()))
+    }
+
+    fn set_word_size(&mut self, bits: u8) -> Result {
+        self.perform(Transaction::WordSize(bits)).and(Ok(()))
+    }
+
+    fn set_bit_order(&mut self, order: FirstBit) -> Result {
+        self.perform(Transaction::BitOrder(order)).and(Ok(()))
+    }
+
+    fn max_lines(&self) -> Lines {
+        self.state.borrow().max_lines
+    }
+
+    fn read_multi_line(
+        &mut self,
+        phases: Phases,
+        instruction: u8,
+        address: &[u8],
+        words: &mut [u8],
+    ) -> Result {
+        if phases.max_lines() > self.max_lines() {
+            return Err(Error::NotImplemented);
+        }
+
+        let actual = Transaction::multi_line_read(phases, instruction, address, words);
+
+        if let Transaction::MultiLineRead(_, _, read) = self.perform(actual)? {
+            words.copy_from_slice(&read);
+        }
+
+        Ok(())
+    }
+}
+
+impl Transfer<u8> for MockSpi {
+    impl_cs_transfer_common!();
+}
+
+impl BackgroundTransfer for MockSpi {
+    fn raw_start_transfer(&mut self, words: &'static mut [u8]) -> Result {
+        self.raw_transfer(words)?;
+        self.state.borrow_mut().background = Some(words);
+        Ok(())
+    }
+
+    fn is_done(&self) -> bool {
+        self.state.borrow().background.is_none()
+    }
+
+    fn raw_finish(&mut self) -> Result<&'static mut [u8]> {
+        self.state
+            .borrow_mut()
+            .background
+            .take()
+            .ok_or(Error::Transfer)
+    }
+}
+
+impl embedded_hal_1::spi::ErrorType for MockSpi {
+    type Error = Error;
+}
+
+impl embedded_hal_1::spi::SpiDevice for MockSpi {
+    impl_hal1_common!();
+}
+
+impl ChipSelect for MockSpi {}
+impl ClockSpeed for MockSpi {}
+impl SpiMode for MockSpi {}
+impl WordSize for MockSpi {}
+impl BitOrder for MockSpi {}
+impl MultiLine for MockSpi {}
@@ -0,0 +1,293 @@
+use crate::{
+    BitOrder, ChipSelect, ClockSpeed, Error, ErrorKind, FirstBit, Lines, MultiLine, Operation,
+    Phases, Result, SpiDevice, SpiMode, Transfer, WordSize,
+};
+use embedded_hal::{blocking::delay::DelayUs, spi::Mode};
+
+/// When and how often [`Retry`] repeats a failed operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The most attempts after the first.
+    pub retries: u8,
+    /// The wait before the first retry, doubled before each further one.
+    pub backoff_us: u32,
+    /// The longest wait between attempts.
+    pub max_backoff_us: u32,
+    /// Give up with [`Error::Timeout`] rather than retry past this long after
+    /// the first attempt started.
+    pub deadline_us: Option<u64>,
+    /// The kinds of error worth retrying.
+    pub transient: &'static [ErrorKind],
+}
+
+impl RetryPolicy {
+    pub fn is_transient(&self, err: &Error) -> bool {
+        self.transient.contains(&err.kind())
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            backoff_us: 100,
+            max_backoff_us: 10_000,
+            deadline_us: None,
+            transient: &[ErrorKind::Transfer],
+        }
+    }
+}
+
+/// Repeats the failed operations of an [`SpiDevice`] according to a
+/// [`RetryPolicy`], waiting with the delay provider in between.
+///
+/// The clock returns the current time in microseconds and is only used for
+/// deadlines. Use `|| 0` if there is none.
+///
+/// Transfers are repeated with the bytes originally sent, which are saved in
+/// a buffer of `N` bytes, so longer transfers are attempted only once. Raw
+/// transfers happen within a frame selected by the caller and are never
+/// repeated, nor are transactions containing [`Operation::Transfer`], whose
+/// bytes are overwritten by the ones read. These errors are returned as they
+/// are, even when the policy would treat them as transient.
+///
+/// A repeat sends the whole transfer or transaction again, including any
+/// instruction the chip already acted on before the error was noticed. For
+/// instructions which are not idempotent, such as a flash page program or an
+/// erase following a write enable, only list an error as
+/// [`transient`](RetryPolicy::transient) if sending them twice is harmless.
+///
+/// [`delay_us`](SpiDevice::delay_us) waits with the delay provider, so the
+/// wrapped transport does not need one of its own.
+pub struct Retry<S: SpiDevice, D: DelayUs<u32>, C: FnMut() -> u64, const N: usize = 64> {
+    spi: S,
+    delay: D,
+    clock: C,
+    policy: RetryPolicy,
+    retried: usize,
+}
+
+impl<S: SpiDevice, D: DelayUs<u32>, C: FnMut() -> u64, const N: usize> Retry<S, D, C, N> {
+    /// Transfers longer than `N` bytes are attempted only once, so `N` must
+    /// be at least the longest transfer which should be retried. A flash
+    /// device with a `Buffer<L>` sends transfers of up to `L` bytes.
+    pub fn new(spi: S, delay: D, clock: C) -> Self {
+        Self {
+            spi,
+            delay,
+            clock,
+            policy: RetryPolicy::default(),
+            retried: 0,
+        }
+    }
+
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    pub fn policy_mut(&mut self) -> &mut RetryPolicy {
+        &mut self.policy
+    }
+
+    /// The longest transfer which is retried, `N`.
+    pub const fn max_retried_len(&self) -> usize {
+        N
+    }
+
+    /// The number of retries made so far.
+    pub fn retried(&self) -> usize {
+        self.retried
+    }
+
+    pub fn into_inner(self) -> (S, D) {
+        (self.spi, self.delay)
+    }
+
+    fn retry<T>(&mut self, mut attempt: impl FnMut(&mut S) -> Result<T>) -> Result<T> {
+        let start = (self.clock)();
+        let mut backoff = self.policy.backoff_us.min(self.policy.max_backoff_us);
+        let mut retries = 0;
+
+        loop {
+            match attempt(&mut self.spi) {
+                Err(err) if retries < self.policy.retries && self.policy.is_transient(&err) => {
+                    if let Some(deadline) = self.policy.deadline_us {
+                        let elapsed = (self.clock)().wrapping_sub(start);
+
+                        if elapsed + backoff as u64 > deadline {
+                            return Err(Error::Timeout);
+                        }
+                    }
+
+                    self.delay.delay_us(backoff);
+                    backoff = backoff.saturating_mul(2).min(self.policy.max_backoff_us);
+                    retries += 1;
+                    self.retried += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<S: SpiDevice, D: DelayUs<u32>, C: FnMut() -> u64, const N: usize> Transfer<u8>
+    for Retry<S, D, C, N>
+{
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        if words.len() > N {
+            return self.spi.transfer(words);
+        }
+
+        let mut sent = [0; N];
+        let sent = &mut sent[..words.len()];
+        sent.copy_from_slice(words);
+
+        self.retry(|spi| {
+            words.copy_from_slice(sent);
+            spi.transfer(words).and(Ok(()))
+        })?;
+
+        Ok(words)
+    }
+}
+
+impl<S: SpiDevice, D: DelayUs<u32>, C: FnMut() -> u64, const N: usize> SpiDevice
+    for Retry<S, D, C, N>
+{
+    fn is_chip_select(&self) -> bool {
+        self.spi.is_chip_select()
+    }
+
+    fn is_clock_speed(&self) -> bool {
+        self.spi.is_clock_speed()
+    }
+
+    fn is_mode(&self) -> bool {
+        self.spi.is_mode()
+    }
+
+    fn is_word_size(&self) -> bool {
+        self.spi.is_word_size()
+    }
+
+    fn is_bit_order(&self) -> bool {
+        self.spi.is_bit_order()
+    }
+
+    fn select(&mut self) -> Result {
+        self.retry(|spi| spi.select())
+    }
+
+    fn deselect(&mut self) -> Result {
+        self.retry(|spi| spi.deselect())
+    }
+
+    fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.spi.raw_transfer(words)
+    }
+
+    fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result {
+        if operations
+            .iter()
+            .any(|operation| matches!(operation, Operation::Transfer(_)))
+        {
+            return self.spi.transaction(operations);
+        }
+
+        self.retry(|spi| spi.transaction(operations))
+    }
+
+    fn delay_us(&mut self, us: u32) -> Result {
+        self.delay.delay_us(us);
+        Ok(())
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        self.retry(|spi| spi.set_clock_speed(speed))
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        self.retry(|spi| spi.set_mode(mode))
+    }
+
+    fn set_word_size(&mut self, bits: u8) -> Result {
+        self.retry(|spi| spi.set_word_size(bits))
+    }
+
+    fn set_bit_order(&mut self, order: FirstBit) -> Result {
+        self.retry(|spi| spi.set_bit_order(order))
+    }
+
+    fn max_lines(&self) -> Lines {
+        self.spi.max_lines()
+    }
+
+    fn read_multi_line(
+        &mut self,
+        phases: Phases,
+        instruction: u8,
+        address: &[u8],
+        words: &mut [u8],
+    ) -> Result {
+        self.retry(|spi| spi.read_multi_line(phases, instruction, address, words))
+    }
+
+    fn annotate(&mut self, note: &'static str) {
+        self.spi.annotate(note)
+    }
+}
+
+impl<S, D, C, const N: usize> ChipSelect for Retry<S, D, C, N>
+where
+    S: ChipSelect,
+    D: DelayUs<u32>,
+    C: FnMut() -> u64,
+{
+}
+
+impl<S, D, C, const N: usize> ClockSpeed for Retry<S, D, C, N>
+where
+    S: ClockSpeed,
+    D: DelayUs<u32>,
+    C: FnMut() -> u64,
+{
+}
+
+impl<S, D, C, const N: usize> SpiMode for Retry<S, D, C, N>
+where
+    S: SpiMode,
+    D: DelayUs<u32>,
+    C: FnMut() -> u64,
+{
+}
+
+impl<S, D, C, const N: usize> WordSize for Retry<S, D, C, N>
+where
+    S: WordSize,
+    D: DelayUs<u32>,
+    C: FnMut() -> u64,
+{
+}
+
+impl<S, D, C, const N: usize> BitOrder for Retry<S, D, C, N>
+where
+    S: BitOrder,
+    D: DelayUs<u32>,
+    C: FnMut() -> u64,
+{
+}
+
+impl<S, D, C, const N: usize> MultiLine for Retry<S, D, C, N>
+where
+    S: MultiLine,
+    D: DelayUs<u32>,
+    C: FnMut() -> u64,
+{
+}
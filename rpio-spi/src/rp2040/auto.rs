@@ -0,0 +1,67 @@
+use super::super::{Error, Result};
+use super::regs::{self, SpiRegisters};
+use crate::{ClockSpeed, SpiDevice, SpiMode, Transfer, WordSize};
+use embedded_hal::spi::Mode;
+use embedded_time::rate::{Extensions, Hertz};
+use rp2040_hal::spi::{Enabled, Spi as Rp2040Spi};
+
+pub struct Spi<D: SpiRegisters> {
+    spi: Rp2040Spi<Enabled, D, 8>,
+    peripheral_freq: Hertz<u32>,
+}
+
+impl<D: SpiRegisters> Spi<D> {
+    pub fn new(spi: Rp2040Spi<Enabled, D, 8>, peripheral_freq: Hertz<u32>) -> Self {
+        Self {
+            spi,
+            peripheral_freq,
+        }
+    }
+}
+
+impl<D: SpiRegisters> Transfer<u8> for Spi<D> {
+    impl_auto_transfer_common!();
+}
+
+impl<D: SpiRegisters> Transfer<u16> for Spi<D> {
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u16]) -> Result<&'w [u16]> {
+        regs::transfer16::<D>(words);
+        Ok(words)
+    }
+}
+
+impl<D: SpiRegisters> SpiDevice for Spi<D> {
+    impl_auto_raw_common!();
+
+    fn is_clock_speed(&self) -> bool {
+        true
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        self.spi.set_baudrate(self.peripheral_freq, speed.Hz());
+        Ok(())
+    }
+
+    fn is_mode(&self) -> bool {
+        true
+    }
+
+    fn is_word_size(&self) -> bool {
+        true
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        regs::set_mode::<D>(mode);
+        Ok(())
+    }
+
+    fn set_word_size(&mut self, bits: u8) -> Result {
+        regs::set_word_size::<D>(bits)
+    }
+}
+
+impl<D: SpiRegisters> ClockSpeed for Spi<D> {}
+impl<D: SpiRegisters> SpiMode for Spi<D> {}
+impl<D: SpiRegisters> WordSize for Spi<D> {}
@@ -1,19 +1,23 @@
-use super::cs;
-use crate::Spi as EmbeddedSpi;
-use embedded_hal::spi::Mode;
-use embedded_time::rate::Hertz;
+use super::dma::{self, DmaChannel, DmaSpiDevice};
+use super::pio::{self, Pio, PioPins};
+use super::regs::SpiRegisters;
+use super::{auto, cs, slave};
+use crate::{ChipSelectConfig, Error, NoDelay, Spi as EmbeddedSpi};
+use embedded_hal::{blocking::delay::DelayUs, spi::Mode};
+use embedded_time::rate::{Extensions, Hertz};
 use rp2040_hal::{
-    gpio::{Pin, PinId, PinMode, PushPullOutput, ValidPinMode},
+    gpio::{FunctionSpi, Pin, PinId, PinMode, PushPullOutput, ValidPinMode},
     pac::RESETS,
-    spi::{Enabled, Spi, SpiDevice},
+    pio::{UninitStateMachine, ValidStateMachine, PIO},
+    spi::{Enabled, Spi},
 };
 
 impl EmbeddedSpi {
     pub fn new_rp2040<
-        D: SpiDevice,
+        D: SpiRegisters,
         CS: PinId,
         M: PinMode + ValidPinMode<CS>,
-        P: Into<Hertz>,
+        P: Into<Hertz> + Copy,
         B: Into<Hertz> + Copy,
     >(
         device: D,
@@ -27,29 +31,92 @@ impl EmbeddedSpi {
 
         Rp2040Builder {
             spi,
-            baudrate: baudrate.into(),
+            peripheral_freq: peri_frequency.into(),
         }
         .with_cs(chip_select.into_push_pull_output())
     }
 
+    /// Construct a transport driven by a PIO state machine, so that any pins
+    /// can be used. The pins must already be in the function of the PIO
+    /// block, e.g. `FunctionPio0`.
+    ///
+    /// Two programs of up to three instructions are installed, and the clock
+    /// speed can be at most a quarter of the system clock.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_rp2040_pio<
+        SM: ValidStateMachine,
+        SCK: PinId,
+        MOSI: PinId,
+        MISO: PinId,
+        M1: PinMode + ValidPinMode<SCK>,
+        M2: PinMode + ValidPinMode<MOSI>,
+        M3: PinMode + ValidPinMode<MISO>,
+    >(
+        pio: &mut PIO<SM::PIO>,
+        sm: UninitStateMachine<SM>,
+        _sck: Pin<SCK, M1>,
+        _mosi: Pin<MOSI, M2>,
+        _miso: Pin<MISO, M3>,
+        system_freq: impl Into<Hertz>,
+        baudrate: impl Into<Hertz>,
+        mode: &Mode,
+    ) -> Rp2040PioBuilder<SM> {
+        let pins = PioPins {
+            sck: SCK::DYN.num,
+            mosi: MOSI::DYN.num,
+            miso: MISO::DYN.num,
+        };
+
+        Rp2040PioBuilder {
+            pio: Pio::new(pio, sm, pins, system_freq.into(), baudrate.into(), *mode),
+        }
+    }
+
+    /// Construct an SPI slave, framed by the master driving `chip_select`.
+    /// The clock, data and chip select pins must be in [`FunctionSpi`].
+    ///
+    /// The peripheral clock frequency is used to initialize the peripheral;
+    /// the SPI clock is driven by the master.
+    pub fn new_rp2040_slave<D: SpiRegisters, CS: PinId, P: Into<Hertz> + Copy>(
+        device: D,
+        chip_select: &Pin<CS, FunctionSpi>,
+        resets: &mut RESETS,
+        peri_frequency: P,
+        mode: &Mode,
+    ) -> Rp2040SlaveBuilder<D> {
+        let peripheral_freq: Hertz = peri_frequency.into();
+        let spi: Spi<_, _, 8> =
+            Spi::new(device).init(resets, peripheral_freq, (peripheral_freq.0 / 12).Hz(), mode);
+
+        Rp2040SlaveBuilder {
+            spi,
+            cs: CS::DYN.num,
+        }
+    }
+
     /// Construct a transport from an [`rp2040::spi::Spi`](Spi).
-    pub fn from_rp2040<D: SpiDevice>(
+    ///
+    /// `peripheral_freq` is the frequency of the peripheral clock, not the
+    /// SPI clock speed, which was set when `spi` was initialized. It is the
+    /// base the clock divider is worked out from in
+    /// [`set_clock_speed`](crate::SpiDevice::set_clock_speed).
+    pub fn from_rp2040<D: SpiRegisters>(
         spi: Spi<Enabled, D, 8>,
-        baudrate: impl Into<Hertz>,
+        peripheral_freq: impl Into<Hertz>,
     ) -> Rp2040Builder<D> {
         Rp2040Builder {
             spi,
-            baudrate: baudrate.into(),
+            peripheral_freq: peripheral_freq.into(),
         }
     }
 }
 
-pub struct Rp2040Builder<D: SpiDevice> {
+pub struct Rp2040Builder<D: SpiRegisters> {
     spi: Spi<Enabled, D, 8>,
-    baudrate: Hertz,
+    peripheral_freq: Hertz,
 }
 
-impl<D: SpiDevice> Rp2040Builder<D> {
+impl<D: SpiRegisters> Rp2040Builder<D> {
     /// Use the provided [`rp2040::gpio::Pin`](Pin) for chip select. It must
     /// be configured as a [`PushPullOutput`].
     pub fn with_cs<CS: PinId, M: PinMode + ValidPinMode<CS>>(
@@ -58,28 +125,227 @@ impl<D: SpiDevice> Rp2040Builder<D> {
     ) -> Rp2040ChipSelectBuilder<D, CS> {
         Rp2040ChipSelectBuilder {
             spi: self.spi,
-            baudrate: self.baudrate,
+            peripheral_freq: self.peripheral_freq,
             cs: pin.into_push_pull_output(),
+            cs_config: ChipSelectConfig::default(),
+            delay: None,
         }
     }
+
+    /// Initialize the transport.
+    ///
+    /// Chip select must be handled by the SPI peripheral, or by the devices
+    /// on a [`SharedBus`](crate::SharedBus).
+    pub fn init(self) -> auto::Spi<D> {
+        auto::Spi::new(self.spi, self.peripheral_freq)
+    }
 }
 
-pub struct Rp2040ChipSelectBuilder<D: SpiDevice, P: PinId> {
+impl<D: DmaSpiDevice> Rp2040Builder<D> {
+    /// Drive transfers with the provided DMA channels, one for each
+    /// direction.
+    pub fn with_dma(self, tx: DmaChannel, rx: DmaChannel) -> Rp2040DmaBuilder<D> {
+        Rp2040DmaBuilder {
+            spi: self.spi,
+            peripheral_freq: self.peripheral_freq,
+            tx,
+            rx,
+        }
+    }
+}
+
+pub struct Rp2040ChipSelectBuilder<D: SpiRegisters, P: PinId, DL: DelayUs<u32> = NoDelay> {
     spi: Spi<Enabled, D, 8>,
-    baudrate: Hertz,
+    peripheral_freq: Hertz,
     cs: Pin<P, PushPullOutput>,
-    //active_high: bool,
+    cs_config: ChipSelectConfig,
+    delay: Option<DL>,
 }
 
-impl<D: SpiDevice, P: PinId> Rp2040ChipSelectBuilder<D, P> {
-    /// Use the provided polarity. Defaults to [IdleHigh](Polarity::IdleHigh).
-    // pub fn with_active_high(mut self) -> Self {
-    //     self.active_high = true;
-    //     self
-    // }
+impl<D: SpiRegisters, P: PinId, DL: DelayUs<u32>> Rp2040ChipSelectBuilder<D, P, DL> {
+    impl_cs_builder_common!();
+
+    /// Use the provided delay, such as a `cortex_m::delay::Delay`, for chip
+    /// select timing and [`delay_us`](crate::SpiDevice::delay_us).
+    pub fn with_delay<T: DelayUs<u32>>(self, delay: T) -> Rp2040ChipSelectBuilder<D, P, T> {
+        Rp2040ChipSelectBuilder {
+            spi: self.spi,
+            peripheral_freq: self.peripheral_freq,
+            cs: self.cs,
+            cs_config: self.cs_config,
+            delay: Some(delay),
+        }
+    }
 
     /// Initialize the transport.
-    pub fn init(self) -> cs::Spi<D, P> {
-        cs::Spi::new(self.spi, self.baudrate, self.cs)
+    pub fn init(self) -> cs::Spi<D, P, DL> {
+        cs::Spi::new(
+            self.spi,
+            self.peripheral_freq,
+            self.cs,
+            self.cs_config,
+            self.delay,
+        )
+    }
+}
+
+impl<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32>> Rp2040ChipSelectBuilder<D, P, DL> {
+    /// Drive transfers with the provided DMA channels, one for each
+    /// direction.
+    pub fn with_dma(self, tx: DmaChannel, rx: DmaChannel) -> Rp2040DmaChipSelectBuilder<D, P, DL> {
+        Rp2040DmaChipSelectBuilder {
+            spi: self.spi,
+            peripheral_freq: self.peripheral_freq,
+            tx,
+            rx,
+            cs: self.cs,
+            cs_config: self.cs_config,
+            delay: self.delay,
+        }
+    }
+}
+
+pub struct Rp2040DmaBuilder<D: DmaSpiDevice> {
+    spi: Spi<Enabled, D, 8>,
+    peripheral_freq: Hertz,
+    tx: DmaChannel,
+    rx: DmaChannel,
+}
+
+impl<D: DmaSpiDevice> Rp2040DmaBuilder<D> {
+    /// Use the provided [`rp2040::gpio::Pin`](Pin) for chip select.
+    pub fn with_cs<CS: PinId, M: PinMode + ValidPinMode<CS>>(
+        self,
+        pin: Pin<CS, M>,
+    ) -> Rp2040DmaChipSelectBuilder<D, CS> {
+        Rp2040DmaChipSelectBuilder {
+            spi: self.spi,
+            peripheral_freq: self.peripheral_freq,
+            tx: self.tx,
+            rx: self.rx,
+            cs: pin.into_push_pull_output(),
+            cs_config: ChipSelectConfig::default(),
+            delay: None,
+        }
+    }
+
+    /// Initialize the transport.
+    pub fn init(self) -> dma::auto::Spi<D> {
+        dma::auto::Spi::new(self.spi, self.peripheral_freq, self.tx, self.rx)
+    }
+}
+
+pub struct Rp2040DmaChipSelectBuilder<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32> = NoDelay> {
+    spi: Spi<Enabled, D, 8>,
+    peripheral_freq: Hertz,
+    tx: DmaChannel,
+    rx: DmaChannel,
+    cs: Pin<P, PushPullOutput>,
+    cs_config: ChipSelectConfig,
+    delay: Option<DL>,
+}
+
+impl<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32>> Rp2040DmaChipSelectBuilder<D, P, DL> {
+    impl_cs_builder_common!();
+
+    /// Use the provided delay for chip select timing and
+    /// [`delay_us`](crate::SpiDevice::delay_us).
+    pub fn with_delay<T: DelayUs<u32>>(self, delay: T) -> Rp2040DmaChipSelectBuilder<D, P, T> {
+        Rp2040DmaChipSelectBuilder {
+            spi: self.spi,
+            peripheral_freq: self.peripheral_freq,
+            tx: self.tx,
+            rx: self.rx,
+            cs: self.cs,
+            cs_config: self.cs_config,
+            delay: Some(delay),
+        }
+    }
+
+    /// Initialize the transport.
+    pub fn init(self) -> dma::cs::Spi<D, P, DL> {
+        dma::cs::Spi::new(
+            self.spi,
+            self.peripheral_freq,
+            self.tx,
+            self.rx,
+            self.cs,
+            self.cs_config,
+            self.delay,
+        )
+    }
+}
+
+pub struct Rp2040PioBuilder<SM: ValidStateMachine> {
+    pio: Result<Pio<SM>, Error>,
+}
+
+impl<SM: ValidStateMachine> Rp2040PioBuilder<SM> {
+    /// Use the provided [`rp2040::gpio::Pin`](Pin) for chip select.
+    pub fn with_cs<CS: PinId, M: PinMode + ValidPinMode<CS>>(
+        self,
+        pin: Pin<CS, M>,
+    ) -> Rp2040PioChipSelectBuilder<SM, CS> {
+        Rp2040PioChipSelectBuilder {
+            pio: self.pio,
+            cs: pin.into_push_pull_output(),
+            cs_config: ChipSelectConfig::default(),
+            delay: None,
+        }
+    }
+
+    /// Initialize the transport.
+    ///
+    /// Chip select must be handled by the devices on a
+    /// [`SharedBus`](crate::SharedBus).
+    pub fn init(self) -> Result<pio::auto::Spi<SM>, Error> {
+        Ok(pio::auto::Spi::new(self.pio?))
+    }
+}
+
+pub struct Rp2040PioChipSelectBuilder<SM: ValidStateMachine, P: PinId, DL: DelayUs<u32> = NoDelay> {
+    pio: Result<Pio<SM>, Error>,
+    cs: Pin<P, PushPullOutput>,
+    cs_config: ChipSelectConfig,
+    delay: Option<DL>,
+}
+
+impl<SM: ValidStateMachine, P: PinId, DL: DelayUs<u32>> Rp2040PioChipSelectBuilder<SM, P, DL> {
+    impl_cs_builder_common!();
+
+    /// Use the provided delay for chip select timing and
+    /// [`delay_us`](crate::SpiDevice::delay_us).
+    pub fn with_delay<T: DelayUs<u32>>(self, delay: T) -> Rp2040PioChipSelectBuilder<SM, P, T> {
+        Rp2040PioChipSelectBuilder {
+            pio: self.pio,
+            cs: self.cs,
+            cs_config: self.cs_config,
+            delay: Some(delay),
+        }
+    }
+
+    /// Initialize the transport.
+    ///
+    /// Fails if the PIO block had no room for the programs.
+    pub fn init(self) -> Result<pio::cs::Spi<SM, P, DL>, Error> {
+        Ok(pio::cs::Spi::new(
+            self.pio?,
+            self.cs,
+            self.cs_config,
+            self.delay,
+        ))
+    }
+}
+
+pub struct Rp2040SlaveBuilder<D: SpiRegisters> {
+    spi: Spi<Enabled, D, 8>,
+    cs: u8,
+}
+
+impl<D: SpiRegisters> Rp2040SlaveBuilder<D> {
+    /// Initialize the slave, with room for `N` received bytes and an `N` byte
+    /// response.
+    pub fn init<const N: usize>(self) -> slave::Slave<D, N> {
+        slave::Slave::new(self.spi, self.cs)
     }
 }
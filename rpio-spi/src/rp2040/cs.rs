@@ -1,27 +1,38 @@
-use super::super::{Error, Result};
-use crate::{ChipSelect, ClockSpeed, OutputPin, SpiDevice, Transfer};
+use super::super::{Error, ErrorKind, Result};
+use super::regs::{self, SpiRegisters};
+use crate::{
+    ChipSelect, ChipSelectConfig, ClockSpeed, NoDelay, OutputPin, SpiDevice, SpiMode, Transfer,
+    WordSize,
+};
+use embedded_hal::{blocking::delay::DelayUs, spi::Mode};
 use embedded_time::rate::{Extensions, Hertz};
 use rp2040_hal::{
     gpio::{Pin, PinId, PushPullOutput},
-    spi::{Enabled, Spi as Rp2040Spi, SpiDevice as Rp2040SpiDevice},
+    spi::{Enabled, Spi as Rp2040Spi},
 };
 
-pub struct Spi<D: Rp2040SpiDevice, P: PinId> {
+pub struct Spi<D: SpiRegisters, P: PinId, DL: DelayUs<u32> = NoDelay> {
     spi: Rp2040Spi<Enabled, D, 8>,
     peripheral_freq: Hertz<u32>,
     cs: Pin<P, PushPullOutput>,
+    cs_config: ChipSelectConfig,
+    delay: Option<DL>,
 }
 
-impl<D: Rp2040SpiDevice, P: PinId> Spi<D, P> {
+impl<D: SpiRegisters, P: PinId, DL: DelayUs<u32>> Spi<D, P, DL> {
     pub fn new(
         spi: Rp2040Spi<Enabled, D, 8>,
         peripheral_freq: Hertz<u32>,
         cs: Pin<P, PushPullOutput>,
+        cs_config: ChipSelectConfig,
+        delay: Option<DL>,
     ) -> Self {
         let mut transport = Self {
             spi,
             peripheral_freq,
             cs,
+            cs_config,
+            delay,
         };
 
         transport.deselect().ok();
@@ -29,11 +40,14 @@ impl<D: Rp2040SpiDevice, P: PinId> Spi<D, P> {
     }
 }
 
-impl<D: Rp2040SpiDevice, P: PinId> SpiDevice for Spi<D, P> {
+impl<D: SpiRegisters, P: PinId, DL: DelayUs<u32>> SpiDevice for Spi<D, P, DL> {
     impl_cs_common!();
+    impl_delay_common!();
 
     fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
-        self.spi.transfer(words).or(Err(Error::Transfer))
+        self.spi
+            .transfer(words)
+            .map_err(|err| Error::with_hal(ErrorKind::Transfer, err))
     }
 
     fn is_clock_speed(&self) -> bool {
@@ -44,11 +58,48 @@ impl<D: Rp2040SpiDevice, P: PinId> SpiDevice for Spi<D, P> {
         self.spi.set_baudrate(self.peripheral_freq, speed.Hz());
         Ok(())
     }
+
+    fn is_mode(&self) -> bool {
+        true
+    }
+
+    fn is_word_size(&self) -> bool {
+        true
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        regs::set_mode::<D>(mode);
+        Ok(())
+    }
+
+    fn set_word_size(&mut self, bits: u8) -> Result {
+        regs::set_word_size::<D>(bits)
+    }
 }
 
-impl<D: Rp2040SpiDevice, P: PinId> Transfer<u8> for Spi<D, P> {
+impl<D: SpiRegisters, P: PinId, DL: DelayUs<u32>> Transfer<u8> for Spi<D, P, DL> {
     impl_cs_transfer_common!();
 }
 
-impl<D: Rp2040SpiDevice, P: PinId> ChipSelect for Spi<D, P> {}
-impl<D: Rp2040SpiDevice, P: PinId> ClockSpeed for Spi<D, P> {}
+impl<D: SpiRegisters, P: PinId, DL: DelayUs<u32>> Transfer<u16> for Spi<D, P, DL> {
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u16]) -> Result<&'w [u16]> {
+        self.select()?;
+        regs::transfer16::<D>(words);
+        self.deselect().and(Ok(words))
+    }
+}
+
+impl<D: SpiRegisters, P: PinId, DL: DelayUs<u32>> embedded_hal_1::spi::ErrorType for Spi<D, P, DL> {
+    type Error = Error;
+}
+
+impl<D: SpiRegisters, P: PinId, DL: DelayUs<u32>> embedded_hal_1::spi::SpiDevice for Spi<D, P, DL> {
+    impl_hal1_common!();
+}
+
+impl<D: SpiRegisters, P: PinId, DL: DelayUs<u32>> ChipSelect for Spi<D, P, DL> {}
+impl<D: SpiRegisters, P: PinId, DL: DelayUs<u32>> ClockSpeed for Spi<D, P, DL> {}
+impl<D: SpiRegisters, P: PinId, DL: DelayUs<u32>> SpiMode for Spi<D, P, DL> {}
+impl<D: SpiRegisters, P: PinId, DL: DelayUs<u32>> WordSize for Spi<D, P, DL> {}
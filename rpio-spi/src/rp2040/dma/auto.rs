@@ -0,0 +1,78 @@
+use super::super::super::{Error, Result};
+use super::{Dma, DmaChannel, DmaSpiDevice};
+use crate::{BackgroundTransfer, ClockSpeed, SpiDevice, SpiMode, Transfer};
+use embedded_hal::spi::Mode;
+use embedded_time::rate::Hertz;
+use rp2040_hal::spi::{Enabled, Spi as Rp2040Spi};
+
+pub struct Spi<D: DmaSpiDevice> {
+    dma: Dma<D>,
+}
+
+impl<D: DmaSpiDevice> Spi<D> {
+    pub fn new(
+        spi: Rp2040Spi<Enabled, D, 8>,
+        peripheral_freq: Hertz<u32>,
+        tx: DmaChannel,
+        rx: DmaChannel,
+    ) -> Self {
+        Self {
+            dma: Dma::new(spi, peripheral_freq, tx, rx),
+        }
+    }
+
+    /// Raise `DMA_IRQ_0` when a background transfer finishes. The interrupt
+    /// is cleared by [`finish`](BackgroundTransfer::finish).
+    pub fn listen(&mut self) {
+        self.dma.listen();
+    }
+
+    pub fn unlisten(&mut self) {
+        self.dma.unlisten();
+    }
+}
+
+impl<D: DmaSpiDevice> Transfer<u8> for Spi<D> {
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.dma.transfer(words)
+    }
+}
+
+impl<D: DmaSpiDevice> SpiDevice for Spi<D> {
+    impl_auto_raw_common!();
+
+    fn is_clock_speed(&self) -> bool {
+        true
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        self.dma.set_clock_speed(speed)
+    }
+
+    fn is_mode(&self) -> bool {
+        true
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        self.dma.set_mode(mode)
+    }
+}
+
+impl<D: DmaSpiDevice> BackgroundTransfer for Spi<D> {
+    fn raw_start_transfer(&mut self, words: &'static mut [u8]) -> Result {
+        self.dma.start_transfer(words)
+    }
+
+    fn is_done(&self) -> bool {
+        self.dma.is_done()
+    }
+
+    fn raw_finish(&mut self) -> Result<&'static mut [u8]> {
+        self.dma.finish()
+    }
+}
+
+impl<D: DmaSpiDevice> ClockSpeed for Spi<D> {}
+impl<D: DmaSpiDevice> SpiMode for Spi<D> {}
@@ -0,0 +1,106 @@
+use super::super::super::{Error, Result};
+use super::{Dma, DmaChannel, DmaSpiDevice};
+use crate::{
+    BackgroundTransfer, ChipSelect, ChipSelectConfig, ClockSpeed, NoDelay, OutputPin, SpiDevice,
+    SpiMode, Transfer,
+};
+use embedded_hal::{blocking::delay::DelayUs, spi::Mode};
+use embedded_time::rate::Hertz;
+use rp2040_hal::{
+    gpio::{Pin, PinId, PushPullOutput},
+    spi::{Enabled, Spi as Rp2040Spi},
+};
+
+pub struct Spi<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32> = NoDelay> {
+    dma: Dma<D>,
+    cs: Pin<P, PushPullOutput>,
+    cs_config: ChipSelectConfig,
+    delay: Option<DL>,
+}
+
+impl<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32>> Spi<D, P, DL> {
+    pub fn new(
+        spi: Rp2040Spi<Enabled, D, 8>,
+        peripheral_freq: Hertz<u32>,
+        tx: DmaChannel,
+        rx: DmaChannel,
+        cs: Pin<P, PushPullOutput>,
+        cs_config: ChipSelectConfig,
+        delay: Option<DL>,
+    ) -> Self {
+        let mut transport = Self {
+            dma: Dma::new(spi, peripheral_freq, tx, rx),
+            cs,
+            cs_config,
+            delay,
+        };
+
+        transport.deselect().ok();
+        transport
+    }
+
+    /// Raise `DMA_IRQ_0` when a background transfer finishes. The interrupt
+    /// is cleared by [`finish`](BackgroundTransfer::finish).
+    pub fn listen(&mut self) {
+        self.dma.listen();
+    }
+
+    pub fn unlisten(&mut self) {
+        self.dma.unlisten();
+    }
+}
+
+impl<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32>> SpiDevice for Spi<D, P, DL> {
+    impl_cs_common!();
+    impl_delay_common!();
+
+    fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.dma.transfer(words)
+    }
+
+    fn is_clock_speed(&self) -> bool {
+        true
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        self.dma.set_clock_speed(speed)
+    }
+
+    fn is_mode(&self) -> bool {
+        true
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        self.dma.set_mode(mode)
+    }
+}
+
+impl<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32>> Transfer<u8> for Spi<D, P, DL> {
+    impl_cs_transfer_common!();
+}
+
+impl<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32>> BackgroundTransfer for Spi<D, P, DL> {
+    fn raw_start_transfer(&mut self, words: &'static mut [u8]) -> Result {
+        self.dma.start_transfer(words)
+    }
+
+    fn is_done(&self) -> bool {
+        self.dma.is_done()
+    }
+
+    fn raw_finish(&mut self) -> Result<&'static mut [u8]> {
+        self.dma.finish()
+    }
+}
+
+impl<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32>> embedded_hal_1::spi::ErrorType for Spi<D, P, DL> {
+    type Error = Error;
+}
+
+impl<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32>> embedded_hal_1::spi::SpiDevice for Spi<D, P, DL> {
+    impl_hal1_common!();
+}
+
+impl<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32>> ChipSelect for Spi<D, P, DL> {}
+impl<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32>> ClockSpeed for Spi<D, P, DL> {}
+impl<D: DmaSpiDevice, P: PinId, DL: DelayUs<u32>> SpiMode for Spi<D, P, DL> {}
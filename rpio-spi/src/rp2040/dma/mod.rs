@@ -0,0 +1,231 @@
+pub mod auto;
+pub mod cs;
+
+use super::super::{Error, ErrorKind, Result};
+use super::regs::{self, SpiRegisters};
+use core::sync::atomic::{compiler_fence, Ordering};
+use embedded_hal::{blocking::spi::Transfer, spi::Mode};
+use embedded_time::rate::{Extensions, Hertz};
+use rp2040_hal::{
+    pac::{self, dma::CH, DMA, RESETS},
+    spi::{Enabled, Spi as Rp2040Spi},
+};
+
+const CTRL_EN: u32 = 1 << 0;
+const CTRL_INCR_READ: u32 = 1 << 4;
+const CTRL_INCR_WRITE: u32 = 1 << 5;
+const CTRL_CHAIN_TO: u32 = 11;
+const CTRL_TREQ_SEL: u32 = 15;
+const CTRL_BUSY: u32 = 1 << 24;
+
+/// Transfers shorter than this are not worth setting up the DMA channels for.
+const DMA_THRESHOLD: usize = 16;
+
+/// A channel of the DMA peripheral, created by [`DmaChannel::split`].
+#[derive(Debug)]
+pub struct DmaChannel(u8);
+
+impl DmaChannel {
+    /// Take the DMA peripheral out of reset and split it into its channels.
+    pub fn split(_dma: DMA, resets: &mut RESETS) -> [DmaChannel; 12] {
+        resets.reset.modify(|_, w| w.dma().clear_bit());
+        while resets.reset_done.read().dma().bit_is_clear() {}
+
+        core::array::from_fn(|id| DmaChannel(id as u8))
+    }
+
+    fn regs(&self) -> &'static CH {
+        unsafe { &(*DMA::ptr()).ch[self.0 as usize] }
+    }
+
+    fn mask(&self) -> u32 {
+        1 << self.0
+    }
+
+    /// Configure the channel, chained to itself so that nothing else is
+    /// triggered, and start it.
+    fn start(&self, read: u32, write: u32, len: usize, ctrl: u32) {
+        let ctrl = ctrl | CTRL_EN | (self.0 as u32) << CTRL_CHAIN_TO;
+        let regs = self.regs();
+
+        regs.ch_read_addr.write(|w| unsafe { w.bits(read) });
+        regs.ch_write_addr.write(|w| unsafe { w.bits(write) });
+        regs.ch_trans_count.write(|w| unsafe { w.bits(len as u32) });
+        regs.ch_ctrl_trig.write(|w| unsafe { w.bits(ctrl) });
+    }
+
+    fn is_busy(&self) -> bool {
+        self.regs().ch_ctrl_trig.read().bits() & CTRL_BUSY != 0
+    }
+
+    fn listen(&self) {
+        let dma = unsafe { &*DMA::ptr() };
+        dma.inte0
+            .modify(|r, w| unsafe { w.bits(r.bits() | self.mask()) });
+    }
+
+    fn unlisten(&self) {
+        let dma = unsafe { &*DMA::ptr() };
+        dma.inte0
+            .modify(|r, w| unsafe { w.bits(r.bits() & !self.mask()) });
+    }
+
+    fn clear_interrupt(&self) {
+        let dma = unsafe { &*DMA::ptr() };
+        dma.ints0.write(|w| unsafe { w.bits(self.mask()) });
+    }
+}
+
+/// An SPI peripheral which can be paced by the DMA peripheral.
+pub trait DmaSpiDevice: SpiRegisters {
+    /// The data request signal for the transmit FIFO.
+    const TX_DREQ: u32;
+    /// The data request signal for the receive FIFO.
+    const RX_DREQ: u32;
+}
+
+impl DmaSpiDevice for pac::SPI0 {
+    const TX_DREQ: u32 = 16;
+    const RX_DREQ: u32 = 17;
+}
+
+impl DmaSpiDevice for pac::SPI1 {
+    const TX_DREQ: u32 = 18;
+    const RX_DREQ: u32 = 19;
+}
+
+/// Drives an SPI peripheral with a pair of DMA channels, one feeding the
+/// transmit FIFO and one draining the receive FIFO into the same buffer.
+///
+/// The receive channel finishes last, so it is the one polled for completion
+/// and used to raise `DMA_IRQ_0`.
+pub struct Dma<D: DmaSpiDevice> {
+    spi: Rp2040Spi<Enabled, D, 8>,
+    peripheral_freq: Hertz<u32>,
+    tx: DmaChannel,
+    rx: DmaChannel,
+    words: Option<&'static mut [u8]>,
+}
+
+impl<D: DmaSpiDevice> Dma<D> {
+    pub fn new(
+        spi: Rp2040Spi<Enabled, D, 8>,
+        peripheral_freq: Hertz<u32>,
+        tx: DmaChannel,
+        rx: DmaChannel,
+    ) -> Self {
+        D::regs()
+            .sspdmacr
+            .write(|w| w.txdmae().set_bit().rxdmae().set_bit());
+
+        Self {
+            spi,
+            peripheral_freq,
+            tx,
+            rx,
+            words: None,
+        }
+    }
+
+    fn start(&mut self, words: *mut u8, len: usize) {
+        if len == 0 {
+            return;
+        }
+
+        let data = &D::regs().sspdr as *const _ as u32;
+        compiler_fence(Ordering::SeqCst);
+
+        // Start draining before feeding, so no byte is missed
+        self.rx.start(
+            data,
+            words as u32,
+            len,
+            CTRL_INCR_WRITE | D::RX_DREQ << CTRL_TREQ_SEL,
+        );
+        self.tx.start(
+            words as u32,
+            data,
+            len,
+            CTRL_INCR_READ | D::TX_DREQ << CTRL_TREQ_SEL,
+        );
+    }
+
+    fn wait(&self) {
+        while self.rx.is_busy() {}
+        compiler_fence(Ordering::SeqCst);
+    }
+
+    /// Exchange bytes in place, waiting for the transfer to finish.
+    pub fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        if self.words.is_some() {
+            return Err(Error::Busy);
+        }
+
+        if words.len() < DMA_THRESHOLD {
+            return self
+                .spi
+                .transfer(words)
+                .map_err(|err| Error::with_hal(ErrorKind::Transfer, err));
+        }
+
+        self.start(words.as_mut_ptr(), words.len());
+        self.wait();
+        Ok(words)
+    }
+
+    pub fn start_transfer(&mut self, words: &'static mut [u8]) -> Result {
+        if self.words.is_some() {
+            return Err(Error::Busy);
+        }
+
+        self.start(words.as_mut_ptr(), words.len());
+        self.words = Some(words);
+        Ok(())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.words.is_none() || !self.rx.is_busy()
+    }
+
+    pub fn finish(&mut self) -> Result<&'static mut [u8]> {
+        let words = self.words.take().ok_or(Error::Transfer)?;
+
+        self.wait();
+        self.rx.clear_interrupt();
+        Ok(words)
+    }
+
+    pub fn set_clock_speed(&mut self, speed: u32) -> Result {
+        if self.words.is_some() {
+            return Err(Error::Busy);
+        }
+
+        self.spi.set_baudrate(self.peripheral_freq, speed.Hz());
+        Ok(())
+    }
+
+    pub fn set_mode(&mut self, mode: Mode) -> Result {
+        if self.words.is_some() {
+            return Err(Error::Busy);
+        }
+
+        regs::set_mode::<D>(mode);
+        Ok(())
+    }
+
+    pub fn listen(&mut self) {
+        self.rx.listen();
+    }
+
+    pub fn unlisten(&mut self) {
+        self.rx.unlisten();
+    }
+}
+
+impl<D: DmaSpiDevice> Drop for Dma<D> {
+    fn drop(&mut self) {
+        if self.words.is_some() {
+            self.wait();
+        }
+    }
+}
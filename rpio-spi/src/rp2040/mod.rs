@@ -1,2 +1,9 @@
+mod auto;
 mod build;
 mod cs;
+mod dma;
+mod pio;
+mod regs;
+mod slave;
+
+pub use dma::DmaChannel;
@@ -0,0 +1,46 @@
+use super::super::super::{Error, Result};
+use super::Pio;
+use crate::{ClockSpeed, SpiDevice, SpiMode, Transfer};
+use embedded_hal::spi::Mode;
+use rp2040_hal::pio::ValidStateMachine;
+
+pub struct Spi<SM: ValidStateMachine> {
+    pio: Pio<SM>,
+}
+
+impl<SM: ValidStateMachine> Spi<SM> {
+    pub fn new(pio: Pio<SM>) -> Self {
+        Self { pio }
+    }
+}
+
+impl<SM: ValidStateMachine> Transfer<u8> for Spi<SM> {
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.pio.transfer(words)
+    }
+}
+
+impl<SM: ValidStateMachine> SpiDevice for Spi<SM> {
+    impl_auto_raw_common!();
+
+    fn is_clock_speed(&self) -> bool {
+        true
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        self.pio.set_clock_speed(speed)
+    }
+
+    fn is_mode(&self) -> bool {
+        true
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        self.pio.set_mode(mode)
+    }
+}
+
+impl<SM: ValidStateMachine> ClockSpeed for Spi<SM> {}
+impl<SM: ValidStateMachine> SpiMode for Spi<SM> {}
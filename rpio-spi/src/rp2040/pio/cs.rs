@@ -0,0 +1,81 @@
+use super::super::super::{Error, Result};
+use super::Pio;
+use crate::{
+    ChipSelect, ChipSelectConfig, ClockSpeed, NoDelay, OutputPin, SpiDevice, SpiMode, Transfer,
+};
+use embedded_hal::{blocking::delay::DelayUs, spi::Mode};
+use rp2040_hal::{
+    gpio::{Pin, PinId, PushPullOutput},
+    pio::ValidStateMachine,
+};
+
+pub struct Spi<SM: ValidStateMachine, P: PinId, DL: DelayUs<u32> = NoDelay> {
+    pio: Pio<SM>,
+    cs: Pin<P, PushPullOutput>,
+    cs_config: ChipSelectConfig,
+    delay: Option<DL>,
+}
+
+impl<SM: ValidStateMachine, P: PinId, DL: DelayUs<u32>> Spi<SM, P, DL> {
+    pub fn new(
+        pio: Pio<SM>,
+        cs: Pin<P, PushPullOutput>,
+        cs_config: ChipSelectConfig,
+        delay: Option<DL>,
+    ) -> Self {
+        let mut transport = Self {
+            pio,
+            cs,
+            cs_config,
+            delay,
+        };
+
+        transport.deselect().ok();
+        transport
+    }
+}
+
+impl<SM: ValidStateMachine, P: PinId, DL: DelayUs<u32>> SpiDevice for Spi<SM, P, DL> {
+    impl_cs_common!();
+    impl_delay_common!();
+
+    fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.pio.transfer(words)
+    }
+
+    fn is_clock_speed(&self) -> bool {
+        true
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        self.pio.set_clock_speed(speed)
+    }
+
+    fn is_mode(&self) -> bool {
+        true
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        self.pio.set_mode(mode)
+    }
+}
+
+impl<SM: ValidStateMachine, P: PinId, DL: DelayUs<u32>> Transfer<u8> for Spi<SM, P, DL> {
+    impl_cs_transfer_common!();
+}
+
+impl<SM: ValidStateMachine, P: PinId, DL: DelayUs<u32>> embedded_hal_1::spi::ErrorType
+    for Spi<SM, P, DL>
+{
+    type Error = Error;
+}
+
+impl<SM: ValidStateMachine, P: PinId, DL: DelayUs<u32>> embedded_hal_1::spi::SpiDevice
+    for Spi<SM, P, DL>
+{
+    impl_hal1_common!();
+}
+
+impl<SM: ValidStateMachine, P: PinId, DL: DelayUs<u32>> ChipSelect for Spi<SM, P, DL> {}
+impl<SM: ValidStateMachine, P: PinId, DL: DelayUs<u32>> ClockSpeed for Spi<SM, P, DL> {}
+impl<SM: ValidStateMachine, P: PinId, DL: DelayUs<u32>> SpiMode for Spi<SM, P, DL> {}
@@ -0,0 +1,221 @@
+pub mod auto;
+pub mod cs;
+
+use super::super::{Error, Result};
+use ::pio::{Assembler, InSource, MovDestination, MovOperation, MovSource, OutDestination};
+use embedded_hal::spi::{Mode, Phase, Polarity};
+use embedded_time::rate::Hertz;
+use rp2040_hal::{
+    pac,
+    pio::{
+        InstalledProgram, PIOBuilder, PinDir, Running, Rx, ShiftDirection, StateMachine, Tx,
+        UninitStateMachine, ValidStateMachine, PIO,
+    },
+};
+
+/// Each bit takes this many state machine cycles.
+const CYCLES_PER_BIT: u32 = 4;
+
+/// Words sent but not yet received are limited to the receive FIFO depth.
+const FIFO_DEPTH: usize = 4;
+
+/// Assemble the program for the clock phase, sending one bit per
+/// [`CYCLES_PER_BIT`] with SCK driven by side-set.
+///
+/// Autopull stalls the program with SCK idle while there is nothing to send.
+fn program(phase: Phase) -> ::pio::Program<32> {
+    let mut asm = Assembler::<32>::new_with_side_set(::pio::SideSet::new(false, 1, false));
+    let mut wrap_target = asm.label();
+    let mut wrap_source = asm.label();
+
+    asm.bind(&mut wrap_target);
+
+    match phase {
+        Phase::CaptureOnFirstTransition => {
+            asm.out_with_delay_and_side_set(OutDestination::PINS, 1, 1, 0);
+            asm.in_with_delay_and_side_set(InSource::PINS, 1, 1, 1);
+        }
+        Phase::CaptureOnSecondTransition => {
+            asm.out_with_side_set(OutDestination::X, 1, 0);
+            asm.mov_with_delay_and_side_set(
+                MovDestination::PINS,
+                MovOperation::None,
+                MovSource::X,
+                1,
+                1,
+            );
+            asm.in_with_side_set(InSource::PINS, 1, 0);
+        }
+    }
+
+    asm.bind(&mut wrap_source);
+    asm.assemble_with_wrap(wrap_source, wrap_target)
+}
+
+/// The GPIO numbers used by a PIO transport.
+///
+/// The pins are driven by number, so their function select is left as it
+/// is. The caller must first put each pin in the function of the PIO block,
+/// for example with `pins.gpio2.into_mode::<FunctionPio0>()`, and keep the
+/// pins for as long as the transport is in use.
+#[derive(Debug, Clone, Copy)]
+pub struct PioPins {
+    pub sck: u8,
+    pub mosi: u8,
+    pub miso: u8,
+}
+
+/// Drives SPI from a PIO state machine, on any pins.
+///
+/// The programs for both clock phases are installed, and the state machine is
+/// rebuilt from the other one when the phase changes. Clock polarity is set
+/// by inverting the SCK output.
+pub struct Pio<SM: ValidStateMachine> {
+    sm: Option<(StateMachine<SM, Running>, Rx<SM>, Tx<SM>)>,
+    other: Option<InstalledProgram<SM::PIO>>,
+    pins: PioPins,
+    system_freq: Hertz<u32>,
+    divisor: f32,
+    mode: Mode,
+}
+
+impl<SM: ValidStateMachine> Pio<SM> {
+    /// Start the state machine on `pins`, which must already be in the
+    /// function of the PIO block. See [`PioPins`].
+    pub fn new(
+        pio: &mut PIO<SM::PIO>,
+        sm: UninitStateMachine<SM>,
+        pins: PioPins,
+        system_freq: Hertz<u32>,
+        baudrate: Hertz<u32>,
+        mode: Mode,
+    ) -> Result<Self> {
+        let (current, other) = match mode.phase {
+            Phase::CaptureOnFirstTransition => (
+                program(Phase::CaptureOnFirstTransition),
+                program(Phase::CaptureOnSecondTransition),
+            ),
+            Phase::CaptureOnSecondTransition => (
+                program(Phase::CaptureOnSecondTransition),
+                program(Phase::CaptureOnFirstTransition),
+            ),
+        };
+
+        let current = pio.install(&current).or(Err(Error::Init))?;
+        let other = pio.install(&other).or(Err(Error::Init))?;
+
+        let mut transport = Self {
+            sm: None,
+            other: Some(other),
+            pins,
+            system_freq,
+            divisor: divisor(system_freq, baudrate.0)?,
+            mode,
+        };
+
+        transport.set_polarity(mode.polarity);
+        transport.start(current, sm);
+        Ok(transport)
+    }
+
+    fn start(&mut self, program: InstalledProgram<SM::PIO>, sm: UninitStateMachine<SM>) {
+        let pins = self.pins;
+        let (mut sm, rx, tx) = PIOBuilder::from_program(program)
+            .side_set_pin_base(pins.sck)
+            .out_pins(pins.mosi, 1)
+            .in_pin_base(pins.miso)
+            .clock_divisor(self.divisor)
+            .out_shift_direction(ShiftDirection::Left)
+            .in_shift_direction(ShiftDirection::Left)
+            .autopull(true)
+            .pull_threshold(8)
+            .autopush(true)
+            .push_threshold(8)
+            .build(sm);
+
+        sm.set_pindirs([
+            (pins.sck, PinDir::Output),
+            (pins.mosi, PinDir::Output),
+            (pins.miso, PinDir::Input),
+        ]);
+
+        self.sm = Some((sm.start(), rx, tx));
+    }
+
+    /// Stop the state machine and start it again with the current settings,
+    /// swapping programs if the clock phase changed.
+    fn restart(&mut self, phase: Phase) {
+        let (sm, rx, tx) = self.sm.take().expect("state machine is running");
+        let (sm, mut program) = sm.stop().uninit(rx, tx);
+
+        if phase != self.mode.phase {
+            program = self.other.replace(program).expect("program is installed");
+        }
+
+        self.start(program, sm);
+    }
+
+    fn set_polarity(&mut self, polarity: Polarity) {
+        let io = unsafe { &*pac::IO_BANK0::ptr() };
+
+        io.gpio[self.pins.sck as usize]
+            .gpio_ctrl
+            .modify(|_, w| match polarity {
+                Polarity::IdleLow => w.outover().normal(),
+                Polarity::IdleHigh => w.outover().invert(),
+            });
+    }
+
+    pub fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        let (_, rx, tx) = self.sm.as_mut().ok_or(Error::Transfer)?;
+        let mut sent = 0;
+        let mut received = 0;
+
+        while received < words.len() {
+            if sent < words.len()
+                && sent - received < FIFO_DEPTH
+                && tx.write((words[sent] as u32) << 24)
+            {
+                sent += 1;
+            }
+
+            if let Some(word) = rx.read() {
+                words[received] = word as u8;
+                received += 1;
+            }
+        }
+
+        Ok(words)
+    }
+
+    pub fn set_clock_speed(&mut self, speed: u32) -> Result {
+        self.divisor = divisor(self.system_freq, speed)?;
+
+        let phase = self.mode.phase;
+        self.restart(phase);
+        Ok(())
+    }
+
+    pub fn set_mode(&mut self, mode: Mode) -> Result {
+        self.set_polarity(mode.polarity);
+
+        if mode.phase != self.mode.phase {
+            self.restart(mode.phase);
+        }
+
+        self.mode = mode;
+        Ok(())
+    }
+}
+
+/// The clock divisor for a bit rate, which can be at most a quarter of the
+/// system clock.
+fn divisor(system_freq: Hertz<u32>, speed: u32) -> Result<f32> {
+    match speed {
+        0 => Err(Error::ClockSpeed),
+        speed => {
+            let cycles = speed.saturating_mul(CYCLES_PER_BIT);
+            Ok((system_freq.0 as f32 / cycles as f32).max(1.0))
+        }
+    }
+}
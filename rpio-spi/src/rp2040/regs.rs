@@ -0,0 +1,104 @@
+use super::super::{Error, Result};
+use embedded_hal::spi::{Mode, Phase, Polarity};
+use rp2040_hal::{
+    pac::{self, spi0::RegisterBlock},
+    spi::SpiDevice,
+};
+
+/// An SPI peripheral whose registers are accessed directly, for the settings
+/// rp2040-hal only applies at initialization.
+pub trait SpiRegisters: SpiDevice {
+    /// The bit of the peripheral in the `RESETS` registers.
+    const RESET: u32;
+
+    fn regs() -> &'static RegisterBlock;
+}
+
+impl SpiRegisters for pac::SPI0 {
+    const RESET: u32 = 1 << 16;
+
+    fn regs() -> &'static RegisterBlock {
+        unsafe { &*pac::SPI0::ptr() }
+    }
+}
+
+impl SpiRegisters for pac::SPI1 {
+    const RESET: u32 = 1 << 17;
+
+    fn regs() -> &'static RegisterBlock {
+        unsafe { &*pac::SPI1::ptr() }
+    }
+}
+
+/// Change the frame format with the peripheral disabled, once it is idle.
+fn reconfigure<D: SpiRegisters>(f: impl FnOnce(&RegisterBlock)) {
+    let regs = D::regs();
+
+    while regs.sspsr.read().bsy().bit_is_set() {}
+
+    regs.sspcr1.modify(|_, w| w.sse().clear_bit());
+    f(regs);
+    regs.sspcr1.modify(|_, w| w.sse().set_bit());
+}
+
+/// Switch the peripheral to slave mode, where the master drives the clock.
+pub fn set_slave<D: SpiRegisters>() {
+    reconfigure::<D>(|regs| regs.sspcr1.modify(|_, w| w.ms().set_bit()));
+}
+
+/// Reset the peripheral to empty its FIFOs, keeping its configuration.
+pub fn flush<D: SpiRegisters>() {
+    let regs = D::regs();
+    let resets = unsafe { &*pac::RESETS::ptr() };
+    let cr0 = regs.sspcr0.read().bits();
+    let cr1 = regs.sspcr1.read().bits();
+    let cpsr = regs.sspcpsr.read().bits();
+    let imsc = regs.sspimsc.read().bits();
+
+    resets
+        .reset
+        .modify(|r, w| unsafe { w.bits(r.bits() | D::RESET) });
+    resets
+        .reset
+        .modify(|r, w| unsafe { w.bits(r.bits() & !D::RESET) });
+    while resets.reset_done.read().bits() & D::RESET == 0 {}
+
+    regs.sspcr0.write(|w| unsafe { w.bits(cr0) });
+    regs.sspcpsr.write(|w| unsafe { w.bits(cpsr) });
+    regs.sspimsc.write(|w| unsafe { w.bits(imsc) });
+    regs.sspcr1.write(|w| unsafe { w.bits(cr1) });
+}
+
+pub fn set_mode<D: SpiRegisters>(mode: Mode) {
+    reconfigure::<D>(|regs| {
+        regs.sspcr0.modify(|_, w| {
+            w.spo()
+                .bit(mode.polarity == Polarity::IdleHigh)
+                .sph()
+                .bit(mode.phase == Phase::CaptureOnSecondTransition)
+        })
+    });
+}
+
+/// The peripheral supports 4 to 16 bits per word.
+pub fn set_word_size<D: SpiRegisters>(bits: u8) -> Result {
+    if !(4..=16).contains(&bits) {
+        return Err(Error::WordSize);
+    }
+
+    reconfigure::<D>(|regs| regs.sspcr0.modify(|_, w| unsafe { w.dss().bits(bits - 1) }));
+    Ok(())
+}
+
+/// Exchange words of up to 16 bits in place through the FIFOs.
+pub fn transfer16<D: SpiRegisters>(words: &mut [u16]) {
+    let regs = D::regs();
+
+    for word in words.iter_mut() {
+        while regs.sspsr.read().tnf().bit_is_clear() {}
+        regs.sspdr.write(|w| unsafe { w.data().bits(*word) });
+
+        while regs.sspsr.read().rne().bit_is_clear() {}
+        *word = regs.sspdr.read().data().bits();
+    }
+}
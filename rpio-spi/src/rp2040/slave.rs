@@ -0,0 +1,128 @@
+use super::super::{Error, Result};
+use super::regs::{self, SpiRegisters};
+use crate::{SlaveQueue, SpiSlave};
+use rp2040_hal::{
+    pac,
+    spi::{Enabled, Spi as Rp2040Spi},
+};
+
+/// An SPI peripheral in slave mode, receiving into a queue of `N` bytes.
+///
+/// The peripheral needs the master to toggle chip select between bytes in
+/// modes 0 and 2, so transactions of several bytes need mode 1 or 3. The
+/// peripheral clock must be at least 12 times the SPI clock.
+///
+/// After each transaction the peripheral is reset to discard the response
+/// bytes left in its transmit FIFO.
+pub struct Slave<D: SpiRegisters, const N: usize> {
+    _spi: Rp2040Spi<Enabled, D, 8>,
+    cs: u32,
+    queue: SlaveQueue<N>,
+    response: [u8; N],
+    response_len: usize,
+    sent: usize,
+    in_transaction: bool,
+}
+
+impl<D: SpiRegisters, const N: usize> Slave<D, N> {
+    pub fn new(spi: Rp2040Spi<Enabled, D, 8>, cs: u8) -> Self {
+        regs::set_slave::<D>();
+
+        let mut slave = Self {
+            _spi: spi,
+            cs: 1 << cs,
+            queue: SlaveQueue::new(),
+            response: [0; N],
+            response_len: 0,
+            sent: 0,
+            in_transaction: false,
+        };
+
+        slave.feed();
+        slave
+    }
+
+    /// Raise the peripheral's interrupt when bytes are received. Call
+    /// [`poll`](SpiSlave::poll) from the handler, and also on the rising
+    /// edge of chip select to end transactions promptly.
+    pub fn listen(&mut self) {
+        D::regs()
+            .sspimsc
+            .modify(|_, w| w.rxim().set_bit().rtim().set_bit());
+    }
+
+    pub fn unlisten(&mut self) {
+        D::regs()
+            .sspimsc
+            .modify(|_, w| w.rxim().clear_bit().rtim().clear_bit());
+    }
+
+    /// Fill the transmit FIFO with the next bytes of the response.
+    fn feed(&mut self) {
+        let regs = D::regs();
+
+        while regs.sspsr.read().tnf().bit_is_set() {
+            let word = match self.sent < self.response_len {
+                true => self.response[self.sent],
+                false => 0,
+            };
+
+            regs.sspdr.write(|w| unsafe { w.data().bits(word.into()) });
+            self.sent += 1;
+        }
+    }
+}
+
+impl<D: SpiRegisters, const N: usize> SpiSlave for Slave<D, N> {
+    fn preload(&mut self, words: &[u8]) -> Result {
+        if words.len() > N {
+            return Err(Error::Overflow);
+        }
+
+        self.response[..words.len()].copy_from_slice(words);
+        self.response_len = words.len();
+
+        if !self.in_transaction {
+            regs::flush::<D>();
+            self.sent = 0;
+            self.feed();
+        }
+
+        Ok(())
+    }
+
+    fn is_selected(&self) -> bool {
+        let sio = unsafe { &*pac::SIO::ptr() };
+        sio.gpio_in.read().bits() & self.cs == 0
+    }
+
+    fn poll(&mut self) -> Result {
+        let regs = D::regs();
+
+        regs.sspicr.write(|w| w.rtic().set_bit());
+
+        while regs.sspsr.read().rne().bit_is_set() {
+            self.queue.push(regs.sspdr.read().data().bits() as u8);
+            self.in_transaction = true;
+            self.feed();
+        }
+
+        if self.in_transaction && !self.is_selected() && regs.sspsr.read().rne().bit_is_clear() {
+            self.queue.end_transaction();
+            self.in_transaction = false;
+
+            regs::flush::<D>();
+            self.sent = 0;
+            self.feed();
+        }
+
+        match self.queue.take_dropped() {
+            true => Err(Error::Overflow),
+            false => Ok(()),
+        }
+    }
+
+    fn read_transaction(&mut self, words: &mut [u8]) -> Option<usize> {
+        self.queue.pop(words)
+    }
+}
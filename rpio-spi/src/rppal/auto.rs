@@ -1,33 +1,87 @@
-use super::super::{Error, Result};
-use crate::{ClockSpeed, SpiDevice, Transfer};
-use _rppal::spi::Spi as RppalSpi;
+use super::RppalBus;
+use crate::{
+    BitOrder, ClockSpeed, Error, FirstBit, Operation, Result, SpiDevice, SpiMode, Transfer,
+    WordSize,
+};
+use _rppal::spi::{Segment, Spi as RppalSpi};
+use embedded_hal::spi::Mode;
 
-pub struct Spi {
-    spi: RppalSpi,
+pub struct Spi<B: RppalBus = RppalSpi> {
+    spi: B,
 }
 
-impl Spi {
-    pub fn new(spi: RppalSpi) -> Self {
+impl<B: RppalBus> Spi<B> {
+    pub fn new(spi: B) -> Self {
         Self { spi }
     }
+
+    /// Perform rppal segments as a single transfer, e.g. to change the clock
+    /// speed or word size part way through.
+    pub fn transfer_segments(&mut self, segments: &[Segment<'_, '_>]) -> Result {
+        self.spi.transfer_segments(segments)
+    }
+
+    pub fn free(self) -> B {
+        self.spi
+    }
 }
 
-impl Transfer<u8> for Spi {
+impl<B: RppalBus> Transfer<u8> for Spi<B> {
     type Error = Error;
 
     fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
-        <RppalSpi as Transfer<u8>>::transfer(&mut self.spi, words).or(Err(Error::Transfer))
+        self.spi.transfer(words)?;
+        Ok(words)
     }
 }
 
-impl SpiDevice for Spi {
+impl<B: RppalBus> SpiDevice for Spi<B> {
+    impl_auto_raw_common!();
+
     fn is_clock_speed(&self) -> bool {
         true
     }
 
     fn set_clock_speed(&mut self, speed: u32) -> Result {
-        self.spi.set_clock_speed(speed).or(Err(Error::ClockSpeed))
+        self.spi.set_clock_speed(speed)
+    }
+
+    fn is_mode(&self) -> bool {
+        true
+    }
+
+    fn is_word_size(&self) -> bool {
+        true
+    }
+
+    fn is_bit_order(&self) -> bool {
+        true
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        self.spi.set_mode(super::mode(mode))
+    }
+
+    fn set_word_size(&mut self, bits: u8) -> Result {
+        self.spi.set_bits_per_word(bits)
+    }
+
+    fn set_bit_order(&mut self, order: FirstBit) -> Result {
+        self.spi.set_bit_order(super::bit_order(order))
+    }
+
+    fn delay_us(&mut self, us: u32) -> Result {
+        super::delay_us(us)
+    }
+
+    /// Performs the operations as a single segmented transfer, so the
+    /// hardware slave select line stays active throughout.
+    fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result {
+        super::transaction(&mut self.spi, operations)
     }
 }
 
-impl ClockSpeed for Spi {}
+impl<B: RppalBus> ClockSpeed for Spi<B> {}
+impl<B: RppalBus> SpiMode for Spi<B> {}
+impl<B: RppalBus> WordSize for Spi<B> {}
+impl<B: RppalBus> BitOrder for Spi<B> {}
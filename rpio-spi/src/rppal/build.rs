@@ -1,56 +1,80 @@
-use super::{auto, cs};
-use crate::Spi as EmbeddedSpi;
+use super::{auto, cs, RppalBus};
+use crate::{ChipSelectConfig, Error, ErrorKind, OutputPin, Spi as EmbeddedSpi};
 use _rppal::{
-    gpio::{Gpio, OutputPin},
-    spi::{Bus, Error, Mode, SlaveSelect, Spi},
+    gpio::{Gpio, OutputPin as RppalPin},
+    spi::{Bus, Mode, SlaveSelect, Spi},
 };
 
 impl EmbeddedSpi {
+    /// Open an SPI bus, using the hardware slave select line `slave_select`
+    /// unless a GPIO pin is chosen with
+    /// [`with_cs`](RppalBuilder::with_cs). An error opening the bus has the
+    /// kind [`ErrorKind::Init`].
     pub fn new_rppal(
         bus: Bus,
         slave_select: SlaveSelect,
         clock_speed: u32,
         mode: Mode,
-    ) -> RppalBuilder {
-        RppalBuilder {
-            spi: Spi::new(bus, slave_select, clock_speed, mode),
-        }
+    ) -> Result<RppalBuilder, Error> {
+        let spi = Spi::new(bus, slave_select, clock_speed, mode)
+            .map_err(|err| Error::with_source(ErrorKind::Init, err))?;
+
+        Ok(RppalBuilder { spi })
     }
 
-    /// Construct a transport from an [`rppal::spi::Spi`](Spi).
-    pub fn from_rppal(spi: Spi) -> RppalBuilder {
-        RppalBuilder { spi: Ok(Spi) }
+    /// Construct a transport from an existing [`rppal::spi::Spi`](Spi), or
+    /// anything else which implements [`RppalBus`].
+    pub fn from_rppal<B: RppalBus>(spi: B) -> RppalBuilder<B> {
+        RppalBuilder { spi }
     }
 }
 
-pub struct RppalBuilder {
-    spi: Result<Spi, Error>,
+pub struct RppalBuilder<B: RppalBus = Spi> {
+    spi: B,
 }
 
-impl RppalBuilder {
-    /// Use the provided [`rppal::gpio::OutputPin`](OutputPin) for chip select.
-    pub fn with_cs(self, bcm_pin: u8) -> RppalChipSelectBuilder {
+impl<B: RppalBus> RppalBuilder<B> {
+    /// Use the BCM GPIO pin `bcm_pin` for chip select. An error opening the
+    /// pin has the kind [`ErrorKind::ChipSelect`].
+    pub fn with_cs(self, bcm_pin: u8) -> Result<RppalChipSelectBuilder<B, RppalPin>, Error> {
         let cs = Gpio::new()
             .and_then(|gpio| gpio.get(bcm_pin))
-            .map(|pin| pin.into_output_high());
+            .map(|pin| pin.into_output_high())
+            .map_err(|err| Error::with_source(ErrorKind::ChipSelect, err))?;
+
+        Ok(RppalChipSelectBuilder {
+            spi: self.spi,
+            cs,
+            cs_config: ChipSelectConfig::default(),
+        })
+    }
 
-        RppalChipSelectBuilder { spi: self.spi, cs }
+    /// Use the provided pin for chip select.
+    pub fn with_cs_pin<CS: OutputPin>(self, cs: CS) -> RppalChipSelectBuilder<B, CS> {
+        RppalChipSelectBuilder {
+            spi: self.spi,
+            cs,
+            cs_config: ChipSelectConfig::default(),
+        }
     }
 
     /// Initialize the transport.
-    pub fn init(self) -> Result<auto::Spi, Error> {
-        Ok(auto::Spi::new(self.spi?))
+    pub fn init(self) -> auto::Spi<B> {
+        auto::Spi::new(self.spi)
     }
 }
 
-pub struct RppalChipSelectBuilder {
-    spi: Result<Spi, Error>,
-    cs: Result<OutputPin, Error>,
+pub struct RppalChipSelectBuilder<B: RppalBus = Spi, CS: OutputPin = RppalPin> {
+    spi: B,
+    cs: CS,
+    cs_config: ChipSelectConfig,
 }
 
-impl RppalChipSelectBuilder {
+impl<B: RppalBus, CS: OutputPin> RppalChipSelectBuilder<B, CS> {
+    impl_cs_builder_common!();
+
     /// Initialize the transport.
-    pub fn init(self) -> Result<cs::Spi, Error> {
-        Ok(cs::Spi::new(self.spi?, self.cs?))
+    pub fn init(self) -> cs::Spi<B, CS> {
+        cs::Spi::new(self.spi, self.cs, self.cs_config)
     }
 }
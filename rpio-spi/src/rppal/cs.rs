@@ -1,38 +1,51 @@
-use super::super::{Error, Result};
-use crate::{ChipSelect, ClockSpeed, SpiDevice, Transfer};
-use _rppal::{gpio::OutputPin as RppalPin, spi::Spi as RppalSpi};
+use super::RppalBus;
+use crate::{
+    BitOrder, ChipSelect, ChipSelectConfig, ClockSpeed, Error, FirstBit, Operation, OutputPin,
+    Result, SpiDevice, SpiMode, Transfer, WordSize,
+};
+use _rppal::{
+    gpio::OutputPin as RppalPin,
+    spi::{Segment, Spi as RppalSpi},
+};
+use embedded_hal::spi::Mode;
 
-pub struct Spi {
-    spi: RppalSpi,
-    cs: RppalPin,
+pub struct Spi<B: RppalBus = RppalSpi, CS: OutputPin = RppalPin> {
+    spi: B,
+    cs: CS,
+    cs_config: ChipSelectConfig,
 }
 
-impl Spi {
-    pub fn new(spi: RppalSpi, cs: RppalPin) -> Self {
-        let mut transport = Self { spi, cs };
+impl<B: RppalBus, CS: OutputPin> Spi<B, CS> {
+    pub fn new(spi: B, cs: CS, cs_config: ChipSelectConfig) -> Self {
+        let mut transport = Self { spi, cs, cs_config };
 
         transport.deselect().ok();
         transport
     }
-}
 
-impl SpiDevice for Spi {
-    fn is_chip_select(&self) -> bool {
-        true
-    }
+    /// Perform rppal segments as a single transfer while the chip is
+    /// selected.
+    pub fn transfer_segments(&mut self, segments: &[Segment<'_, '_>]) -> Result {
+        self.select()?;
+
+        self.spi
+            .transfer_segments(segments)
+            .map_err(|err| self.deselect().map_or(Error::ChipDeselect, |_| err))?;
 
-    fn select(&mut self) -> Result {
-        self.cs.set_low();
-        Ok(())
+        self.deselect()
     }
 
-    fn deselect(&mut self) -> Result {
-        self.cs.set_high();
-        Ok(())
+    pub fn free(self) -> (B, CS) {
+        (self.spi, self.cs)
     }
+}
+
+impl<B: RppalBus, CS: OutputPin> SpiDevice for Spi<B, CS> {
+    impl_cs_common!();
 
     fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
-        <RppalSpi as Transfer<u8>>::transfer(&mut self.spi, words).or(Err(Error::Transfer))
+        self.spi.transfer(words)?;
+        Ok(words)
     }
 
     fn is_clock_speed(&self) -> bool {
@@ -40,13 +53,63 @@ impl SpiDevice for Spi {
     }
 
     fn set_clock_speed(&mut self, speed: u32) -> Result {
-        self.spi.set_clock_speed(speed).or(Err(Error::ClockSpeed))
+        self.spi.set_clock_speed(speed)
+    }
+
+    fn is_mode(&self) -> bool {
+        true
+    }
+
+    fn is_word_size(&self) -> bool {
+        true
+    }
+
+    fn is_bit_order(&self) -> bool {
+        true
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        self.spi.set_mode(super::mode(mode))
+    }
+
+    fn set_word_size(&mut self, bits: u8) -> Result {
+        self.spi.set_bits_per_word(bits)
+    }
+
+    fn set_bit_order(&mut self, order: FirstBit) -> Result {
+        self.spi.set_bit_order(super::bit_order(order))
+    }
+
+    fn delay_us(&mut self, us: u32) -> Result {
+        super::delay_us(us)
+    }
+
+    /// Performs the operations as a single segmented transfer while the chip
+    /// is selected.
+    fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result {
+        self.select()?;
+
+        super::transaction(&mut self.spi, operations)
+            .map_err(|err| self.deselect().map_or(Error::ChipDeselect, |_| err))?;
+
+        self.deselect()
     }
 }
 
-impl Transfer<u8> for Spi {
+impl<B: RppalBus, CS: OutputPin> Transfer<u8> for Spi<B, CS> {
     impl_cs_transfer_common!();
 }
 
-impl ChipSelect for Spi {}
-impl ClockSpeed for Spi {}
+impl<B: RppalBus, CS: OutputPin> embedded_hal_1::spi::ErrorType for Spi<B, CS> {
+    type Error = Error;
+}
+
+impl<B: RppalBus, CS: OutputPin> embedded_hal_1::spi::SpiDevice for Spi<B, CS> {
+    impl_hal1_common!();
+}
+
+impl<B: RppalBus, CS: OutputPin> ChipSelect for Spi<B, CS> {}
+impl<B: RppalBus, CS: OutputPin> ClockSpeed for Spi<B, CS> {}
+impl<B: RppalBus, CS: OutputPin> SpiMode for Spi<B, CS> {}
+impl<B: RppalBus, CS: OutputPin> WordSize for Spi<B, CS> {}
+impl<B: RppalBus, CS: OutputPin> BitOrder for Spi<B, CS> {}
@@ -1,3 +1,115 @@
 mod auto;
 mod build;
 mod cs;
+
+use crate::{Error, ErrorKind, FirstBit, Operation, Result, Transfer};
+use _rppal::spi::{BitOrder, Mode, Segment, Spi};
+use embedded_hal::spi::{Mode as HalMode, Phase, Polarity};
+use std::{thread, time::Duration, vec::Vec};
+
+/// The parts of an [`rppal::spi::Spi`](Spi) used by the rppal transports,
+/// so that they can be driven without a Raspberry Pi.
+pub trait RppalBus {
+    /// Exchange bytes in place.
+    fn transfer(&mut self, words: &mut [u8]) -> Result;
+
+    /// Perform the segments as a single transfer, keeping the slave select
+    /// line active between them.
+    fn transfer_segments(&mut self, segments: &[Segment<'_, '_>]) -> Result;
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result;
+
+    fn set_mode(&mut self, mode: Mode) -> Result;
+
+    fn set_bits_per_word(&mut self, bits: u8) -> Result;
+
+    fn set_bit_order(&mut self, order: BitOrder) -> Result;
+}
+
+impl RppalBus for Spi {
+    fn transfer(&mut self, words: &mut [u8]) -> Result {
+        <Spi as Transfer<u8>>::transfer(self, words)
+            .map(|_| ())
+            .map_err(|err| Error::with_source(ErrorKind::Transfer, err))
+    }
+
+    fn transfer_segments(&mut self, segments: &[Segment<'_, '_>]) -> Result {
+        Spi::transfer_segments(self, segments)
+            .map_err(|err| Error::with_source(ErrorKind::Transfer, err))
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        Spi::set_clock_speed(self, speed)
+            .map_err(|err| Error::with_source(ErrorKind::ClockSpeed, err))
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        Spi::set_mode(self, mode).map_err(|err| Error::with_source(ErrorKind::Mode, err))
+    }
+
+    fn set_bits_per_word(&mut self, bits: u8) -> Result {
+        Spi::set_bits_per_word(self, bits)
+            .map_err(|err| Error::with_source(ErrorKind::WordSize, err))
+    }
+
+    fn set_bit_order(&mut self, order: BitOrder) -> Result {
+        Spi::set_bit_order(self, order).map_err(|err| Error::with_source(ErrorKind::BitOrder, err))
+    }
+}
+
+fn mode(mode: HalMode) -> Mode {
+    match (mode.polarity, mode.phase) {
+        (Polarity::IdleLow, Phase::CaptureOnFirstTransition) => Mode::Mode0,
+        (Polarity::IdleLow, Phase::CaptureOnSecondTransition) => Mode::Mode1,
+        (Polarity::IdleHigh, Phase::CaptureOnFirstTransition) => Mode::Mode2,
+        (Polarity::IdleHigh, Phase::CaptureOnSecondTransition) => Mode::Mode3,
+    }
+}
+
+fn bit_order(order: FirstBit) -> BitOrder {
+    match order {
+        FirstBit::Msb => BitOrder::MsbFirst,
+        FirstBit::Lsb => BitOrder::LsbFirst,
+    }
+}
+
+fn delay_us(us: u32) -> Result {
+    thread::sleep(Duration::from_micros(us.into()));
+    Ok(())
+}
+
+/// Performs the operations as a single segmented transfer. Delays longer than
+/// a segment allows are split across several empty segments.
+fn transaction<B: RppalBus>(bus: &mut B, operations: &mut [Operation<'_>]) -> Result {
+    let writes: Vec<Vec<u8>> = operations
+        .iter()
+        .map(|operation| match operation {
+            Operation::Transfer(words) => words.to_vec(),
+            _ => Vec::new(),
+        })
+        .collect();
+
+    let mut segments = Vec::with_capacity(operations.len());
+
+    for (operation, write) in operations.iter_mut().zip(&writes) {
+        match operation {
+            Operation::Write(words) => segments.push(Segment::with_write(words)),
+            Operation::Read(words) => segments.push(Segment::with_read(words)),
+            Operation::Transfer(words) => segments.push(Segment::new(words, write)),
+            Operation::DelayUs(us) => {
+                let mut remaining = *us;
+
+                while remaining > 0 {
+                    let delay = remaining.min(u16::MAX.into());
+                    let mut segment = Segment::with_write(&[]);
+
+                    segment.set_delay(delay as u16);
+                    segments.push(segment);
+                    remaining -= delay;
+                }
+            }
+        }
+    }
+
+    bus.transfer_segments(&segments)
+}
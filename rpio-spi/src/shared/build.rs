@@ -0,0 +1,87 @@
+use super::cs::{self, Settings};
+use super::{BusMutex, SharedBus};
+use crate::{ChipSelectConfig, FirstBit, OutputPin, Spi, SpiDevice};
+use embedded_hal::spi::Mode;
+
+impl Spi {
+    /// Construct a transport for one device on a [`SharedBus`].
+    pub fn from_shared<M: BusMutex>(bus: &SharedBus<M>) -> SharedBuilder<'_, M>
+    where
+        M::Bus: SpiDevice,
+    {
+        SharedBuilder { bus }
+    }
+}
+
+pub struct SharedBuilder<'a, M: BusMutex>
+where
+    M::Bus: SpiDevice,
+{
+    bus: &'a SharedBus<M>,
+}
+
+impl<'a, M: BusMutex> SharedBuilder<'a, M>
+where
+    M::Bus: SpiDevice,
+{
+    /// Use the provided chip select pin. The device starts with the bus's
+    /// settings.
+    pub fn with_cs<CS: OutputPin>(self, cs: CS) -> SharedChipSelectBuilder<'a, M, CS> {
+        SharedChipSelectBuilder {
+            bus: self.bus,
+            cs,
+            cs_config: ChipSelectConfig::default(),
+            settings: self.bus.settings(),
+        }
+    }
+}
+
+pub struct SharedChipSelectBuilder<'a, M: BusMutex, CS: OutputPin>
+where
+    M::Bus: SpiDevice,
+{
+    bus: &'a SharedBus<M>,
+    cs: CS,
+    cs_config: ChipSelectConfig,
+    settings: Settings,
+}
+
+impl<'a, M: BusMutex, CS: OutputPin> SharedChipSelectBuilder<'a, M, CS>
+where
+    M::Bus: SpiDevice,
+{
+    impl_cs_builder_common!();
+
+    /// Set the bus to the provided clock speed before each transfer. The bus
+    /// must support [`ClockSpeed`](crate::ClockSpeed).
+    pub fn with_clock_speed(mut self, speed: u32) -> Self {
+        self.settings.clock_speed = Some(speed);
+        self
+    }
+
+    /// Set the bus to the provided SPI mode before each transfer. The bus
+    /// must support [`SpiMode`](crate::SpiMode).
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.settings.mode = Some(mode);
+        self
+    }
+
+    /// Set the bus to the provided word size before each transfer. The bus
+    /// must support [`WordSize`](crate::WordSize).
+    pub fn with_word_size(mut self, bits: u8) -> Self {
+        self.settings.word_size = Some(bits);
+        self
+    }
+
+    /// Set the bus to the provided bit order before each transfer. The bus
+    /// must support [`BitOrder`](crate::BitOrder).
+    pub fn with_bit_order(mut self, order: FirstBit) -> Self {
+        self.settings.bit_order = Some(order);
+        self
+    }
+
+    /// Initialize the transport.
+    pub fn init(self) -> cs::Spi<'a, M, CS> {
+        cs::Spi::new(self.bus, self.cs, self.cs_config, self.settings)
+    }
+}
@@ -0,0 +1,113 @@
+use super::cs::Settings;
+use crate::{FirstBit, SpiDevice};
+use core::cell::RefCell;
+use embedded_hal::spi::Mode;
+
+/// Provides exclusive access to an SPI bus which is shared between several
+/// devices.
+pub trait BusMutex {
+    type Bus;
+
+    /// Wrap the bus.
+    fn create(bus: Self::Bus) -> Self;
+
+    /// Run `f` with exclusive access to the bus.
+    fn lock<R>(&self, f: impl FnOnce(&mut Self::Bus) -> R) -> R;
+}
+
+/// For buses which are only used from a single context. Access from an
+/// interrupt while the bus is in use will panic.
+impl<T> BusMutex for RefCell<T> {
+    type Bus = T;
+
+    fn create(bus: T) -> Self {
+        RefCell::new(bus)
+    }
+
+    fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.borrow_mut())
+    }
+}
+
+/// For buses which are used from interrupts. The bus is locked inside a
+/// critical section.
+#[cfg(feature = "critical-section")]
+impl<T> BusMutex for critical_section::Mutex<RefCell<T>> {
+    type Bus = T;
+
+    fn create(bus: T) -> Self {
+        critical_section::Mutex::new(RefCell::new(bus))
+    }
+
+    fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.borrow_ref_mut(cs)))
+    }
+}
+
+/// An SPI bus shared by several devices, each with its own chip select.
+///
+/// The bus should be a transport without chip select, such as the result of
+/// [`Spi::from_hal(spi).init()`](crate::Spi::from_hal). Devices are created
+/// with [`Spi::from_shared`](crate::Spi::from_shared).
+///
+/// A device applies its settings each time it takes the bus, starting from
+/// the bus's settings. A setting which neither the bus nor the device sets is
+/// left as the previous device left it, so when devices differ in a setting
+/// the bus should set it too.
+pub struct SharedBus<M: BusMutex> {
+    mutex: M,
+    settings: Settings,
+}
+
+impl<M: BusMutex> SharedBus<M>
+where
+    M::Bus: SpiDevice,
+{
+    pub fn new(spi: M::Bus) -> Self {
+        Self {
+            mutex: M::create(spi),
+            settings: Settings::default(),
+        }
+    }
+
+    /// Use the provided clock speed for devices which don't set their own.
+    pub fn with_clock_speed(mut self, speed: u32) -> Self {
+        self.settings.clock_speed = Some(speed);
+        self
+    }
+
+    /// Use the provided SPI mode for devices which don't set their own.
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.settings.mode = Some(mode);
+        self
+    }
+
+    /// Use the provided word size for devices which don't set their own.
+    pub fn with_word_size(mut self, bits: u8) -> Self {
+        self.settings.word_size = Some(bits);
+        self
+    }
+
+    /// Use the provided bit order for devices which don't set their own.
+    pub fn with_bit_order(mut self, order: FirstBit) -> Self {
+        self.settings.bit_order = Some(order);
+        self
+    }
+
+    /// The settings new devices start from.
+    pub(crate) fn settings(&self) -> Settings {
+        self.settings
+    }
+
+    /// Run `f` with exclusive access to the bus.
+    pub fn lock<R>(&self, f: impl FnOnce(&mut M::Bus) -> R) -> R {
+        self.mutex.lock(f)
+    }
+}
+
+/// A [`SharedBus`] for single-core use outside of interrupts.
+pub type RefCellBus<SPI> = SharedBus<RefCell<SPI>>;
+
+/// A [`SharedBus`] which can be used from interrupts.
+#[cfg(feature = "critical-section")]
+pub type CriticalSectionBus<SPI> = SharedBus<critical_section::Mutex<RefCell<SPI>>>;
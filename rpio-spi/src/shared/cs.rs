@@ -0,0 +1,253 @@
+use super::super::{Error, Result};
+use super::{BusMutex, SharedBus};
+use crate::chip_select;
+use crate::{
+    BitOrder, ChipSelect, ChipSelectConfig, ClockSpeed, FirstBit, Operation, OutputPin, SpiDevice,
+    SpiMode, Transfer, WordSize,
+};
+use embedded_hal::spi::Mode;
+
+/// The bus settings of one device, applied whenever it takes the bus.
+#[derive(Clone, Copy, Default)]
+pub struct Settings {
+    pub clock_speed: Option<u32>,
+    pub mode: Option<Mode>,
+    pub word_size: Option<u8>,
+    pub bit_order: Option<FirstBit>,
+}
+
+impl Settings {
+    fn apply<S: SpiDevice>(&self, spi: &mut S) -> Result {
+        if let Some(speed) = self.clock_speed {
+            spi.set_clock_speed(speed)?;
+        }
+
+        if let Some(mode) = self.mode {
+            spi.set_mode(mode)?;
+        }
+
+        if let Some(bits) = self.word_size {
+            spi.set_word_size(bits)?;
+        }
+
+        match self.bit_order {
+            Some(order) => spi.set_bit_order(order),
+            None => Ok(()),
+        }
+    }
+}
+
+/// One device on a [`SharedBus`].
+///
+/// [`transfer`](Transfer::transfer) and
+/// [`transaction`](SpiDevice::transaction) hold the bus from select until
+/// deselect. When using [`select`](SpiDevice::select) and
+/// [`raw_transfer`](SpiDevice::raw_transfer) directly, the bus is only held
+/// for each raw transfer.
+pub struct Spi<'a, M: BusMutex, CS: OutputPin>
+where
+    M::Bus: SpiDevice,
+{
+    bus: &'a SharedBus<M>,
+    cs: CS,
+    cs_config: ChipSelectConfig,
+    settings: Settings,
+}
+
+impl<'a, M: BusMutex, CS: OutputPin> Spi<'a, M, CS>
+where
+    M::Bus: SpiDevice,
+{
+    pub fn new(
+        bus: &'a SharedBus<M>,
+        cs: CS,
+        cs_config: ChipSelectConfig,
+        settings: Settings,
+    ) -> Self {
+        let mut transport = Self {
+            bus,
+            cs,
+            cs_config,
+            settings,
+        };
+
+        transport.deselect().ok();
+        transport
+    }
+
+    /// Apply this device's settings to the bus.
+    fn configure(&self, spi: &mut M::Bus) -> Result {
+        self.settings.apply(spi)
+    }
+
+    /// Select the chip, waiting for setup on the locked bus.
+    fn select_on(&mut self, spi: &mut M::Bus) -> Result {
+        self.cs_config.select(&mut self.cs)?;
+        chip_select::delay(spi, self.cs_config.setup_us)
+    }
+
+    /// Deselect the chip, waiting for hold and gap on the locked bus.
+    fn deselect_on(&mut self, spi: &mut M::Bus) -> Result {
+        chip_select::delay(spi, self.cs_config.hold_us)?;
+        self.cs_config.deselect(&mut self.cs)?;
+        chip_select::delay(spi, self.cs_config.gap_us)
+    }
+}
+
+impl<'a, M: BusMutex, CS: OutputPin> Transfer<u8> for Spi<'a, M, CS>
+where
+    M::Bus: SpiDevice,
+{
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        let bus = self.bus;
+
+        bus.lock(|spi| {
+            self.configure(spi)?;
+            self.select_on(spi)?;
+
+            let res = spi
+                .transfer(words)
+                .map_err(|err| self.deselect_on(spi).map_or(Error::ChipDeselect, |_| err))?;
+
+            self.deselect_on(spi).and(Ok(res))
+        })
+    }
+}
+
+impl<'a, M: BusMutex, CS: OutputPin> SpiDevice for Spi<'a, M, CS>
+where
+    M::Bus: SpiDevice,
+{
+    fn is_chip_select(&self) -> bool {
+        true
+    }
+
+    fn select(&mut self) -> Result {
+        let bus = self.bus;
+        bus.lock(|spi| self.select_on(spi))
+    }
+
+    fn deselect(&mut self) -> Result {
+        let bus = self.bus;
+        bus.lock(|spi| self.deselect_on(spi))
+    }
+
+    fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.bus.lock(|spi| {
+            self.configure(spi)?;
+            spi.transfer(words)
+        })
+    }
+
+    fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result {
+        let bus = self.bus;
+
+        bus.lock(|spi| {
+            self.configure(spi)?;
+            self.select_on(spi)?;
+
+            for operation in operations.iter_mut() {
+                spi.raw_operation(operation)
+                    .map_err(|err| self.deselect_on(spi).map_or(Error::ChipDeselect, |_| err))?;
+            }
+
+            self.deselect_on(spi)
+        })
+    }
+
+    fn delay_us(&mut self, us: u32) -> Result {
+        self.bus.lock(|spi| spi.delay_us(us))
+    }
+
+    fn is_clock_speed(&self) -> bool {
+        self.bus.lock(|spi| spi.is_clock_speed())
+    }
+
+    fn is_mode(&self) -> bool {
+        self.bus.lock(|spi| spi.is_mode())
+    }
+
+    fn is_word_size(&self) -> bool {
+        self.bus.lock(|spi| spi.is_word_size())
+    }
+
+    fn is_bit_order(&self) -> bool {
+        self.bus.lock(|spi| spi.is_bit_order())
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        if self.is_clock_speed() {
+            self.settings.clock_speed = Some(speed);
+            Ok(())
+        } else {
+            Err(Error::NotImplemented)
+        }
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        if self.is_mode() {
+            self.settings.mode = Some(mode);
+            Ok(())
+        } else {
+            Err(Error::NotImplemented)
+        }
+    }
+
+    fn set_word_size(&mut self, bits: u8) -> Result {
+        if self.is_word_size() {
+            self.settings.word_size = Some(bits);
+            Ok(())
+        } else {
+            Err(Error::NotImplemented)
+        }
+    }
+
+    fn set_bit_order(&mut self, order: FirstBit) -> Result {
+        if self.is_bit_order() {
+            self.settings.bit_order = Some(order);
+            Ok(())
+        } else {
+            Err(Error::NotImplemented)
+        }
+    }
+}
+
+impl<'a, M: BusMutex, CS: OutputPin> embedded_hal_1::spi::ErrorType for Spi<'a, M, CS>
+where
+    M::Bus: SpiDevice,
+{
+    type Error = Error;
+}
+
+impl<'a, M: BusMutex, CS: OutputPin> embedded_hal_1::spi::SpiDevice for Spi<'a, M, CS>
+where
+    M::Bus: SpiDevice,
+{
+    fn transaction(&mut self, operations: &mut [embedded_hal_1::spi::Operation<'_, u8>]) -> Result {
+        let bus = self.bus;
+
+        bus.lock(|spi| {
+            self.configure(spi)?;
+            self.select_on(spi)?;
+
+            for operation in operations.iter_mut() {
+                crate::hal1::raw_operation(spi, operation)
+                    .map_err(|err| self.deselect_on(spi).map_or(Error::ChipDeselect, |_| err))?;
+            }
+
+            self.deselect_on(spi)
+        })
+    }
+}
+
+impl<'a, M: BusMutex, CS: OutputPin> ChipSelect for Spi<'a, M, CS> where M::Bus: SpiDevice {}
+
+impl<'a, M: BusMutex, CS: OutputPin> ClockSpeed for Spi<'a, M, CS> where M::Bus: ClockSpeed {}
+
+impl<'a, M: BusMutex, CS: OutputPin> SpiMode for Spi<'a, M, CS> where M::Bus: SpiMode {}
+
+impl<'a, M: BusMutex, CS: OutputPin> WordSize for Spi<'a, M, CS> where M::Bus: WordSize {}
+
+impl<'a, M: BusMutex, CS: OutputPin> BitOrder for Spi<'a, M, CS> where M::Bus: BitOrder {}
@@ -0,0 +1,5 @@
+mod build;
+mod bus;
+mod cs;
+
+pub use bus::*;
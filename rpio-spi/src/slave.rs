@@ -0,0 +1,94 @@
+/// The most complete transactions a [`SlaveQueue`] keeps.
+pub const MAX_TRANSACTIONS: usize = 8;
+
+/// Received bytes grouped into transactions, for an [`SpiSlave`] to fill and
+/// drain.
+///
+/// [`SpiSlave`]: crate::SpiSlave
+#[derive(Debug)]
+pub struct SlaveQueue<const N: usize> {
+    bytes: [u8; N],
+    start: usize,
+    len: usize,
+    lens: [usize; MAX_TRANSACTIONS],
+    first: usize,
+    complete: usize,
+    /// The length of the transaction being received.
+    current: usize,
+    dropped: bool,
+}
+
+impl<const N: usize> SlaveQueue<N> {
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            start: 0,
+            len: 0,
+            lens: [0; MAX_TRANSACTIONS],
+            first: 0,
+            complete: 0,
+            current: 0,
+            dropped: false,
+        }
+    }
+
+    /// Add a byte to the transaction being received. Dropped if the queue is
+    /// full.
+    pub fn push(&mut self, byte: u8) {
+        if self.len == N || self.complete == MAX_TRANSACTIONS {
+            self.dropped = true;
+            return;
+        }
+
+        self.bytes[(self.start + self.len) % N] = byte;
+        self.len += 1;
+        self.current += 1;
+    }
+
+    /// End the transaction being received, if it has any bytes.
+    pub fn end_transaction(&mut self) {
+        if self.current == 0 {
+            return;
+        }
+
+        self.lens[(self.first + self.complete) % MAX_TRANSACTIONS] = self.current;
+        self.complete += 1;
+        self.current = 0;
+    }
+
+    /// The number of complete transactions.
+    pub fn transactions(&self) -> usize {
+        self.complete
+    }
+
+    /// Whether bytes were dropped since the last call.
+    pub fn take_dropped(&mut self) -> bool {
+        core::mem::take(&mut self.dropped)
+    }
+
+    /// Copy the oldest complete transaction into `words`, returning its
+    /// length. Bytes which don't fit are discarded.
+    pub fn pop(&mut self, words: &mut [u8]) -> Option<usize> {
+        if self.complete == 0 {
+            return None;
+        }
+
+        let len = self.lens[self.first];
+
+        for (index, word) in words.iter_mut().take(len).enumerate() {
+            *word = self.bytes[(self.start + index) % N];
+        }
+
+        self.start = (self.start + len) % N;
+        self.len -= len;
+        self.first = (self.first + 1) % MAX_TRANSACTIONS;
+        self.complete -= 1;
+        Some(len)
+    }
+}
+
+impl<const N: usize> Default for SlaveQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
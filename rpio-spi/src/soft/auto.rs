@@ -0,0 +1,73 @@
+use super::Bus;
+use crate::{
+    BitOrder, ClockSpeed, Error, FirstBit, OutputPin, Result, SpiDevice, SpiMode, Transfer,
+};
+use embedded_hal::{blocking::delay::DelayUs, digital::v2::InputPin, spi::Mode};
+
+pub struct Spi<SCK, MOSI, MISO, D> {
+    bus: Bus<SCK, MOSI, MISO, D>,
+}
+
+impl<SCK, MOSI, MISO, D> Spi<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    D: DelayUs<u32>,
+{
+    pub fn new(bus: Bus<SCK, MOSI, MISO, D>) -> Self {
+        Self { bus }
+    }
+}
+
+impl<SCK, MOSI, MISO, D> Transfer<u8> for Spi<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    D: DelayUs<u32>,
+{
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.bus.transfer(words)
+    }
+}
+
+impl<SCK, MOSI, MISO, D> SpiDevice for Spi<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    D: DelayUs<u32>,
+{
+    impl_auto_raw_common!();
+    impl_soft_common!();
+}
+
+impl<SCK, MOSI, MISO, D> ClockSpeed for Spi<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    D: DelayUs<u32>,
+{
+}
+
+impl<SCK, MOSI, MISO, D> SpiMode for Spi<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    D: DelayUs<u32>,
+{
+}
+
+impl<SCK, MOSI, MISO, D> BitOrder for Spi<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    D: DelayUs<u32>,
+{
+}
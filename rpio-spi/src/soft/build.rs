@@ -0,0 +1,100 @@
+use super::{auto, cs, Bus};
+use crate::{ChipSelectConfig, FirstBit, OutputPin, Spi};
+use embedded_hal::{blocking::delay::DelayUs, digital::v2::InputPin, spi::Mode};
+
+impl Spi {
+    /// Construct a bit-banged transport from plain GPIO pins.
+    ///
+    /// The delay paces the clock, and defaults to a half-period of 1 µs.
+    pub fn from_soft<SCK, MOSI, MISO, D>(
+        sck: SCK,
+        mosi: MOSI,
+        miso: MISO,
+        delay: D,
+    ) -> SoftBuilder<SCK, MOSI, MISO, D>
+    where
+        SCK: OutputPin,
+        MOSI: OutputPin,
+        MISO: InputPin,
+        D: DelayUs<u32>,
+    {
+        SoftBuilder {
+            bus: Bus::new(sck, mosi, miso, delay),
+        }
+    }
+}
+
+pub struct SoftBuilder<SCK, MOSI, MISO, D> {
+    bus: Bus<SCK, MOSI, MISO, D>,
+}
+
+impl<SCK, MOSI, MISO, D> SoftBuilder<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    D: DelayUs<u32>,
+{
+    /// Use the provided SPI mode.
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.bus.mode = mode;
+        self
+    }
+
+    /// Use the provided bit order.
+    pub fn with_bit_order(mut self, order: FirstBit) -> Self {
+        self.bus.bit_order = order;
+        self
+    }
+
+    /// Wait this long for each half of a clock cycle. Zero clocks as fast as
+    /// the pins can be driven.
+    pub fn with_half_period_us(mut self, us: u32) -> Self {
+        self.bus.half_period_us = us;
+        self
+    }
+
+    /// Use the provided chip select pin.
+    pub fn with_cs<CS: OutputPin>(self, cs: CS) -> SoftChipSelectBuilder<SCK, MOSI, MISO, CS, D> {
+        SoftChipSelectBuilder {
+            bus: self.bus,
+            cs,
+            cs_config: ChipSelectConfig::default(),
+        }
+    }
+
+    /// Initialize the transport.
+    ///
+    /// Chip select must be handled outside of the transport.
+    pub fn init(mut self) -> auto::Spi<SCK, MOSI, MISO, D> {
+        let mode = self.bus.mode;
+
+        self.bus.set_mode(mode).ok();
+        auto::Spi::new(self.bus)
+    }
+}
+
+pub struct SoftChipSelectBuilder<SCK, MOSI, MISO, CS, D> {
+    bus: Bus<SCK, MOSI, MISO, D>,
+    cs: CS,
+    cs_config: ChipSelectConfig,
+}
+
+impl<SCK, MOSI, MISO, CS, D> SoftChipSelectBuilder<SCK, MOSI, MISO, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    CS: OutputPin,
+    D: DelayUs<u32>,
+{
+    impl_cs_builder_common!();
+
+    /// Initialize the transport.
+    pub fn init(mut self) -> cs::Spi<SCK, MOSI, MISO, CS, D> {
+        let mode = self.bus.mode;
+
+        self.bus.set_mode(mode).ok();
+        cs::Spi::new(self.bus, self.cs, self.cs_config)
+    }
+}
@@ -0,0 +1,117 @@
+use super::Bus;
+use crate::{
+    BitOrder, ChipSelect, ChipSelectConfig, ClockSpeed, Error, FirstBit, OutputPin, Result,
+    SpiDevice, SpiMode, Transfer,
+};
+use embedded_hal::{blocking::delay::DelayUs, digital::v2::InputPin, spi::Mode};
+
+pub struct Spi<SCK, MOSI, MISO, CS, D> {
+    bus: Bus<SCK, MOSI, MISO, D>,
+    cs: CS,
+    cs_config: ChipSelectConfig,
+}
+
+impl<SCK, MOSI, MISO, CS, D> Spi<SCK, MOSI, MISO, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    CS: OutputPin,
+    D: DelayUs<u32>,
+{
+    pub fn new(bus: Bus<SCK, MOSI, MISO, D>, cs: CS, cs_config: ChipSelectConfig) -> Self {
+        let mut transport = Self { bus, cs, cs_config };
+
+        transport.deselect().ok();
+        transport
+    }
+}
+
+impl<SCK, MOSI, MISO, CS, D> Transfer<u8> for Spi<SCK, MOSI, MISO, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    CS: OutputPin,
+    D: DelayUs<u32>,
+{
+    impl_cs_transfer_common!();
+}
+
+impl<SCK, MOSI, MISO, CS, D> SpiDevice for Spi<SCK, MOSI, MISO, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    CS: OutputPin,
+    D: DelayUs<u32>,
+{
+    impl_cs_common!();
+    impl_soft_common!();
+
+    fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.bus.transfer(words)
+    }
+}
+
+impl<SCK, MOSI, MISO, CS, D> embedded_hal_1::spi::ErrorType for Spi<SCK, MOSI, MISO, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    CS: OutputPin,
+    D: DelayUs<u32>,
+{
+    type Error = Error;
+}
+
+impl<SCK, MOSI, MISO, CS, D> embedded_hal_1::spi::SpiDevice for Spi<SCK, MOSI, MISO, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    CS: OutputPin,
+    D: DelayUs<u32>,
+{
+    impl_hal1_common!();
+}
+
+impl<SCK, MOSI, MISO, CS, D> ChipSelect for Spi<SCK, MOSI, MISO, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    CS: OutputPin,
+    D: DelayUs<u32>,
+{
+}
+
+impl<SCK, MOSI, MISO, CS, D> ClockSpeed for Spi<SCK, MOSI, MISO, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    CS: OutputPin,
+    D: DelayUs<u32>,
+{
+}
+
+impl<SCK, MOSI, MISO, CS, D> SpiMode for Spi<SCK, MOSI, MISO, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    CS: OutputPin,
+    D: DelayUs<u32>,
+{
+}
+
+impl<SCK, MOSI, MISO, CS, D> BitOrder for Spi<SCK, MOSI, MISO, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    CS: OutputPin,
+    D: DelayUs<u32>,
+{
+}
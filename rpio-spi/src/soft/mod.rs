@@ -0,0 +1,168 @@
+use crate::{Error, ErrorKind, FirstBit, OutputPin, Result};
+use embedded_hal::{
+    blocking::delay::DelayUs,
+    digital::v2::InputPin,
+    spi::{Mode, Phase, Polarity, MODE_0},
+};
+use embedded_hal_1::spi::ErrorKind as HalKind;
+
+macro_rules! impl_soft_common {
+    () => {
+        fn is_clock_speed(&self) -> bool {
+            true
+        }
+
+        fn set_clock_speed(&mut self, speed: u32) -> Result {
+            self.bus.set_clock_speed(speed)
+        }
+
+        fn is_mode(&self) -> bool {
+            true
+        }
+
+        fn set_mode(&mut self, mode: Mode) -> Result {
+            self.bus.set_mode(mode)
+        }
+
+        fn is_bit_order(&self) -> bool {
+            true
+        }
+
+        fn set_bit_order(&mut self, order: FirstBit) -> Result {
+            self.bus.bit_order = order;
+            Ok(())
+        }
+
+        fn delay_us(&mut self, us: u32) -> Result {
+            self.bus.delay.delay_us(us);
+            Ok(())
+        }
+    };
+}
+
+mod auto;
+mod build;
+mod cs;
+
+/// Pins and timing shared by the software transports.
+pub struct Bus<SCK, MOSI, MISO, D> {
+    sck: SCK,
+    mosi: MOSI,
+    miso: MISO,
+    delay: D,
+    mode: Mode,
+    bit_order: FirstBit,
+    half_period_us: u32,
+}
+
+impl<SCK, MOSI, MISO, D> Bus<SCK, MOSI, MISO, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    MISO: InputPin,
+    D: DelayUs<u32>,
+{
+    fn new(sck: SCK, mosi: MOSI, miso: MISO, delay: D) -> Self {
+        Self {
+            sck,
+            mosi,
+            miso,
+            delay,
+            mode: MODE_0,
+            bit_order: FirstBit::Msb,
+            half_period_us: 1,
+        }
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        match speed {
+            0 => Err(Error::ClockSpeed),
+            speed => {
+                self.half_period_us = 500_000u32.div_ceil(speed);
+                Ok(())
+            }
+        }
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        self.mode = mode;
+        self.clock(false).or(Err(Error::Mode))
+    }
+
+    /// Drive the clock to its active level, or back to idle.
+    fn clock(&mut self, active: bool) -> Result {
+        match active == (self.mode.polarity == Polarity::IdleLow) {
+            true => self.sck.set_high(),
+            false => self.sck.set_low(),
+        }
+        .map_err(|_| Error::with_hal(ErrorKind::Transfer, HalKind::Other))
+    }
+
+    fn write_bit(&mut self, high: bool) -> Result {
+        match high {
+            true => self.mosi.set_high(),
+            false => self.mosi.set_low(),
+        }
+        .map_err(|_| Error::with_hal(ErrorKind::Transfer, HalKind::Other))
+    }
+
+    fn read_bit(&mut self) -> Result<bool> {
+        self.miso
+            .is_high()
+            .map_err(|_| Error::with_hal(ErrorKind::Transfer, HalKind::Other))
+    }
+
+    fn half_period(&mut self) {
+        if self.half_period_us > 0 {
+            self.delay.delay_us(self.half_period_us);
+        }
+    }
+
+    fn transfer_word(&mut self, word: u8) -> Result<u8> {
+        let mut read = 0;
+
+        for bit in 0..8 {
+            let mask = match self.bit_order {
+                FirstBit::Msb => 0x80 >> bit,
+                FirstBit::Lsb => 1 << bit,
+            };
+
+            match self.mode.phase {
+                Phase::CaptureOnFirstTransition => {
+                    self.write_bit(word & mask != 0)?;
+                    self.half_period();
+                    self.clock(true)?;
+
+                    if self.read_bit()? {
+                        read |= mask;
+                    }
+
+                    self.half_period();
+                    self.clock(false)?;
+                }
+                Phase::CaptureOnSecondTransition => {
+                    self.clock(true)?;
+                    self.write_bit(word & mask != 0)?;
+                    self.half_period();
+                    self.clock(false)?;
+
+                    if self.read_bit()? {
+                        read |= mask;
+                    }
+
+                    self.half_period();
+                }
+            }
+        }
+
+        Ok(read)
+    }
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        for word in words.iter_mut() {
+            *word = self.transfer_word(*word)?;
+        }
+
+        Ok(words)
+    }
+}
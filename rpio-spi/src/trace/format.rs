@@ -0,0 +1,166 @@
+use super::{Entry, Record};
+use core::fmt;
+
+/// Writes records as lines of text, one per event or transfer:
+///
+/// ```text
+/// [       120 us] select
+/// [       121 us] note ReadJedecId
+/// [       121 us] transfer 9f:ff 00:ef 00:40 00:18
+/// [       125 us] deselect
+/// ```
+///
+/// Each transfer byte is shown as `mosi:miso`.
+#[derive(Debug, Default)]
+pub struct Text {
+    in_transfer: bool,
+}
+
+impl Text {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, out: &mut impl fmt::Write, record: &Record) -> fmt::Result {
+        if let Entry::Byte { start, mosi, miso } = record.entry {
+            if !start && self.in_transfer {
+                return write!(out, " {:02x}:{:02x}", mosi, miso);
+            }
+
+            self.finish(out)?;
+            self.in_transfer = true;
+            return write!(
+                out,
+                "[{:>10} us] transfer {:02x}:{:02x}",
+                record.time_us, mosi, miso
+            );
+        }
+
+        self.finish(out)?;
+        write!(out, "[{:>10} us] ", record.time_us)?;
+
+        match record.entry {
+            Entry::Select => writeln!(out, "select"),
+            Entry::Deselect => writeln!(out, "deselect"),
+            Entry::ClockSpeed(speed) => writeln!(out, "clock {} Hz", speed),
+            Entry::Note(note) => writeln!(out, "note {}", note),
+            Entry::Byte { .. } => Ok(()),
+        }
+    }
+
+    /// End the line of a transfer in progress.
+    pub fn finish(&mut self, out: &mut impl fmt::Write) -> fmt::Result {
+        if self.in_transfer {
+            self.in_transfer = false;
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The clock speed assumed until one is recorded.
+const DEFAULT_CLOCK_SPEED: u32 = 1_000_000;
+
+/// Writes records as a value change dump with `cs`, `sck`, `mosi` and `miso`
+/// signals, which PulseView's SPI decoder can read.
+///
+/// Only whole bytes are recorded, so the waveform is rebuilt in mode 0, most
+/// significant bit first, at the last recorded clock speed. Transfers are
+/// moved later where needed to keep the timeline in order.
+#[derive(Debug)]
+pub struct Vcd {
+    header: bool,
+    /// The time in nanoseconds of the last change.
+    now: u64,
+    half_period: u64,
+}
+
+impl Default for Vcd {
+    fn default() -> Self {
+        Self {
+            header: false,
+            now: 0,
+            half_period: half_period(DEFAULT_CLOCK_SPEED),
+        }
+    }
+}
+
+fn half_period(speed: u32) -> u64 {
+    500_000_000u64.div_ceil(speed.max(1).into())
+}
+
+impl Vcd {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn header(&mut self, out: &mut impl fmt::Write) -> fmt::Result {
+        if self.header {
+            return Ok(());
+        }
+
+        self.header = true;
+        writeln!(out, "$timescale 1 ns $end")?;
+        writeln!(out, "$scope module spi $end")?;
+        writeln!(out, "$var wire 1 c cs $end")?;
+        writeln!(out, "$var wire 1 k sck $end")?;
+        writeln!(out, "$var wire 1 o mosi $end")?;
+        writeln!(out, "$var wire 1 i miso $end")?;
+        writeln!(out, "$upscope $end")?;
+        writeln!(out, "$enddefinitions $end")?;
+        writeln!(out, "#0")?;
+        writeln!(out, "1c")?;
+        writeln!(out, "0k")?;
+        writeln!(out, "0o")?;
+        writeln!(out, "0i")
+    }
+
+    /// Move to `time`, or just after the last change if that is later.
+    fn at(&mut self, out: &mut impl fmt::Write, time: u64) -> fmt::Result {
+        self.now = time.max(self.now + 1);
+        writeln!(out, "#{}", self.now)
+    }
+
+    pub fn write(&mut self, out: &mut impl fmt::Write, record: &Record) -> fmt::Result {
+        self.header(out)?;
+
+        let time = record.time_us * 1000;
+
+        match record.entry {
+            Entry::Select => {
+                self.at(out, time)?;
+                writeln!(out, "0c")
+            }
+            Entry::Deselect => {
+                self.at(out, time)?;
+                writeln!(out, "1c")
+            }
+            Entry::ClockSpeed(speed) => {
+                self.half_period = half_period(speed);
+                Ok(())
+            }
+            Entry::Byte { mosi, miso, .. } => {
+                self.at(out, time)?;
+
+                // Each bit is set up along with the falling edge of the last
+                for bit in (0..8).rev() {
+                    writeln!(out, "{}o", (mosi >> bit) & 1)?;
+                    writeln!(out, "{}i", (miso >> bit) & 1)?;
+                    self.at(out, self.now + self.half_period)?;
+                    writeln!(out, "1k")?;
+                    self.at(out, self.now + self.half_period)?;
+                    writeln!(out, "0k")?;
+                }
+
+                Ok(())
+            }
+            Entry::Note(_) => Ok(()),
+        }
+    }
+
+    /// Write the header, if there were no records.
+    pub fn finish(&mut self, out: &mut impl fmt::Write) -> fmt::Result {
+        self.header(out)
+    }
+}
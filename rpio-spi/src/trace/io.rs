@@ -0,0 +1,117 @@
+use super::{Entry, Record, Text, TraceEvent, TraceSink, Vcd};
+use core::fmt;
+use std::{io, vec::Vec};
+
+enum Format {
+    Text(Text),
+    Vcd(Vcd),
+}
+
+/// Writes records to a [`std::io::Write`] as they happen.
+///
+/// Write errors are kept until [`finish`](Self::finish), and later records
+/// are dropped.
+pub struct WriteSink<W: io::Write> {
+    out: Adapter<W>,
+    format: Format,
+    mosi: Vec<u8>,
+    mosi_time: u64,
+}
+
+impl<W: io::Write> WriteSink<W> {
+    /// Write a text log, as by [`Text`].
+    pub fn text(out: W) -> Self {
+        Self::new(out, Format::Text(Text::new()))
+    }
+
+    /// Write a value change dump, as by [`Vcd`].
+    pub fn vcd(out: W) -> Self {
+        Self::new(out, Format::Vcd(Vcd::new()))
+    }
+
+    fn new(out: W, format: Format) -> Self {
+        Self {
+            out: Adapter { out, error: None },
+            format,
+            mosi: Vec::new(),
+            mosi_time: 0,
+        }
+    }
+
+    fn write(&mut self, record: &Record) {
+        if self.out.error.is_some() {
+            return;
+        }
+
+        match &mut self.format {
+            Format::Text(text) => text.write(&mut self.out, record),
+            Format::Vcd(vcd) => vcd.write(&mut self.out, record),
+        }
+        .ok();
+    }
+
+    /// End the output and return the writer, or the first write error.
+    pub fn finish(mut self) -> io::Result<W> {
+        match &mut self.format {
+            Format::Text(text) => text.finish(&mut self.out),
+            Format::Vcd(vcd) => vcd.finish(&mut self.out),
+        }
+        .ok();
+
+        match self.out.error {
+            Some(err) => Err(err),
+            None => Ok(self.out.out),
+        }
+    }
+}
+
+impl<W: io::Write> TraceSink for WriteSink<W> {
+    fn record(&mut self, time_us: u64, event: TraceEvent<'_>) {
+        let entry = match event {
+            TraceEvent::Select => Entry::Select,
+            TraceEvent::Deselect => Entry::Deselect,
+            TraceEvent::ClockSpeed(speed) => Entry::ClockSpeed(speed),
+            TraceEvent::Note(note) => Entry::Note(note),
+            TraceEvent::Mosi(mosi) => {
+                self.mosi.clear();
+                self.mosi.extend_from_slice(mosi);
+                self.mosi_time = time_us;
+                return;
+            }
+            TraceEvent::Miso(miso) => {
+                let mosi = core::mem::take(&mut self.mosi);
+
+                for (index, (&mosi, &miso)) in mosi.iter().zip(miso).enumerate() {
+                    self.write(&Record {
+                        time_us: self.mosi_time,
+                        entry: Entry::Byte {
+                            start: index == 0,
+                            mosi,
+                            miso,
+                        },
+                    });
+                }
+
+                self.mosi = mosi;
+                return;
+            }
+        };
+
+        self.write(&Record { time_us, entry });
+    }
+}
+
+/// Lets the formats write to an [`io::Write`], keeping the error.
+struct Adapter<W: io::Write> {
+    out: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for Adapter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_all(s.as_bytes()).map_err(|err| {
+            self.error.get_or_insert(err);
+            fmt::Error
+        })
+    }
+}
@@ -0,0 +1,422 @@
+mod format;
+mod ring;
+
+#[cfg(feature = "std")]
+mod io;
+
+pub use format::{Text, Vcd};
+pub use ring::RingBuffer;
+
+#[cfg(feature = "std")]
+pub use io::WriteSink;
+
+use crate::{
+    BitOrder, ChipSelect, ClockSpeed, Error, FirstBit, Lines, MultiLine, Operation, Phases, Result,
+    SpiDevice, SpiMode, Transfer, WordSize,
+};
+use embedded_hal::spi::Mode;
+
+/// Recorded for the direction of an exchange which carries nothing.
+const ZEROS: [u8; 32] = [0; 32];
+
+/// How many bytes sent by the transfers of a transaction are kept, to be
+/// recorded after the transport has exchanged them in place.
+const SENT_LEN: usize = 64;
+
+/// Something that happened on the bus, as passed to a [`TraceSink`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceEvent<'a> {
+    Select,
+    Deselect,
+    ClockSpeed(u32),
+    /// The bytes about to be sent.
+    Mosi(&'a [u8]),
+    /// The bytes received in exchange for the last [`Mosi`](Self::Mosi).
+    Miso(&'a [u8]),
+    /// An annotation from a [`Decoder`] or a higher layer.
+    Note(&'static str),
+}
+
+/// Receives the events recorded by [`Traced`], with a timestamp in
+/// microseconds.
+pub trait TraceSink {
+    fn record(&mut self, time_us: u64, event: TraceEvent<'_>);
+}
+
+/// A single stored event, with the bytes of a transfer paired up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    pub time_us: u64,
+    pub entry: Entry,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entry {
+    Select,
+    Deselect,
+    ClockSpeed(u32),
+    /// A byte exchanged with the chip. `start` is set on the first byte of
+    /// each transfer.
+    Byte {
+        start: bool,
+        mosi: u8,
+        miso: u8,
+    },
+    Note(&'static str),
+}
+
+/// Names the frames sent to a chip, such as the opcode at the start of each
+/// flash command.
+pub trait Decoder {
+    /// Called before each transfer with the bytes to be sent. `frame_start`
+    /// is set for the first transfer after the chip is selected.
+    fn decode(&mut self, frame_start: bool, mosi: &[u8]) -> Option<&'static str>;
+}
+
+impl<F: FnMut(bool, &[u8]) -> Option<&'static str>> Decoder for F {
+    fn decode(&mut self, frame_start: bool, mosi: &[u8]) -> Option<&'static str> {
+        self(frame_start, mosi)
+    }
+}
+
+/// The decoder of a [`Traced`] device which was not given one.
+pub struct NoDecoder;
+
+impl Decoder for NoDecoder {
+    fn decode(&mut self, _frame_start: bool, _mosi: &[u8]) -> Option<&'static str> {
+        None
+    }
+}
+
+/// Records the traffic of an [`SpiDevice`] into a [`TraceSink`].
+///
+/// The clock returns the current time in microseconds, e.g. from a hardware
+/// timer. Use `|| 0` if there is none.
+pub struct Traced<S: SpiDevice, K: TraceSink, C: FnMut() -> u64, D: Decoder = NoDecoder> {
+    spi: S,
+    sink: K,
+    clock: C,
+    decoder: D,
+    frame_start: bool,
+}
+
+impl<S: SpiDevice, K: TraceSink, C: FnMut() -> u64> Traced<S, K, C> {
+    pub fn new(spi: S, sink: K, clock: C) -> Self {
+        Self {
+            spi,
+            sink,
+            clock,
+            decoder: NoDecoder,
+            frame_start: true,
+        }
+    }
+}
+
+impl<S: SpiDevice, K: TraceSink, C: FnMut() -> u64, D: Decoder> Traced<S, K, C, D> {
+    /// Annotate frames with the provided decoder.
+    pub fn with_decoder<T: Decoder>(self, decoder: T) -> Traced<S, K, C, T> {
+        Traced {
+            spi: self.spi,
+            sink: self.sink,
+            clock: self.clock,
+            decoder,
+            frame_start: self.frame_start,
+        }
+    }
+
+    pub fn sink(&self) -> &K {
+        &self.sink
+    }
+
+    pub fn sink_mut(&mut self) -> &mut K {
+        &mut self.sink
+    }
+
+    pub fn into_inner(self) -> (S, K) {
+        (self.spi, self.sink)
+    }
+
+    fn record(&mut self, event: TraceEvent<'_>) {
+        let time = (self.clock)();
+        self.sink.record(time, event);
+    }
+
+    /// Record bytes exchanged with the chip, annotated by the decoder. The
+    /// bytes received are left out when the exchange failed.
+    fn record_exchange(&mut self, mosi: &[u8], miso: Option<&[u8]>) {
+        if let Some(note) = self.decoder.decode(self.frame_start, mosi) {
+            self.record(TraceEvent::Note(note));
+        }
+
+        self.frame_start = false;
+        self.record(TraceEvent::Mosi(mosi));
+
+        if let Some(miso) = miso {
+            self.record(TraceEvent::Miso(miso));
+        }
+    }
+
+    /// Exchange bytes with the chip, recording both directions. Transports
+    /// without chip select control frame every transfer.
+    fn traced_transfer<'w>(&mut self, words: &'w mut [u8], raw: bool) -> Result<&'w [u8]> {
+        let frame_start = self.frame_start || !self.spi.is_chip_select();
+
+        if let Some(note) = self.decoder.decode(frame_start, words) {
+            self.record(TraceEvent::Note(note));
+        }
+
+        self.frame_start = false;
+        self.record(TraceEvent::Mosi(words));
+
+        let read = match raw {
+            true => self.spi.raw_transfer(words)?,
+            false => self.spi.transfer(words)?,
+        };
+
+        self.record(TraceEvent::Miso(read));
+        Ok(read)
+    }
+}
+
+impl<S: SpiDevice, K: TraceSink, C: FnMut() -> u64, D: Decoder> Transfer<u8>
+    for Traced<S, K, C, D>
+{
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        if !self.spi.is_chip_select() {
+            return self.traced_transfer(words, false);
+        }
+
+        self.select()
+            .and_then(|_| self.raw_transfer_or_deselect(words))
+            .and_then(|res| self.deselect().and(Ok(res)))
+    }
+}
+
+impl<S: SpiDevice, K: TraceSink, C: FnMut() -> u64, D: Decoder> SpiDevice for Traced<S, K, C, D> {
+    fn is_chip_select(&self) -> bool {
+        self.spi.is_chip_select()
+    }
+
+    fn is_clock_speed(&self) -> bool {
+        self.spi.is_clock_speed()
+    }
+
+    fn is_mode(&self) -> bool {
+        self.spi.is_mode()
+    }
+
+    fn is_word_size(&self) -> bool {
+        self.spi.is_word_size()
+    }
+
+    fn is_bit_order(&self) -> bool {
+        self.spi.is_bit_order()
+    }
+
+    fn select(&mut self) -> Result {
+        self.record(TraceEvent::Select);
+        self.frame_start = true;
+        self.spi.select()
+    }
+
+    fn deselect(&mut self) -> Result {
+        self.record(TraceEvent::Deselect);
+        self.spi.deselect()
+    }
+
+    fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.traced_transfer(words, true)
+    }
+
+    /// Transports without chip select control perform the whole transaction
+    /// themselves, so its operations are recorded in order once it is done,
+    /// as a frame of their own. Zeros are recorded for what writes read and
+    /// reads send, and for bytes sent by transfers beyond the first 64. When
+    /// the transaction fails, only the bytes sent are recorded, followed by
+    /// an `error` note.
+    fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result {
+        if self.spi.is_chip_select() {
+            self.select()?;
+
+            for operation in operations.iter_mut() {
+                self.raw_operation(operation)
+                    .map_err(|err| self.deselect().map_or(Error::ChipDeselect, |_| err))?;
+            }
+
+            return self.deselect();
+        }
+
+        // Transfers exchange their bytes in place, so keep what they send.
+        let mut sent = [0; SENT_LEN];
+        let mut kept = 0;
+
+        for operation in operations.iter() {
+            if let Operation::Transfer(words) = operation {
+                let len = words.len().min(SENT_LEN - kept);
+                sent[kept..kept + len].copy_from_slice(&words[..len]);
+                kept += len;
+            }
+        }
+
+        let result = self.spi.transaction(operations);
+        let received = |miso| result.is_ok().then_some(miso);
+
+        let mut sent = &sent[..kept];
+        self.frame_start = true;
+
+        for operation in operations.iter() {
+            match operation {
+                Operation::Write(words) => {
+                    for chunk in words.chunks(ZEROS.len()) {
+                        self.record_exchange(chunk, received(&ZEROS[..chunk.len()]));
+                    }
+                }
+                Operation::Read(words) => {
+                    for chunk in words.chunks(ZEROS.len()) {
+                        self.record_exchange(&ZEROS[..chunk.len()], received(chunk));
+                    }
+                }
+                Operation::Transfer(words) => {
+                    for chunk in words.chunks(ZEROS.len()) {
+                        let mut mosi = ZEROS;
+                        let len = chunk.len().min(sent.len());
+                        mosi[..len].copy_from_slice(&sent[..len]);
+                        sent = &sent[len..];
+
+                        self.record_exchange(&mosi[..chunk.len()], received(chunk));
+                    }
+                }
+                Operation::DelayUs(_) => (),
+            }
+        }
+
+        if result.is_err() {
+            self.record(TraceEvent::Note("error"));
+        }
+
+        result
+    }
+
+    fn delay_us(&mut self, us: u32) -> Result {
+        self.spi.delay_us(us)
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        self.record(TraceEvent::ClockSpeed(speed));
+        self.spi.set_clock_speed(speed)
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        self.spi.set_mode(mode)
+    }
+
+    fn set_word_size(&mut self, bits: u8) -> Result {
+        self.spi.set_word_size(bits)
+    }
+
+    fn set_bit_order(&mut self, order: FirstBit) -> Result {
+        self.spi.set_bit_order(order)
+    }
+
+    fn max_lines(&self) -> Lines {
+        self.spi.max_lines()
+    }
+
+    /// The instruction and address are recorded as one transfer, and the
+    /// data as transfers of up to 32 bytes, whatever lines they use.
+    fn read_multi_line(
+        &mut self,
+        phases: Phases,
+        instruction: u8,
+        address: &[u8],
+        words: &mut [u8],
+    ) -> Result {
+        let mut header = [0; 32];
+        let len = (address.len() + 1).min(header.len());
+        header[0] = instruction;
+        header[1..len].copy_from_slice(&address[..len - 1]);
+
+        self.record(TraceEvent::Select);
+
+        if let Some(note) = self.decoder.decode(true, &header[..len]) {
+            self.record(TraceEvent::Note(note));
+        }
+
+        self.record(TraceEvent::Mosi(&header[..len]));
+        self.record(TraceEvent::Miso(&ZEROS[..len]));
+
+        let result = self
+            .spi
+            .read_multi_line(phases, instruction, address, words);
+
+        if result.is_ok() {
+            for chunk in words.chunks(ZEROS.len()) {
+                self.record(TraceEvent::Mosi(&ZEROS[..chunk.len()]));
+                self.record(TraceEvent::Miso(chunk));
+            }
+        }
+
+        self.record(TraceEvent::Deselect);
+        result
+    }
+
+    fn annotate(&mut self, note: &'static str) {
+        self.record(TraceEvent::Note(note));
+    }
+}
+
+impl<S, K, C, D> ChipSelect for Traced<S, K, C, D>
+where
+    S: ChipSelect,
+    K: TraceSink,
+    C: FnMut() -> u64,
+    D: Decoder,
+{
+}
+
+impl<S, K, C, D> ClockSpeed for Traced<S, K, C, D>
+where
+    S: ClockSpeed,
+    K: TraceSink,
+    C: FnMut() -> u64,
+    D: Decoder,
+{
+}
+
+impl<S, K, C, D> SpiMode for Traced<S, K, C, D>
+where
+    S: SpiMode,
+    K: TraceSink,
+    C: FnMut() -> u64,
+    D: Decoder,
+{
+}
+
+impl<S, K, C, D> WordSize for Traced<S, K, C, D>
+where
+    S: WordSize,
+    K: TraceSink,
+    C: FnMut() -> u64,
+    D: Decoder,
+{
+}
+
+impl<S, K, C, D> BitOrder for Traced<S, K, C, D>
+where
+    S: BitOrder,
+    K: TraceSink,
+    C: FnMut() -> u64,
+    D: Decoder,
+{
+}
+
+impl<S, K, C, D> MultiLine for Traced<S, K, C, D>
+where
+    S: MultiLine,
+    K: TraceSink,
+    C: FnMut() -> u64,
+    D: Decoder,
+{
+}
@@ -0,0 +1,145 @@
+use super::{Entry, Record, Text, TraceEvent, TraceSink, Vcd};
+use core::fmt;
+
+/// Keeps the latest `N` records, overwriting the oldest.
+pub struct RingBuffer<const N: usize> {
+    records: [Record; N],
+    start: usize,
+    len: usize,
+    dropped: usize,
+    /// Bytes of the current transfer still waiting for the bytes received.
+    pending: usize,
+}
+
+impl<const N: usize> RingBuffer<N> {
+    const EMPTY: Record = Record {
+        time_us: 0,
+        entry: Entry::Deselect,
+    };
+
+    pub const fn new() -> Self {
+        Self {
+            records: [Self::EMPTY; N],
+            start: 0,
+            len: 0,
+            dropped: 0,
+            pending: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of records overwritten since the buffer was cleared.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.len = 0;
+        self.dropped = 0;
+        self.pending = 0;
+    }
+
+    /// The records from oldest to newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Record> + Clone {
+        (0..self.len).map(move |index| &self.records[(self.start + index) % N])
+    }
+
+    fn push(&mut self, record: Record) {
+        if N == 0 {
+            return;
+        }
+
+        if self.len < N {
+            self.records[(self.start + self.len) % N] = record;
+            self.len += 1;
+        } else {
+            self.records[self.start] = record;
+            self.start = (self.start + 1) % N;
+            self.dropped += 1;
+        }
+    }
+
+    /// Fill in the bytes received for the bytes of the last transfer which
+    /// have not been overwritten.
+    fn receive(&mut self, miso: &[u8]) {
+        let kept = self.pending.min(self.len);
+        let skipped = self.pending - kept;
+        let first = self.len - kept;
+
+        for (index, &byte) in miso.iter().skip(skipped).take(kept).enumerate() {
+            let record = &mut self.records[(self.start + first + index) % N];
+
+            if let Entry::Byte { miso, .. } = &mut record.entry {
+                *miso = byte;
+            }
+        }
+
+        self.pending = 0;
+    }
+
+    /// Write the records as a text log.
+    pub fn write_text(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        let mut text = Text::new();
+
+        for record in self.iter() {
+            text.write(out, record)?;
+        }
+
+        text.finish(out)
+    }
+
+    /// Write the records as a value change dump, which can be imported into
+    /// sigrok and PulseView.
+    pub fn write_vcd(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        let mut vcd = Vcd::new();
+
+        for record in self.iter() {
+            vcd.write(out, record)?;
+        }
+
+        vcd.finish(out)
+    }
+}
+
+impl<const N: usize> Default for RingBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> TraceSink for RingBuffer<N> {
+    fn record(&mut self, time_us: u64, event: TraceEvent<'_>) {
+        let entry = match event {
+            TraceEvent::Select => Entry::Select,
+            TraceEvent::Deselect => Entry::Deselect,
+            TraceEvent::ClockSpeed(speed) => Entry::ClockSpeed(speed),
+            TraceEvent::Note(note) => Entry::Note(note),
+            TraceEvent::Miso(miso) => return self.receive(miso),
+            TraceEvent::Mosi(mosi) => {
+                for (index, &byte) in mosi.iter().enumerate() {
+                    self.push(Record {
+                        time_us,
+                        entry: Entry::Byte {
+                            start: index == 0,
+                            mosi: byte,
+                            miso: 0,
+                        },
+                    });
+                }
+
+                self.pending = mosi.len();
+                return;
+            }
+        };
+
+        self.push(Record { time_us, entry });
+    }
+}
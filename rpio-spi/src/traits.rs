@@ -1,4 +1,60 @@
 use crate::{Error, Result, Transfer};
+use embedded_hal::spi::Mode;
+
+/// A single step of a [`transaction`](SpiDevice::transaction).
+#[derive(Debug, PartialEq)]
+pub enum Operation<'a> {
+    /// Write bytes to the chip, discarding the bytes read.
+    Write(&'a [u8]),
+    /// Read bytes from the chip while writing zeroes.
+    Read(&'a mut [u8]),
+    /// Exchange bytes with the chip in place.
+    Transfer(&'a mut [u8]),
+    /// Wait for a number of microseconds with the chip selected.
+    ///
+    /// Fails with [`NotImplemented`](Error::NotImplemented) on transports
+    /// which can't delay, such as a chip select transport built without
+    /// `with_delay`. The operations before it have then already been
+    /// performed.
+    DelayUs(u32),
+}
+
+/// Which bit of each word is sent first.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FirstBit {
+    #[default]
+    Msb,
+    Lsb,
+}
+
+/// The number of data lines used by a phase of a multi-line transfer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Lines {
+    #[default]
+    Single = 1,
+    Dual = 2,
+    Quad = 4,
+}
+
+/// The lines used by each phase of a
+/// [`read_multi_line`](SpiDevice::read_multi_line), as in the dual and quad
+/// read commands of flash chips.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Phases {
+    pub instruction: Lines,
+    pub address: Lines,
+    /// Clock cycles between the address and the data, with the lines of the
+    /// address phase.
+    pub dummy_cycles: u8,
+    pub data: Lines,
+}
+
+impl Phases {
+    /// The most lines used by any phase.
+    pub fn max_lines(&self) -> Lines {
+        self.instruction.max(self.address).max(self.data)
+    }
+}
 
 /// Indicates that the implementation of [`Transfer<u8>`](Transfer) for this
 /// struct:
@@ -17,6 +73,21 @@ pub trait SpiDevice: Transfer<u8, Error = Error> {
         false
     }
 
+    /// Whether the SPI mode can be controlled
+    fn is_mode(&self) -> bool {
+        false
+    }
+
+    /// Whether the word size can be controlled
+    fn is_word_size(&self) -> bool {
+        false
+    }
+
+    /// Whether the bit order can be controlled
+    fn is_bit_order(&self) -> bool {
+        false
+    }
+
     /// Select the chip.
     ///
     /// This typically drives the pin low, but in some configurations could
@@ -34,7 +105,7 @@ pub trait SpiDevice: Transfer<u8, Error = Error> {
     }
 
     /// Exchange bytes with the chip without selecting or deslecting it.
-    fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+    fn raw_transfer<'w>(&mut self, _words: &'w mut [u8]) -> Result<&'w [u8]> {
         Err(Error::NotImplemented)
     }
 
@@ -45,10 +116,150 @@ pub trait SpiDevice: Transfer<u8, Error = Error> {
             .map_err(|err| self.deselect().map_or(Error::ChipDeselect, |_| err))
     }
 
+    /// Write bytes to the chip without selecting or deselecting it.
+    fn raw_write(&mut self, words: &[u8]) -> Result {
+        let mut buf = [0; 32];
+
+        for chunk in words.chunks(buf.len()) {
+            let buf = &mut buf[..chunk.len()];
+            buf.copy_from_slice(chunk);
+            self.raw_transfer(buf)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read bytes from the chip without selecting or deselecting it.
+    fn raw_read(&mut self, words: &mut [u8]) -> Result {
+        words.fill(0);
+        self.raw_transfer(words).and(Ok(()))
+    }
+
+    /// Perform an [`Operation`] without selecting or deselecting the chip.
+    fn raw_operation(&mut self, operation: &mut Operation<'_>) -> Result {
+        match operation {
+            Operation::Write(words) => self.raw_write(words),
+            Operation::Read(words) => self.raw_read(words),
+            Operation::Transfer(words) => self.raw_transfer(words).and(Ok(())),
+            Operation::DelayUs(us) => self.delay_us(*us),
+        }
+    }
+
+    /// Perform the operations in order while the chip is selected. The chip
+    /// is deselected at the end, or when an operation fails.
+    ///
+    /// Transports which don't control chip selection can't hold the chip
+    /// selected between operations, and return
+    /// [`NotImplemented`](Error::NotImplemented) unless they override this
+    /// with a transaction the peripheral performs in one piece.
+    fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result {
+        if !self.is_chip_select() {
+            return Err(Error::NotImplemented);
+        }
+
+        self.select()?;
+
+        for operation in operations.iter_mut() {
+            self.raw_operation(operation)
+                .map_err(|err| self.deselect().map_or(Error::ChipDeselect, |_| err))?;
+        }
+
+        self.deselect()
+    }
+
+    /// Wait for a number of microseconds.
+    fn delay_us(&mut self, _us: u32) -> Result {
+        Err(Error::NotImplemented)
+    }
+
     /// Set the SPI clock speed.
-    fn set_clock_speed(&mut self, speed: u32) -> Result {
+    fn set_clock_speed(&mut self, _speed: u32) -> Result {
         Err(Error::NotImplemented)
     }
+
+    /// Set the SPI mode, i.e. the clock polarity and phase.
+    fn set_mode(&mut self, _mode: Mode) -> Result {
+        Err(Error::NotImplemented)
+    }
+
+    /// Set the number of bits in each word. Transfers of `u8` words need a
+    /// word size of 8 bits or fewer.
+    fn set_word_size(&mut self, _bits: u8) -> Result {
+        Err(Error::NotImplemented)
+    }
+
+    /// Set which bit of each word is sent first.
+    fn set_bit_order(&mut self, _order: FirstBit) -> Result {
+        Err(Error::NotImplemented)
+    }
+
+    /// The most data lines a multi-line read can use.
+    fn max_lines(&self) -> Lines {
+        Lines::Single
+    }
+
+    /// Select the chip, send the instruction and address, wait for the dummy
+    /// cycles and read `words`, each phase on its own lines, then deselect.
+    fn read_multi_line(
+        &mut self,
+        _phases: Phases,
+        _instruction: u8,
+        _address: &[u8],
+        _words: &mut [u8],
+    ) -> Result {
+        Err(Error::NotImplemented)
+    }
+
+    /// Describe the frame about to be sent, for transports which record
+    /// traffic such as [`Traced`](crate::Traced). Ignored by others.
+    fn annotate(&mut self, _note: &'static str) {}
+}
+
+/// A transfer which runs in the background, for example using DMA, while the
+/// core does other work.
+///
+/// The buffer is owned by the transport until the transfer is finished, and
+/// blocking transfers fail with [`Error::Busy`] in the meantime.
+pub trait BackgroundTransfer: SpiDevice {
+    /// Start exchanging bytes with the chip in place, without selecting it.
+    fn raw_start_transfer(&mut self, words: &'static mut [u8]) -> Result;
+
+    /// Whether the transfer in progress has finished. Also true when no
+    /// transfer was started.
+    fn is_done(&self) -> bool;
+
+    /// Wait for the transfer in progress to finish, without deselecting the
+    /// chip, and return its buffer.
+    fn raw_finish(&mut self) -> Result<&'static mut [u8]>;
+
+    /// Select the chip, if chip selection is controlled, and start exchanging
+    /// bytes in place.
+    fn start_transfer(&mut self, words: &'static mut [u8]) -> Result {
+        if !self.is_done() {
+            return Err(Error::Busy);
+        }
+
+        if self.is_chip_select() {
+            self.select()?;
+            self.raw_start_transfer(words)
+                .map_err(|err| self.deselect().map_or(Error::ChipDeselect, |_| err))
+        } else {
+            self.raw_start_transfer(words)
+        }
+    }
+
+    /// Wait for the transfer in progress to finish, deselect the chip if
+    /// chip selection is controlled, and return the buffer with the bytes
+    /// read.
+    fn finish(&mut self) -> Result<&'static mut [u8]> {
+        let words = self.raw_finish()?;
+
+        if self.is_chip_select() {
+            self.deselect()?;
+        }
+
+        Ok(words)
+    }
 }
 
 /// Indicates that chip selection is controlled by a user-defined output pin.
@@ -56,3 +267,38 @@ pub trait ChipSelect: SpiDevice {}
 
 /// Indicates that the SPI clock speed can be set during operation.
 pub trait ClockSpeed: SpiDevice {}
+
+/// Indicates that the SPI mode can be set during operation.
+pub trait SpiMode: SpiDevice {}
+
+/// Indicates that the word size can be set during operation.
+pub trait WordSize: SpiDevice {}
+
+/// Indicates that the bit order can be set during operation.
+pub trait BitOrder: SpiDevice {}
+
+/// Indicates that reads can use more than one data line.
+pub trait MultiLine: SpiDevice {}
+
+/// An SPI peripheral acting as a slave, receiving transactions framed by the
+/// master's chip select.
+pub trait SpiSlave {
+    /// Send these bytes to the master in each following transaction, then
+    /// zeroes. Replaces the previous response.
+    fn preload(&mut self, words: &[u8]) -> Result;
+
+    /// Whether the master is selecting this device.
+    fn is_selected(&self) -> bool;
+
+    /// Move received bytes into the queue and end the transaction once the
+    /// master deselects this device. Call this from the interrupt handler,
+    /// or often enough not to miss a transaction.
+    ///
+    /// Fails with [`Error::Overflow`] if bytes were dropped since the last
+    /// call.
+    fn poll(&mut self) -> Result;
+
+    /// Copy the oldest complete transaction into `words`, returning its
+    /// length. Bytes which don't fit are discarded.
+    fn read_transaction(&mut self, words: &mut [u8]) -> Option<usize>;
+}
@@ -0,0 +1,83 @@
+use crate::mock::{Event, Log, MockBus, MockPin};
+use rpio_spi::{BackgroundTransfer, Error, OutputPin, Result, SpiDevice, Transfer};
+
+/// Holds on to the buffer until finished, then transfers it on the bus.
+struct MockBackground {
+    bus: MockBus,
+    cs: MockPin,
+    words: Option<&'static mut [u8]>,
+}
+
+impl Transfer<u8> for MockBackground {
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.bus.transfer(words)
+    }
+}
+
+impl SpiDevice for MockBackground {
+    fn is_chip_select(&self) -> bool {
+        true
+    }
+
+    fn select(&mut self) -> Result {
+        self.cs.set_low().or(Err(Error::ChipSelect))
+    }
+
+    fn deselect(&mut self) -> Result {
+        self.cs.set_high().or(Err(Error::ChipDeselect))
+    }
+}
+
+impl BackgroundTransfer for MockBackground {
+    fn raw_start_transfer(&mut self, words: &'static mut [u8]) -> Result {
+        if self.words.is_some() {
+            return Err(Error::Busy);
+        }
+
+        self.words = Some(words);
+        Ok(())
+    }
+
+    fn is_done(&self) -> bool {
+        self.words.is_none()
+    }
+
+    fn raw_finish(&mut self) -> Result<&'static mut [u8]> {
+        let words = self.words.take().ok_or(Error::Transfer)?;
+        self.bus.transfer(words)?;
+        Ok(words)
+    }
+}
+
+#[test]
+fn background() {
+    let log = Log::default();
+    let mut spi = MockBackground {
+        bus: MockBus::new(&log),
+        cs: MockPin::new(1, &log),
+        words: None,
+    };
+
+    spi.start_transfer(Box::leak(Box::new([1, 2]))).unwrap();
+    assert!(!spi.is_done());
+    assert_eq!(
+        spi.start_transfer(Box::leak(Box::new([3]))),
+        Err(Error::Busy)
+    );
+
+    assert_eq!(spi.finish().map(|words| &*words), Ok(&[1, 2][..]));
+    assert!(spi.is_done());
+    assert_eq!(spi.finish(), Err(Error::Transfer));
+
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::Select(1),
+            Event::Transfer(1),
+            Event::Transfer(2),
+            Event::Deselect(1),
+        ]
+    );
+}
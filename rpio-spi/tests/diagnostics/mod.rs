@@ -0,0 +1,75 @@
+use rpio_spi::{
+    diagnostics::{self, ChipSelectCheck, ClockSweep, Loopback, PATTERN, SPEEDS, SWEEP_ROUNDS},
+    Error, MockSpi, Transaction,
+};
+
+#[test]
+fn loopback_passes() {
+    let mut spi = MockSpi::new();
+    let report = diagnostics::run(&mut spi);
+
+    assert_eq!(report.loopback, Ok(Loopback::Passed));
+    assert_eq!(
+        report.clock,
+        Some(Ok(ClockSweep {
+            highest: Some(40_000_000),
+            failed: None,
+        }))
+    );
+    assert_eq!(report.chip_select, ChipSelectCheck::Passed);
+    assert!(report.is_passed());
+}
+
+#[test]
+fn loopback_faults() {
+    assert_eq!(diagnostics::compare(&[0; 8]), Loopback::StuckLow);
+    assert_eq!(diagnostics::compare(&[0xFF; 8]), Loopback::StuckHigh);
+    assert_eq!(
+        diagnostics::compare(&[0x00, 0xFF, 0xAA, 0x54, 0x01, 0x80, 0x0F, 0xF0]),
+        Loopback::Corrupt {
+            index: 3,
+            sent: 0x55,
+            received: 0x54,
+        }
+    );
+
+    let mut spi = MockSpi::with_expectations(&[
+        Transaction::Select,
+        Transaction::transfer(&PATTERN, &[0; 8]),
+        Transaction::Deselect,
+    ]);
+
+    assert_eq!(diagnostics::loopback(&mut spi), Ok(Loopback::StuckLow));
+    spi.done();
+}
+
+#[test]
+fn sweep_clock() {
+    let mut spi = MockSpi::new();
+    let mut checks = 0;
+
+    let sweep = diagnostics::sweep_clock(&mut spi, &SPEEDS, |_| {
+        checks += 1;
+        Ok(checks <= 3 * SWEEP_ROUNDS)
+    });
+
+    assert_eq!(
+        sweep,
+        Ok(ClockSweep {
+            highest: Some(1_000_000),
+            failed: Some(2_000_000),
+        })
+    );
+    assert_eq!(spi.log().last(), Some(&Transaction::ClockSpeed(1_000_000)));
+}
+
+#[test]
+fn chip_select_fault() {
+    let mut spi = MockSpi::new();
+    spi.expect_err(Transaction::Select, Error::ChipSelect);
+
+    assert_eq!(
+        diagnostics::check_chip_select(&mut spi),
+        ChipSelectCheck::Failed(Error::ChipSelect)
+    );
+}
@@ -0,0 +1,32 @@
+use embedded_hal_1::spi::ErrorKind as HalKind;
+use rpio_spi::{Error, ErrorKind, ErrorSource};
+
+#[test]
+fn hal_source() {
+    let err = Error::with_hal(ErrorKind::Transfer, HalKind::Overrun);
+
+    assert_eq!(err, Error::Transfer);
+    assert_eq!(err.kind(), ErrorKind::Transfer);
+    assert!(matches!(err.backend(), ErrorSource::Hal(HalKind::Overrun)));
+    assert_eq!(embedded_hal_1::spi::Error::kind(&err), HalKind::Overrun);
+
+    assert_eq!(
+        embedded_hal_1::spi::Error::kind(&Error::ChipDeselect),
+        HalKind::ChipSelectFault
+    );
+    assert!(matches!(Error::Busy.backend(), ErrorSource::None));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn std_source() {
+    use std::{error::Error as _, io, string::ToString};
+
+    let io = io::Error::new(io::ErrorKind::PermissionDenied, "no access");
+    let err = Error::with_source(ErrorKind::ChipSelect, io);
+
+    assert_eq!(err, Error::ChipSelect);
+    assert_eq!(err.to_string(), "Select SPI chip error: no access");
+    assert_eq!(err.source().unwrap().to_string(), "no access");
+    assert_eq!(Error::Transfer.to_string(), "SPI transfer error");
+}
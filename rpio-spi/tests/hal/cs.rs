@@ -0,0 +1,156 @@
+use crate::mock::{Event, Log, MockBus, MockDelay, MockPin};
+use embedded_hal_1::spi::ErrorKind as HalKind;
+use rpio_spi::{Error, ErrorSource, Operation, Spi, SpiDevice, Transfer};
+
+#[test]
+fn transfer() {
+    let log = Log::default();
+    let mut spi = Spi::from_hal(MockBus::new(&log))
+        .with_cs(MockPin::new(1, &log))
+        .init();
+
+    assert_eq!(log.borrow().as_slice(), &[Event::Deselect(1)]);
+    log.borrow_mut().clear();
+
+    assert_eq!(spi.transfer(&mut [1, 2]), Ok(&[1, 2][..]));
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::Select(1),
+            Event::Transfer(1),
+            Event::Transfer(2),
+            Event::Deselect(1),
+        ]
+    );
+}
+
+#[test]
+fn transaction() {
+    let log = Log::default();
+    let mut spi = Spi::from_hal(MockBus::new(&log))
+        .with_cs(MockPin::new(1, &log))
+        .init();
+
+    let mut read = [0xFF; 1];
+    let mut transfer = [3];
+    log.borrow_mut().clear();
+
+    spi.transaction(&mut [
+        Operation::Write(&[1, 2]),
+        Operation::Transfer(&mut transfer),
+        Operation::Read(&mut read),
+    ])
+    .unwrap();
+
+    assert_eq!(read, [0]);
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::Select(1),
+            Event::Transfer(1),
+            Event::Transfer(2),
+            Event::Transfer(3),
+            Event::Transfer(0),
+            Event::Deselect(1),
+        ]
+    );
+
+    log.borrow_mut().clear();
+
+    assert_eq!(
+        spi.transaction(&mut [Operation::Write(&[1]), Operation::DelayUs(10)]),
+        Err(Error::NotImplemented)
+    );
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[Event::Select(1), Event::Transfer(1), Event::Deselect(1)]
+    );
+}
+
+#[test]
+fn auto_transaction() {
+    let log = Log::default();
+    let mut spi = Spi::from_hal(MockBus::new(&log)).init();
+    let mut read = [0xFF; 1];
+
+    // Without chip select the operations can't be held together.
+    assert_eq!(
+        spi.transaction(&mut [Operation::Write(&[1, 2]), Operation::Read(&mut read)]),
+        Err(Error::NotImplemented)
+    );
+    assert_eq!(read, [0xFF]);
+    assert_eq!(log.borrow().as_slice(), &[]);
+}
+
+#[test]
+fn cs_config() {
+    let log = Log::default();
+    let mut spi = Spi::from_hal(MockBus::new(&log))
+        .with_cs(MockPin::new(1, &log))
+        .with_active_high()
+        .with_setup_us(1)
+        .with_hold_us(2)
+        .with_gap_us(3)
+        .with_delay(MockDelay::new(&log))
+        .init();
+
+    log.borrow_mut().clear();
+    spi.transfer(&mut [1]).unwrap();
+
+    // The mock pin logs driving low as a select and high as a deselect
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::Deselect(1),
+            Event::Delay(1),
+            Event::Transfer(1),
+            Event::Delay(2),
+            Event::Select(1),
+            Event::Delay(3),
+        ]
+    );
+}
+
+#[test]
+fn cs_config_without_delay() {
+    let log = Log::default();
+    let mut spi = Spi::from_hal(MockBus::new(&log))
+        .with_cs(MockPin::new(1, &log))
+        .with_setup_us(1)
+        .init();
+
+    assert_eq!(spi.transfer(&mut [1]), Err(Error::NotImplemented));
+    assert_eq!(spi.delay_us(1), Err(Error::NotImplemented));
+}
+
+#[test]
+fn backend_errors() {
+    let log = Log::default();
+    let mut bus = MockBus::new(&log);
+    bus.fail = true;
+
+    // The bus fails with an overrun, which is kept as the source.
+    let mut spi = Spi::from_hal(bus).init();
+    let err = spi.transfer(&mut [1]).unwrap_err();
+    assert_eq!(err, Error::Transfer);
+    assert!(matches!(err.backend(), ErrorSource::Hal(HalKind::Overrun)));
+
+    let mut bus = MockBus::new(&log);
+    bus.fail = true;
+
+    let mut spi = Spi::from_hal(bus).with_cs(MockPin::new(1, &log)).init();
+    let err = spi.transfer(&mut [1]).unwrap_err();
+    assert_eq!(err, Error::Transfer);
+    assert!(matches!(err.backend(), ErrorSource::Hal(HalKind::Overrun)));
+
+    let mut cs = MockPin::new(1, &log);
+    cs.fail = true;
+
+    let mut spi = Spi::from_hal(MockBus::new(&log)).with_cs(cs).init();
+    let err = spi.transfer(&mut [1]).unwrap_err();
+    assert_eq!(err, Error::ChipSelect);
+    assert!(matches!(
+        err.backend(),
+        ErrorSource::Hal(HalKind::ChipSelectFault)
+    ));
+}
@@ -0,0 +1,189 @@
+use crate::mock::{Event, Log, MockBus, MockPin};
+use embedded_hal_1::{digital, spi};
+use rpio_spi::{hal1, Operation, Spi, SpiDevice, Transfer};
+
+#[test]
+fn hal1_device() {
+    let log = Log::default();
+    let mut spi = Spi::from_hal(MockBus::new(&log))
+        .with_cs(MockPin::new(1, &log))
+        .init();
+
+    let mut read = [0; 3];
+    log.borrow_mut().clear();
+
+    spi::SpiDevice::transaction(
+        &mut spi,
+        &mut [
+            spi::Operation::Write(&[1]),
+            spi::Operation::Transfer(&mut read, &[2, 3]),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(read, [2, 3, 0]);
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::Select(1),
+            Event::Transfer(1),
+            Event::Transfer(2),
+            Event::Transfer(3),
+            Event::Transfer(0),
+            Event::Deselect(1),
+        ]
+    );
+}
+
+#[test]
+fn hal1_bus() {
+    let log = Log::default();
+    let mut spi = Spi::from_hal(hal1::SpiBus::new(Hal1Bus::new(&log)))
+        .with_cs(hal1::OutputPin::new(Hal1Pin::new(1, &log)))
+        .init();
+
+    log.borrow_mut().clear();
+
+    assert_eq!(spi.transfer(&mut [1, 2]), Ok(&[1, 2][..]));
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::Select(1),
+            Event::Transfer(1),
+            Event::Transfer(2),
+            Event::Deselect(1),
+        ]
+    );
+}
+
+#[test]
+fn from_hal1() {
+    let log = Log::default();
+    let mut spi = Spi::from_hal1(Hal1Device::new(&log)).init();
+    let mut read = [0xFF];
+
+    spi.transaction(&mut [Operation::Write(&[1]), Operation::Read(&mut read)])
+        .unwrap();
+
+    assert_eq!(read, [0]);
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::Select(0),
+            Event::Transfer(1),
+            Event::Transfer(0),
+            Event::Deselect(0),
+        ]
+    );
+}
+
+struct Hal1Bus {
+    log: Log,
+}
+
+impl Hal1Bus {
+    fn new(log: &Log) -> Self {
+        Self { log: log.clone() }
+    }
+}
+
+impl spi::ErrorType for Hal1Bus {
+    type Error = spi::ErrorKind;
+}
+
+impl spi::SpiBus<u8> for Hal1Bus {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        words.fill(0);
+        self.transfer_in_place(words)
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        let mut log = self.log.borrow_mut();
+        log.extend(words.iter().map(|&word| Event::Transfer(word)));
+        Ok(())
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        read.fill(0);
+        read[..write.len()].copy_from_slice(write);
+        self.transfer_in_place(read)
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.write(words)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+struct Hal1Device {
+    bus: Hal1Bus,
+}
+
+impl Hal1Device {
+    fn new(log: &Log) -> Self {
+        Self {
+            bus: Hal1Bus::new(log),
+        }
+    }
+}
+
+impl spi::ErrorType for Hal1Device {
+    type Error = spi::ErrorKind;
+}
+
+impl spi::SpiDevice<u8> for Hal1Device {
+    fn transaction(
+        &mut self,
+        operations: &mut [spi::Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        use spi::SpiBus;
+
+        self.bus.log.borrow_mut().push(Event::Select(0));
+
+        for operation in operations {
+            match operation {
+                spi::Operation::Read(words) => self.bus.read(words)?,
+                spi::Operation::Write(words) => self.bus.write(words)?,
+                spi::Operation::Transfer(read, write) => self.bus.transfer(read, write)?,
+                spi::Operation::TransferInPlace(words) => self.bus.transfer_in_place(words)?,
+                spi::Operation::DelayNs(_) => (),
+            }
+        }
+
+        self.bus.log.borrow_mut().push(Event::Deselect(0));
+        Ok(())
+    }
+}
+
+struct Hal1Pin {
+    id: u8,
+    log: Log,
+}
+
+impl Hal1Pin {
+    fn new(id: u8, log: &Log) -> Self {
+        Self {
+            id,
+            log: log.clone(),
+        }
+    }
+}
+
+impl digital::ErrorType for Hal1Pin {
+    type Error = digital::ErrorKind;
+}
+
+impl digital::OutputPin for Hal1Pin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.log.borrow_mut().push(Event::Select(self.id));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.log.borrow_mut().push(Event::Deselect(self.id));
+        Ok(())
+    }
+}
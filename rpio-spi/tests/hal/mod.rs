@@ -0,0 +1,2 @@
+mod cs;
+mod hal1;
@@ -0,0 +1,20 @@
+mod mock;
+
+mod background;
+#[cfg(feature = "mock")]
+mod diagnostics;
+mod error;
+#[cfg(feature = "hal")]
+mod hal;
+#[cfg(feature = "mock")]
+mod mock_spi;
+#[cfg(feature = "mock")]
+mod retry;
+#[cfg(feature = "rppal")]
+mod rppal;
+mod shared;
+mod slave;
+#[cfg(feature = "soft")]
+mod soft;
+#[cfg(feature = "mock")]
+mod trace;
@@ -0,0 +1,113 @@
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal_1::spi::ErrorKind as HalKind;
+use rpio_spi::{Error, ErrorKind, OutputPin, Result, SpiDevice, Transfer};
+use std::{cell::RefCell, rc::Rc, vec::Vec};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    Select(u8),
+    Deselect(u8),
+    ClockSpeed(u32),
+    Transfer(u8),
+    Delay(u32),
+}
+
+pub type Log = Rc<RefCell<Vec<Event>>>;
+
+pub struct MockBus {
+    log: Log,
+    pub fail: bool,
+}
+
+impl MockBus {
+    pub fn new(log: &Log) -> Self {
+        Self {
+            log: log.clone(),
+            fail: false,
+        }
+    }
+}
+
+impl Transfer<u8> for MockBus {
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        if self.fail {
+            return Err(Error::with_hal(ErrorKind::Transfer, HalKind::Overrun));
+        }
+
+        let mut log = self.log.borrow_mut();
+        log.extend(words.iter().map(|&word| Event::Transfer(word)));
+        Ok(words)
+    }
+}
+
+impl SpiDevice for MockBus {
+    fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.transfer(words)
+    }
+
+    fn is_clock_speed(&self) -> bool {
+        true
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        self.log.borrow_mut().push(Event::ClockSpeed(speed));
+        Ok(())
+    }
+
+    fn delay_us(&mut self, us: u32) -> Result {
+        self.log.borrow_mut().push(Event::Delay(us));
+        Ok(())
+    }
+}
+
+pub struct MockPin {
+    id: u8,
+    log: Log,
+    pub fail: bool,
+}
+
+impl MockPin {
+    pub fn new(id: u8, log: &Log) -> Self {
+        Self {
+            id,
+            log: log.clone(),
+            fail: false,
+        }
+    }
+}
+
+impl OutputPin for MockPin {
+    type Error = ();
+
+    fn set_low(&mut self) -> core::result::Result<(), ()> {
+        if self.fail {
+            return Err(());
+        }
+
+        self.log.borrow_mut().push(Event::Select(self.id));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), ()> {
+        self.log.borrow_mut().push(Event::Deselect(self.id));
+        Ok(())
+    }
+}
+
+pub struct MockDelay {
+    log: Log,
+}
+
+impl MockDelay {
+    pub fn new(log: &Log) -> Self {
+        Self { log: log.clone() }
+    }
+}
+
+impl DelayUs<u32> for MockDelay {
+    fn delay_us(&mut self, us: u32) {
+        self.log.borrow_mut().push(Event::Delay(us));
+    }
+}
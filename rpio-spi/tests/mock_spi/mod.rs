@@ -0,0 +1,131 @@
+use rpio_spi::{
+    BackgroundTransfer, Error, Lines, MockSpi, Phases, SpiDevice, Transaction, Transfer,
+};
+use std::boxed::Box;
+
+#[test]
+fn scripted() {
+    let mock = MockSpi::with_expectations(&[
+        Transaction::ClockSpeed(1_000_000),
+        Transaction::Select,
+        Transaction::transfer(&[0x9F, 0, 0], &[0, 0xBF, 0x26]),
+        Transaction::Deselect,
+    ]);
+    let mut spi = mock.clone();
+
+    spi.set_clock_speed(1_000_000).unwrap();
+    assert_eq!(spi.transfer(&mut [0x9F, 0, 0]), Ok(&[0, 0xBF, 0x26][..]));
+
+    mock.done();
+    assert_eq!(
+        mock.log(),
+        &[
+            Transaction::ClockSpeed(1_000_000),
+            Transaction::Select,
+            Transaction::transfer(&[0x9F, 0, 0], &[0, 0xBF, 0x26]),
+            Transaction::Deselect,
+        ]
+    );
+}
+
+#[test]
+fn unscripted() {
+    let mut spi = MockSpi::new();
+
+    assert_eq!(spi.transfer(&mut [1, 2]), Ok(&[1, 2][..]));
+    spi.delay_us(10).unwrap();
+
+    assert_eq!(
+        spi.log(),
+        &[
+            Transaction::Select,
+            Transaction::write(&[1, 2]),
+            Transaction::Deselect,
+            Transaction::DelayUs(10),
+        ]
+    );
+}
+
+#[test]
+fn injected_errors() {
+    let mut spi = MockSpi::new();
+    spi.expect(Transaction::Select)
+        .expect_err(Transaction::write(&[1]), Error::Transfer)
+        .expect(Transaction::Deselect)
+        .expect(Transaction::Select)
+        .expect_err(Transaction::write(&[2]), Error::Transfer)
+        .expect_err(Transaction::Deselect, Error::ChipDeselect)
+        .expect_err(Transaction::Select, Error::ChipSelect);
+
+    assert_eq!(spi.transfer(&mut [1]), Err(Error::Transfer));
+    assert_eq!(spi.transfer(&mut [2]), Err(Error::ChipDeselect));
+    assert_eq!(spi.transfer(&mut [3]), Err(Error::ChipSelect));
+    spi.done();
+}
+
+#[test]
+fn background_transfer() {
+    let mock = MockSpi::with_expectations(&[
+        Transaction::Select,
+        Transaction::transfer(&[1, 2], &[3, 4]),
+        Transaction::Deselect,
+    ]);
+    let mut spi = mock.clone();
+
+    spi.start_transfer(Box::leak(Box::new([1, 2]))).unwrap();
+
+    assert!(!spi.is_done());
+    assert_eq!(spi.raw_transfer(&mut [5]), Err(Error::Busy));
+    assert_eq!(spi.finish(), Ok(&mut [3, 4][..]));
+    assert!(spi.is_done());
+    mock.done();
+}
+
+#[test]
+fn multi_line_read() {
+    let phases = Phases {
+        address: Lines::Dual,
+        dummy_cycles: 4,
+        data: Lines::Dual,
+        ..Phases::default()
+    };
+    let mut spi = MockSpi::with_expectations(&[Transaction::multi_line_read(
+        phases,
+        0xBB,
+        &[0, 0, 0],
+        &[1, 2],
+    )]);
+    let mut words = [0; 2];
+
+    assert_eq!(
+        spi.read_multi_line(phases, 0xBB, &[0, 0, 0], &mut words),
+        Err(Error::NotImplemented)
+    );
+
+    spi.set_max_lines(Lines::Dual);
+    spi.read_multi_line(phases, 0xBB, &[0, 0, 0], &mut words)
+        .unwrap();
+    assert_eq!(words, [1, 2]);
+    spi.done();
+}
+
+#[test]
+#[should_panic(expected = "transaction 1 mismatch")]
+fn mismatch() {
+    let mut spi = MockSpi::with_expectations(&[
+        Transaction::Select,
+        Transaction::write(&[1]),
+        Transaction::Deselect,
+    ]);
+
+    spi.transfer(&mut [2]).ok();
+}
+
+#[test]
+#[should_panic(expected = "1 expected transactions not performed")]
+fn not_done() {
+    let mut spi = MockSpi::with_expectations(&[Transaction::Select, Transaction::Deselect]);
+
+    spi.select().unwrap();
+    spi.done();
+}
@@ -0,0 +1,139 @@
+use rpio_spi::{
+    Error, ErrorKind, MockSpi, Operation, Retry, RetryPolicy, SpiDevice, Transaction, Transfer,
+};
+use std::{cell::Cell, vec::Vec};
+
+#[derive(Default)]
+struct Delays(Vec<u32>);
+
+impl embedded_hal::blocking::delay::DelayUs<u32> for Delays {
+    fn delay_us(&mut self, us: u32) {
+        self.0.push(us);
+    }
+}
+
+#[test]
+fn transfer_retried() {
+    let mock = MockSpi::new();
+
+    for _ in 0..2 {
+        mock.expect(Transaction::Select)
+            .expect_err(Transaction::transfer(&[1, 2], &[3, 4]), Error::Transfer)
+            .expect(Transaction::Deselect);
+    }
+
+    mock.expect(Transaction::Select)
+        .expect(Transaction::transfer(&[1, 2], &[5, 6]))
+        .expect(Transaction::Deselect);
+
+    let mut spi: Retry<_, _, _> = Retry::new(mock.clone(), Delays::default(), || 0);
+
+    assert_eq!(spi.transfer(&mut [1, 2]), Ok(&[5, 6][..]));
+    assert_eq!(spi.retried(), 2);
+    assert_eq!(spi.into_inner().1 .0, [100, 200]);
+    mock.done();
+}
+
+#[test]
+fn retries_exhausted() {
+    let mock = MockSpi::new();
+
+    for _ in 0..3 {
+        mock.expect(Transaction::Select)
+            .expect_err(Transaction::write(&[1]), Error::Transfer)
+            .expect(Transaction::Deselect);
+    }
+
+    let mut spi: Retry<_, _, _> =
+        Retry::new(mock.clone(), Delays::default(), || 0).with_policy(RetryPolicy {
+            retries: 2,
+            backoff_us: 1_000,
+            max_backoff_us: 1_500,
+            ..RetryPolicy::default()
+        });
+
+    assert_eq!(spi.transfer(&mut [1]), Err(Error::Transfer));
+    assert_eq!(spi.into_inner().1 .0, [1_000, 1_500]);
+    mock.done();
+}
+
+#[test]
+fn not_transient() {
+    let mock = MockSpi::new();
+    mock.expect_err(Transaction::ClockSpeed(1_000), Error::ClockSpeed);
+
+    let mut spi: Retry<_, _, _> = Retry::new(mock.clone(), Delays::default(), || 0);
+
+    assert_eq!(spi.set_clock_speed(1_000), Err(Error::ClockSpeed));
+    assert_eq!(spi.retried(), 0);
+    mock.done();
+}
+
+#[test]
+fn deadline() {
+    let mock = MockSpi::new();
+
+    for _ in 0..2 {
+        mock.expect(Transaction::Select)
+            .expect_err(Transaction::write(&[1]), Error::Transfer)
+            .expect(Transaction::Deselect);
+    }
+
+    let now = Cell::new(0);
+    let mut spi: Retry<_, _, _> = Retry::new(mock.clone(), Delays::default(), || {
+        now.set(now.get() + 150);
+        now.get()
+    })
+    .with_policy(RetryPolicy {
+        deadline_us: Some(400),
+        ..RetryPolicy::default()
+    });
+
+    assert_eq!(spi.transfer(&mut [1]), Err(Error::Timeout));
+    assert_eq!(spi.retried(), 1);
+    mock.done();
+}
+
+#[test]
+fn long_transfer_not_retried() {
+    let mock = MockSpi::new();
+
+    mock.expect(Transaction::Select)
+        .expect_err(Transaction::write(&[1, 2, 3]), Error::Transfer)
+        .expect(Transaction::Deselect);
+
+    let mut spi: Retry<_, _, _, 2> = Retry::new(mock.clone(), Delays::default(), || 0);
+
+    assert_eq!(spi.max_retried_len(), 2);
+    assert_eq!(spi.transfer(&mut [1, 2, 3]), Err(Error::Transfer));
+    assert_eq!(spi.retried(), 0);
+    mock.done();
+}
+
+#[test]
+fn transaction_with_transfer_not_retried() {
+    let mock = MockSpi::new();
+    mock.expect(Transaction::Select)
+        .expect_err(Transaction::write(&[1]), Error::Transfer)
+        .expect(Transaction::Deselect);
+
+    let mut spi: Retry<_, _, _> = Retry::new(mock.clone(), Delays::default(), || 0);
+    let mut words = [1];
+
+    assert_eq!(
+        spi.transaction(&mut [Operation::Transfer(&mut words)])
+            .map_err(|err| err.kind()),
+        Err(ErrorKind::Transfer)
+    );
+    mock.done();
+}
+
+#[test]
+fn delay_us() {
+    let mock = MockSpi::with_expectations(&[]);
+    let mut spi: Retry<_, _, _> = Retry::new(mock.clone(), Delays::default(), || 0);
+
+    spi.delay_us(10).unwrap();
+    assert_eq!(spi.into_inner().1 .0, [10]);
+    mock.done();
+}
@@ -0,0 +1,191 @@
+use crate::mock::{Event, Log, MockPin};
+use _rppal::spi::{BitOrder, Mode, Segment};
+use rpio_spi::{Error, FirstBit, Operation, Result, RppalBus, Spi, SpiDevice, Transfer, MODE_3};
+use std::{cell::RefCell, rc::Rc, vec, vec::Vec};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Call {
+    Transfer(Vec<u8>),
+    /// The length and delay of each segment.
+    Segments(Vec<(u32, u16)>),
+    ClockSpeed(u32),
+    Mode(Mode),
+    BitsPerWord(u8),
+    BitOrder(BitOrder),
+}
+
+#[derive(Clone, Default)]
+struct MockRppal {
+    calls: Rc<RefCell<Vec<Call>>>,
+    /// Log transfers here too, to order them with chip select.
+    log: Option<Log>,
+    fail: bool,
+}
+
+impl MockRppal {
+    fn with_log(log: &Log) -> Self {
+        Self {
+            log: Some(log.clone()),
+            ..Self::default()
+        }
+    }
+
+    fn calls(&self) -> Vec<Call> {
+        self.calls.borrow().clone()
+    }
+
+    fn call(&mut self, call: Call) -> Result {
+        self.calls.borrow_mut().push(call);
+
+        match self.fail {
+            true => Err(Error::Transfer),
+            false => Ok(()),
+        }
+    }
+}
+
+impl RppalBus for MockRppal {
+    fn transfer(&mut self, words: &mut [u8]) -> Result {
+        if let Some(log) = &self.log {
+            log.borrow_mut()
+                .extend(words.iter().map(|&word| Event::Transfer(word)));
+        }
+
+        self.call(Call::Transfer(words.to_vec()))?;
+        words.reverse();
+        Ok(())
+    }
+
+    fn transfer_segments(&mut self, segments: &[Segment<'_, '_>]) -> Result {
+        let segments = segments
+            .iter()
+            .map(|segment| (segment.len(), segment.delay()))
+            .collect();
+
+        self.call(Call::Segments(segments))
+    }
+
+    fn set_clock_speed(&mut self, speed: u32) -> Result {
+        self.call(Call::ClockSpeed(speed))
+    }
+
+    fn set_mode(&mut self, mode: Mode) -> Result {
+        self.call(Call::Mode(mode))
+    }
+
+    fn set_bits_per_word(&mut self, bits: u8) -> Result {
+        self.call(Call::BitsPerWord(bits))
+    }
+
+    fn set_bit_order(&mut self, order: BitOrder) -> Result {
+        self.call(Call::BitOrder(order))
+    }
+}
+
+#[test]
+fn from_rppal() {
+    let bus = MockRppal::default();
+    let mut spi = Spi::from_rppal(bus.clone()).init();
+
+    assert!(!spi.is_chip_select());
+    assert_eq!(spi.transfer(&mut [1, 2, 3]), Ok(&[3, 2, 1][..]));
+    assert_eq!(bus.calls(), [Call::Transfer(vec![1, 2, 3])]);
+}
+
+#[test]
+fn settings() {
+    let bus = MockRppal::default();
+    let mut spi = Spi::from_rppal(bus.clone()).init();
+
+    spi.set_clock_speed(1_000_000).unwrap();
+    spi.set_mode(MODE_3).unwrap();
+    spi.set_word_size(9).unwrap();
+    spi.set_bit_order(FirstBit::Lsb).unwrap();
+
+    assert_eq!(
+        bus.calls(),
+        [
+            Call::ClockSpeed(1_000_000),
+            Call::Mode(Mode::Mode3),
+            Call::BitsPerWord(9),
+            Call::BitOrder(BitOrder::LsbFirst),
+        ]
+    );
+}
+
+#[test]
+fn transaction() {
+    let bus = MockRppal::default();
+    let mut spi = Spi::from_rppal(bus.clone()).init();
+    let mut read = [0; 2];
+    let mut transfer = [0; 3];
+
+    spi.transaction(&mut [
+        Operation::Write(&[1]),
+        Operation::Read(&mut read),
+        Operation::DelayUs(70_000),
+        Operation::Transfer(&mut transfer),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        bus.calls(),
+        [Call::Segments(vec![
+            (1, 0),
+            (2, 0),
+            (0, u16::MAX),
+            (0, 4_465),
+            (3, 0),
+        ])]
+    );
+}
+
+#[test]
+fn chip_select() {
+    let log = Log::default();
+    let bus = MockRppal::with_log(&log);
+    let mut spi = Spi::from_rppal(bus.clone())
+        .with_cs_pin(MockPin::new(1, &log))
+        .init();
+
+    assert!(spi.is_chip_select());
+    log.borrow_mut().clear();
+
+    assert_eq!(spi.transfer(&mut [1, 2]), Ok(&[2, 1][..]));
+    spi.transaction(&mut [Operation::Write(&[3])]).unwrap();
+
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::Select(1),
+            Event::Transfer(1),
+            Event::Transfer(2),
+            Event::Deselect(1),
+            Event::Select(1),
+            Event::Deselect(1),
+        ]
+    );
+    assert_eq!(
+        bus.calls(),
+        [Call::Transfer(vec![1, 2]), Call::Segments(vec![(1, 0)])]
+    );
+}
+
+#[test]
+fn errors() {
+    let log = Log::default();
+    let bus = MockRppal {
+        fail: true,
+        ..MockRppal::with_log(&log)
+    };
+    let mut spi = Spi::from_rppal(bus)
+        .with_cs_pin(MockPin::new(1, &log))
+        .init();
+    log.borrow_mut().clear();
+
+    assert_eq!(spi.transfer(&mut [1]), Err(Error::Transfer));
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[Event::Select(1), Event::Transfer(1), Event::Deselect(1)]
+    );
+}
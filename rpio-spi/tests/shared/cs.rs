@@ -0,0 +1,194 @@
+use crate::mock::{Event, Log, MockBus, MockPin};
+use rpio_spi::{Error, Operation, RefCellBus, SharedBus, Spi, SpiDevice, Transfer};
+
+#[test]
+fn shared() {
+    let log = Log::default();
+    let bus: RefCellBus<_> = SharedBus::new(MockBus::new(&log));
+
+    let mut dev1 = Spi::from_shared(&bus)
+        .with_cs(MockPin::new(1, &log))
+        .with_clock_speed(1_000_000)
+        .init();
+
+    let mut dev2 = Spi::from_shared(&bus).with_cs(MockPin::new(2, &log)).init();
+
+    assert!(dev1.is_chip_select());
+    assert!(dev1.is_clock_speed());
+
+    log.borrow_mut().clear();
+
+    assert_eq!(dev1.transfer(&mut [1, 2]), Ok(&[1, 2][..]));
+    assert_eq!(dev2.transfer(&mut [3]), Ok(&[3][..]));
+
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::ClockSpeed(1_000_000),
+            Event::Select(1),
+            Event::Transfer(1),
+            Event::Transfer(2),
+            Event::Deselect(1),
+            Event::Select(2),
+            Event::Transfer(3),
+            Event::Deselect(2),
+        ]
+    );
+
+    log.borrow_mut().clear();
+    dev2.set_clock_speed(500_000).unwrap();
+    dev2.transfer(&mut [4]).unwrap();
+
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::ClockSpeed(500_000),
+            Event::Select(2),
+            Event::Transfer(4),
+            Event::Deselect(2),
+        ]
+    );
+}
+
+#[test]
+fn shared_error() {
+    let log = Log::default();
+    let bus: RefCellBus<_> = SharedBus::new(MockBus::new(&log));
+    let mut dev = Spi::from_shared(&bus).with_cs(MockPin::new(1, &log)).init();
+
+    bus.lock(|spi| spi.fail = true);
+    log.borrow_mut().clear();
+
+    assert_eq!(dev.transfer(&mut [1]), Err(Error::Transfer));
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[Event::Select(1), Event::Deselect(1)]
+    );
+}
+
+#[test]
+fn shared_transaction() {
+    let log = Log::default();
+    let bus: RefCellBus<_> = SharedBus::new(MockBus::new(&log));
+    let mut dev = Spi::from_shared(&bus).with_cs(MockPin::new(1, &log)).init();
+
+    let mut read = [0xFF; 2];
+    log.borrow_mut().clear();
+
+    dev.transaction(&mut [Operation::Write(&[5, 6]), Operation::Read(&mut read)])
+        .unwrap();
+
+    assert_eq!(read, [0, 0]);
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::Select(1),
+            Event::Transfer(5),
+            Event::Transfer(6),
+            Event::Transfer(0),
+            Event::Transfer(0),
+            Event::Deselect(1),
+        ]
+    );
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn shared_settings() {
+    use rpio_spi::{FirstBit, MockSpi, Transaction, MODE_0, MODE_3};
+
+    let log = Log::default();
+    let mock = MockSpi::new();
+    let bus: RefCellBus<_> = SharedBus::new(mock.clone());
+
+    let mut dev1 = Spi::from_shared(&bus)
+        .with_cs(MockPin::new(1, &log))
+        .with_mode(MODE_3)
+        .with_word_size(8)
+        .init();
+
+    let mut dev2 = Spi::from_shared(&bus)
+        .with_cs(MockPin::new(2, &log))
+        .with_bit_order(FirstBit::Lsb)
+        .init();
+
+    dev1.transfer(&mut [1]).unwrap();
+    dev2.set_mode(MODE_0).unwrap();
+    dev2.transfer(&mut [2]).unwrap();
+
+    assert_eq!(
+        mock.log(),
+        &[
+            Transaction::Mode(3),
+            Transaction::WordSize(8),
+            Transaction::Select,
+            Transaction::write(&[1]),
+            Transaction::Deselect,
+            Transaction::Mode(0),
+            Transaction::BitOrder(FirstBit::Lsb),
+            Transaction::Select,
+            Transaction::write(&[2]),
+            Transaction::Deselect,
+        ]
+    );
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn shared_bus_settings() {
+    use rpio_spi::{MockSpi, Transaction, MODE_0, MODE_3};
+
+    let log = Log::default();
+    let mock = MockSpi::new();
+    let bus: RefCellBus<_> = SharedBus::new(mock.clone()).with_mode(MODE_0);
+
+    let mut dev1 = Spi::from_shared(&bus)
+        .with_cs(MockPin::new(1, &log))
+        .with_mode(MODE_3)
+        .init();
+
+    let mut dev2 = Spi::from_shared(&bus).with_cs(MockPin::new(2, &log)).init();
+
+    dev1.transfer(&mut [1]).unwrap();
+    dev2.transfer(&mut [2]).unwrap();
+
+    assert_eq!(
+        mock.log(),
+        &[
+            Transaction::Mode(3),
+            Transaction::Select,
+            Transaction::write(&[1]),
+            Transaction::Deselect,
+            Transaction::Mode(0),
+            Transaction::Select,
+            Transaction::write(&[2]),
+            Transaction::Deselect,
+        ]
+    );
+}
+
+#[test]
+fn shared_cs_config() {
+    let log = Log::default();
+    let bus: RefCellBus<_> = SharedBus::new(MockBus::new(&log));
+
+    let mut dev = Spi::from_shared(&bus)
+        .with_cs(MockPin::new(1, &log))
+        .with_setup_us(1)
+        .with_hold_us(2)
+        .init();
+
+    log.borrow_mut().clear();
+    dev.transfer(&mut [1]).unwrap();
+
+    assert_eq!(
+        log.borrow().as_slice(),
+        &[
+            Event::Select(1),
+            Event::Delay(1),
+            Event::Transfer(1),
+            Event::Delay(2),
+            Event::Deselect(1),
+        ]
+    );
+}
@@ -0,0 +1,61 @@
+use rpio_spi::{SlaveQueue, MAX_TRANSACTIONS};
+
+#[test]
+fn transactions() {
+    let mut queue = SlaveQueue::<8>::new();
+    let mut words = [0; 4];
+
+    queue.end_transaction();
+    assert_eq!(queue.transactions(), 0);
+
+    for byte in [0x9F, 0, 0] {
+        queue.push(byte);
+    }
+    assert_eq!(queue.pop(&mut words), None);
+
+    queue.end_transaction();
+    queue.push(0x05);
+    queue.push(0);
+    queue.end_transaction();
+    assert_eq!(queue.transactions(), 2);
+
+    assert_eq!(queue.pop(&mut words), Some(3));
+    assert_eq!(words, [0x9F, 0, 0, 0]);
+    assert_eq!(queue.pop(&mut words[..1]), Some(2));
+    assert_eq!(words, [0x05, 0, 0, 0]);
+    assert_eq!(queue.pop(&mut words), None);
+    assert!(!queue.take_dropped());
+}
+
+#[test]
+fn overflow() {
+    let mut queue = SlaveQueue::<4>::new();
+    let mut words = [0; 8];
+
+    for byte in 1..=6 {
+        queue.push(byte);
+    }
+    queue.end_transaction();
+    assert!(queue.take_dropped());
+    assert!(!queue.take_dropped());
+
+    assert_eq!(queue.pop(&mut words), Some(4));
+    assert_eq!(words[..4], [1, 2, 3, 4]);
+
+    // Wraps around the end of the buffer
+    queue.push(7);
+    queue.push(8);
+    queue.push(9);
+    queue.end_transaction();
+    assert_eq!(queue.pop(&mut words), Some(3));
+    assert_eq!(words[..3], [7, 8, 9]);
+
+    let mut queue = SlaveQueue::<64>::new();
+
+    for byte in 0..=MAX_TRANSACTIONS as u8 {
+        queue.push(byte);
+        queue.end_transaction();
+    }
+    assert_eq!(queue.transactions(), MAX_TRANSACTIONS);
+    assert!(queue.take_dropped());
+}
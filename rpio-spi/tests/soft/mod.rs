@@ -0,0 +1,144 @@
+use crate::mock::{Event, Log, MockDelay, MockPin};
+use embedded_hal::digital::v2::InputPin;
+use rpio_spi::{FirstBit, OutputPin, Spi, SpiDevice, Transfer, MODE_0, MODE_1, MODE_2, MODE_3};
+use std::{cell::RefCell, rc::Rc, vec::Vec};
+
+/// The levels of the clock and data lines, sampling MOSI whenever the clock
+/// reaches `capture`.
+#[derive(Default)]
+struct Wire {
+    sck: bool,
+    mosi: bool,
+    capture: bool,
+    sampled: Vec<bool>,
+}
+
+type Shared = Rc<RefCell<Wire>>;
+
+struct Sck(Shared);
+
+impl OutputPin for Sck {
+    type Error = ();
+
+    fn set_low(&mut self) -> core::result::Result<(), ()> {
+        let mut wire = self.0.borrow_mut();
+
+        if wire.sck && !wire.capture {
+            let mosi = wire.mosi;
+            wire.sampled.push(mosi);
+        }
+
+        wire.sck = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), ()> {
+        let mut wire = self.0.borrow_mut();
+
+        if !wire.sck && wire.capture {
+            let mosi = wire.mosi;
+            wire.sampled.push(mosi);
+        }
+
+        wire.sck = true;
+        Ok(())
+    }
+}
+
+struct Mosi(Shared);
+
+impl OutputPin for Mosi {
+    type Error = ();
+
+    fn set_low(&mut self) -> core::result::Result<(), ()> {
+        self.0.borrow_mut().mosi = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), ()> {
+        self.0.borrow_mut().mosi = true;
+        Ok(())
+    }
+}
+
+/// Loops MOSI back to MISO.
+struct Miso(Shared);
+
+impl InputPin for Miso {
+    type Error = ();
+
+    fn is_high(&self) -> core::result::Result<bool, ()> {
+        Ok(self.0.borrow().mosi)
+    }
+
+    fn is_low(&self) -> core::result::Result<bool, ()> {
+        Ok(!self.0.borrow().mosi)
+    }
+}
+
+fn bits(words: &[u8]) -> Vec<bool> {
+    words
+        .iter()
+        .flat_map(|word| (0..8).rev().map(move |bit| word & (1 << bit) != 0))
+        .collect()
+}
+
+#[test]
+fn loopback() {
+    let modes = [
+        (MODE_0, false, true),
+        (MODE_1, false, false),
+        (MODE_2, true, false),
+        (MODE_3, true, true),
+    ];
+
+    for (mode, idle, capture) in modes {
+        let log = Log::default();
+        let wire = Shared::default();
+        wire.borrow_mut().sck = idle;
+        wire.borrow_mut().capture = capture;
+
+        let mut spi = Spi::from_soft(
+            Sck(wire.clone()),
+            Mosi(wire.clone()),
+            Miso(wire.clone()),
+            MockDelay::new(&log),
+        )
+        .with_mode(mode)
+        .init();
+
+        assert_eq!(spi.transfer(&mut [0xa5, 0x3c]).unwrap(), &[0xa5, 0x3c]);
+        assert_eq!(wire.borrow().sck, idle);
+        assert_eq!(wire.borrow().sampled, bits(&[0xa5, 0x3c]));
+        assert_eq!(log.borrow().len(), 32);
+    }
+}
+
+#[test]
+fn lsb_first() {
+    let log = Log::default();
+    let wire = Shared::default();
+    wire.borrow_mut().capture = true;
+
+    let mut spi = Spi::from_soft(
+        Sck(wire.clone()),
+        Mosi(wire.clone()),
+        Miso(wire.clone()),
+        MockDelay::new(&log),
+    )
+    .with_bit_order(FirstBit::Lsb)
+    .with_half_period_us(0)
+    .with_cs(MockPin::new(0, &log))
+    .init();
+
+    assert_eq!(spi.transfer(&mut [0x01]).unwrap(), &[0x01]);
+    assert_eq!(wire.borrow().sampled, bits(&[0x80]));
+    assert_eq!(
+        *log.borrow(),
+        [Event::Deselect(0), Event::Select(0), Event::Deselect(0)]
+    );
+
+    spi.set_clock_speed(100_000).unwrap();
+    spi.transfer(&mut [0x00]).unwrap();
+    assert!(log.borrow().contains(&Event::Delay(5)));
+}
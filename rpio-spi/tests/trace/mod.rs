@@ -0,0 +1,193 @@
+use rpio_spi::{
+    Entry, Error, MockSpi, Operation, Record, Result, RingBuffer, SpiDevice, Traced, Transaction,
+    Transfer, WriteSink,
+};
+use std::{cell::Cell, rc::Rc, string::String};
+
+fn jedec(frame_start: bool, mosi: &[u8]) -> Option<&'static str> {
+    match (frame_start, mosi.first()) {
+        (true, Some(0x9F)) => Some("ReadJedecId"),
+        _ => None,
+    }
+}
+
+#[test]
+fn ring_buffer() {
+    let mock = MockSpi::with_expectations(&[
+        Transaction::ClockSpeed(1_000_000),
+        Transaction::Select,
+        Transaction::transfer(&[0x9F, 0, 0], &[0, 0xBF, 0x26]),
+        Transaction::Deselect,
+    ]);
+    let time = Rc::new(Cell::new(0));
+    let clock = {
+        let time = time.clone();
+        move || {
+            time.set(time.get() + 10);
+            time.get()
+        }
+    };
+    let mut spi = Traced::new(mock.clone(), RingBuffer::<16>::new(), clock).with_decoder(jedec);
+
+    spi.set_clock_speed(1_000_000).unwrap();
+    assert_eq!(spi.transfer(&mut [0x9F, 0, 0]), Ok(&[0, 0xBF, 0x26][..]));
+    mock.done();
+
+    let records: Vec<Record> = spi.sink().iter().copied().collect();
+    let byte = |start, mosi, miso| Entry::Byte { start, mosi, miso };
+
+    assert_eq!(
+        records,
+        [
+            Record {
+                time_us: 10,
+                entry: Entry::ClockSpeed(1_000_000)
+            },
+            Record {
+                time_us: 20,
+                entry: Entry::Select
+            },
+            Record {
+                time_us: 30,
+                entry: Entry::Note("ReadJedecId")
+            },
+            Record {
+                time_us: 40,
+                entry: byte(true, 0x9F, 0)
+            },
+            Record {
+                time_us: 40,
+                entry: byte(false, 0, 0xBF)
+            },
+            Record {
+                time_us: 40,
+                entry: byte(false, 0, 0x26)
+            },
+            Record {
+                time_us: 60,
+                entry: Entry::Deselect
+            },
+        ]
+    );
+
+    let mut text = String::new();
+    spi.sink().write_text(&mut text).unwrap();
+    assert_eq!(
+        text,
+        "[        10 us] clock 1000000 Hz\n\
+         [        20 us] select\n\
+         [        30 us] note ReadJedecId\n\
+         [        40 us] transfer 9f:00 00:bf 00:26\n\
+         [        60 us] deselect\n"
+    );
+
+    let mut vcd = String::new();
+    spi.sink().write_vcd(&mut vcd).unwrap();
+    assert!(vcd.starts_with("$timescale 1 ns $end\n"));
+    assert!(vcd.contains("#20000\n0c\n"));
+    assert!(vcd.contains("#40000\n1o\n0i\n#40500\n1k\n#41000\n0k\n"));
+    assert!(vcd.ends_with("#64002\n0k\n#64003\n1c\n"));
+}
+
+/// Leaves chip select to the peripheral, which answers every byte with its
+/// complement.
+struct Inverter;
+
+impl Transfer<u8> for Inverter {
+    type Error = Error;
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        words.iter_mut().for_each(|word| *word = !*word);
+        Ok(words)
+    }
+}
+
+impl SpiDevice for Inverter {
+    fn raw_transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8]> {
+        self.transfer(words)
+    }
+
+    fn transaction(&mut self, operations: &mut [Operation<'_>]) -> Result {
+        operations
+            .iter_mut()
+            .try_for_each(|operation| self.raw_operation(operation))
+    }
+}
+
+#[test]
+fn transaction_without_chip_select() {
+    let mut spi = Traced::new(Inverter, RingBuffer::<16>::new(), || 0).with_decoder(jedec);
+    let mut read = [0; 2];
+    let mut transfer = [1, 2];
+
+    // A transaction which fails records only what was sent.
+    spi.transaction(&mut [
+        Operation::Write(&[0x9F]),
+        Operation::Read(&mut read),
+        Operation::DelayUs(10),
+        Operation::Transfer(&mut transfer),
+    ])
+    .unwrap_err();
+
+    spi.transaction(&mut [
+        Operation::Write(&[0x9F]),
+        Operation::Read(&mut read),
+        Operation::Transfer(&mut transfer),
+    ])
+    .unwrap();
+
+    let mut text = String::new();
+    spi.sink().write_text(&mut text).unwrap();
+    assert_eq!(
+        text,
+        "[         0 us] note ReadJedecId\n\
+         [         0 us] transfer 9f:00\n\
+         [         0 us] transfer 00:00 00:00\n\
+         [         0 us] transfer 01:00 02:00\n\
+         [         0 us] note error\n\
+         [         0 us] note ReadJedecId\n\
+         [         0 us] transfer 9f:00\n\
+         [         0 us] transfer 00:ff 00:ff\n\
+         [         0 us] transfer 01:fe 02:fd\n"
+    );
+}
+
+#[test]
+fn ring_buffer_overflow() {
+    let mut spi = Traced::new(MockSpi::new(), RingBuffer::<2>::new(), || 0);
+
+    spi.transfer(&mut [1, 2, 3]).unwrap();
+
+    let records: Vec<Entry> = spi.sink().iter().map(|record| record.entry).collect();
+    assert_eq!(
+        records,
+        [
+            Entry::Byte {
+                start: false,
+                mosi: 3,
+                miso: 3
+            },
+            Entry::Deselect,
+        ]
+    );
+    assert_eq!(spi.sink().dropped(), 3);
+}
+
+#[test]
+fn annotate() {
+    let mut spi = Traced::new(MockSpi::new(), WriteSink::text(Vec::new()), || 5);
+
+    spi.annotate("DisplayOn");
+    spi.transfer(&mut [0xAF]).unwrap();
+
+    let (_, sink) = spi.into_inner();
+    let text = String::from_utf8(sink.finish().unwrap()).unwrap();
+
+    assert_eq!(
+        text,
+        "[         5 us] note DisplayOn\n\
+         [         5 us] select\n\
+         [         5 us] transfer af:af\n\
+         [         5 us] deselect\n"
+    );
+}